@@ -0,0 +1,746 @@
+use crate::backend::DrawTarget;
+use crate::hud::Hud;
+use crate::input::{Event, Key};
+use crate::map::GameMap;
+use crate::math::{Bam, Fixed, ANG90, FRACBITS, FRACUNIT};
+use crate::player::Player;
+use crate::wad::Linedef;
+
+// am_map.c's colors.
+const REDS: u8 = 176;
+const GRAYS: u8 = 96;
+const BROWNS: u8 = 64;
+const YELLOWS: u8 = 231;
+const WHITE: u8 = 209;
+const BACKGROUND: u8 = 0;
+const WALL_COLORS: u8 = REDS;
+const WALL_RANGE: u8 = 16;
+const FLOOR_STEP_COLORS: u8 = BROWNS;
+const CEILING_STEP_COLORS: u8 = YELLOWS;
+const PLAYER_COLORS: u8 = WHITE;
+const GRID_COLORS: u8 = GRAYS + 8;
+const CROSSHAIR_COLORS: u8 = GRAYS;
+
+const START_KEY: Key = Key::Tab;
+const END_KEY: Key = Key::Tab;
+const ZOOM_IN_KEY: Key = Key::Char('=');
+const ZOOM_OUT_KEY: Key = Key::Char('-');
+const GO_BIG_KEY: Key = Key::Char('0');
+const FOLLOW_KEY: Key = Key::Char('f');
+const GRID_KEY: Key = Key::Char('g');
+// Not in vanilla, which never rotates; this is the key the ports settled on.
+const ROTATE_KEY: Key = Key::Char('r');
+
+// d_englsh.h
+const FOLLOW_ON: &str = "Follow Mode ON";
+const FOLLOW_OFF: &str = "Follow Mode OFF";
+const GRID_ON: &str = "Grid ON";
+const GRID_OFF: &str = "Grid OFF";
+const ROTATE_ON: &str = "Rotate Mode ON";
+const ROTATE_OFF: &str = "Rotate Mode OFF";
+
+// F_PANINC: how far a held arrow pans each tic, in 320x200 pixels.
+const PAN_STEP: i32 = 4;
+// M_ZOOMIN and M_ZOOMOUT, (int)(1.02*FRACUNIT) and (int)(FRACUNIT/1.02): the zoom each
+// tic a key is held.
+const ZOOM_IN: Fixed = Fixed(66846);
+const ZOOM_OUT: Fixed = Fixed(64250);
+// INITSCALEMTOF's 0.7: a level starts zoomed in from the whole map.
+const INITIAL_ZOOM_OUT: Fixed = Fixed(45875);
+
+const PLAYER_RADIUS: i32 = 16 * FRACUNIT;
+const MAP_BLOCK_UNITS: i64 = 128 << FRACBITS;
+
+// player_arrow, in map units with the arrow pointing east.
+const R: i32 = 8 * PLAYER_RADIUS / 7;
+const PLAYER_ARROW: [((i32, i32), (i32, i32)); 7] = [
+    ((-R + R / 8, 0), (R, 0)),
+    ((R, 0), (R - R / 2, R / 4)),
+    ((R, 0), (R - R / 2, -R / 4)),
+    ((-R + R / 8, 0), (-R - R / 8, R / 4)),
+    ((-R + R / 8, 0), (-R - R / 8, -R / 4)),
+    ((-R + 3 * R / 8, 0), (-R + R / 8, R / 4)),
+    ((-R + 3 * R / 8, 0), (-R + R / 8, -R / 4))
+];
+
+// A point on the map, 16.16 like fixed_t but wide enough that differences across the
+// whole map and products with the scale don't overflow.
+type MapPoint = (i64, i64);
+
+// am_map.c: a camera over the map with its own zoom and position, following the player
+// or panned by hand.
+pub struct Automap {
+    active: bool,
+    follow: bool,
+    grid: bool,
+    rotate: bool,
+    // bigstate: zoomed out to the whole map, with the old window saved.
+    big: bool,
+    // f_w and f_h, and how many pixels make one of vanilla's.
+    frame_width: i32,
+    frame_height: i32,
+    pixel_scale: i32,
+    // lastlevel: the map the scales were worked out for.
+    level: Option<String>,
+    // The window on the map: m_x, m_y, m_w and m_h.
+    x: Fixed,
+    y: Fixed,
+    width: Fixed,
+    height: Fixed,
+    saved: (Fixed, Fixed, Fixed, Fixed),
+    // min_x to max_y: the map's extent.
+    min: (Fixed, Fixed),
+    max: (Fixed, Fixed),
+    scale_mtof: Fixed,
+    scale_ftom: Fixed,
+    min_scale_mtof: Fixed,
+    max_scale_mtof: Fixed,
+    // m_paninc, and mtof_zoommul and ftom_zoommul.
+    pan: (Fixed, Fixed),
+    zoom: (Fixed, Fixed),
+    // f_oldloc: where the player was when the window last followed them.
+    old_location: Option<(i16, i16)>
+}
+
+impl Default for Automap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Automap {
+    pub fn new() -> Self {
+        Self {
+            active: false,
+            follow: true,
+            grid: false,
+            rotate: false,
+            big: false,
+            frame_width: 0,
+            frame_height: 0,
+            pixel_scale: 1,
+            level: None,
+            x: Fixed::ZERO,
+            y: Fixed::ZERO,
+            width: Fixed::ZERO,
+            height: Fixed::ZERO,
+            saved: (Fixed::ZERO, Fixed::ZERO, Fixed::ZERO, Fixed::ZERO),
+            min: (Fixed::ZERO, Fixed::ZERO),
+            max: (Fixed::ZERO, Fixed::ZERO),
+            scale_mtof: Fixed::ONE,
+            scale_ftom: Fixed::ONE,
+            min_scale_mtof: Fixed::ONE,
+            max_scale_mtof: Fixed::ONE,
+            pan: (Fixed::ZERO, Fixed::ZERO),
+            zoom: (Fixed::ONE, Fixed::ONE),
+            old_location: None
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn is_following(&self) -> bool {
+        self.follow
+    }
+
+    pub fn shows_grid(&self) -> bool {
+        self.grid
+    }
+
+    pub fn is_rotating(&self) -> bool {
+        self.rotate
+    }
+
+    // finit_width and finit_height: the screen pixels the map is drawn in. A new size takes
+    // the scales from the top on the next start.
+    pub fn set_frame(&mut self, width: i32, height: i32, pixel_scale: i32) {
+        if (width, height, pixel_scale) != (self.frame_width, self.frame_height, self.pixel_scale) {
+            (self.frame_width, self.frame_height, self.pixel_scale) = (width, height, pixel_scale.max(1));
+            self.level = None;
+        }
+    }
+
+    // AM_Start
+    pub fn start(&mut self, map: &GameMap) {
+        if self.level.as_deref() != Some(map.map_name.as_str()) {
+            self.level_init(map);
+            self.level = Some(map.map_name.clone());
+        }
+
+        self.init_variables(&map.player);
+    }
+
+    // AM_Stop
+    pub fn stop(&mut self) {
+        self.active = false;
+    }
+
+    // AM_LevelInit: the smallest scale fits the whole map in the frame, the largest makes
+    // the frame two player widths high.
+    fn level_init(&mut self, map: &GameMap) {
+        self.find_min_max_boundaries(map);
+
+        let frame_width = Fixed::from_int(self.frame_width.max(1));
+        let frame_height = Fixed::from_int(self.frame_height.max(1));
+        let map_width = (self.max.0 - self.min.0).max(Fixed::ONE);
+        let map_height = (self.max.1 - self.min.1).max(Fixed::ONE);
+
+        self.min_scale_mtof = (frame_width / map_width).min(frame_height / map_height);
+        self.max_scale_mtof = frame_height / Fixed(2 * PLAYER_RADIUS);
+        self.scale_mtof = self.min_scale_mtof / INITIAL_ZOOM_OUT;
+
+        if self.scale_mtof > self.max_scale_mtof {
+            self.scale_mtof = self.min_scale_mtof;
+        }
+
+        self.scale_ftom = Fixed::ONE / self.scale_mtof;
+    }
+
+    // AM_findMinMaxBoundaries
+    fn find_min_max_boundaries(&mut self, map: &GameMap) {
+        let bounds = &map.bounds;
+
+        self.min = (Fixed::from(bounds.min_x), Fixed::from(bounds.min_y));
+        self.max = (Fixed::from(bounds.max_x), Fixed::from(bounds.max_y));
+    }
+
+    // AM_initVariables: the window centered on the player at the current scale.
+    fn init_variables(&mut self, player: &Player) {
+        self.active = true;
+        self.old_location = None;
+        self.pan = (Fixed::ZERO, Fixed::ZERO);
+        self.zoom = (Fixed::ONE, Fixed::ONE);
+
+        self.width = self.ftom(self.frame_width);
+        self.height = self.ftom(self.frame_height);
+        self.x = Fixed::from(player.position.x) - half(self.width);
+        self.y = Fixed::from(player.position.y) - half(self.height);
+        self.change_window_loc();
+
+        self.save_scale_and_loc();
+    }
+
+    // FTOM: frame pixels to map units.
+    fn ftom(&self, pixels: i32) -> Fixed {
+        Fixed(((((pixels as i64) << FRACBITS) * self.scale_ftom.0 as i64) >> FRACBITS) as i32)
+    }
+
+    // MTOF: map units to frame pixels.
+    fn mtof(&self, x: i64) -> i64 {
+        (x * self.scale_mtof.0 as i64) >> (2 * FRACBITS)
+    }
+
+    // AM_saveScaleAndLoc
+    fn save_scale_and_loc(&mut self) {
+        self.saved = (self.x, self.y, self.width, self.height);
+    }
+
+    // AM_restoreScaleAndLoc: following puts the old window back over the player.
+    fn restore_scale_and_loc(&mut self, player: &Player) {
+        let (x, y, width, height) = self.saved;
+
+        (self.width, self.height) = (width, height);
+
+        if self.follow {
+            self.x = Fixed::from(player.position.x) - half(self.width);
+            self.y = Fixed::from(player.position.y) - half(self.height);
+        } else {
+            (self.x, self.y) = (x, y);
+        }
+
+        self.scale_mtof = Fixed::from_int(self.frame_width) / self.width;
+        self.scale_ftom = Fixed::ONE / self.scale_mtof;
+    }
+
+    // AM_minOutWindowScale
+    fn min_out_window_scale(&mut self) {
+        self.scale_mtof = self.min_scale_mtof;
+        self.scale_ftom = Fixed::ONE / self.scale_mtof;
+        self.activate_new_scale();
+    }
+
+    // AM_activateNewScale: resizes the window about its center.
+    fn activate_new_scale(&mut self) {
+        let center = (self.x + half(self.width), self.y + half(self.height));
+
+        self.width = self.ftom(self.frame_width);
+        self.height = self.ftom(self.frame_height);
+        self.x = center.0 - half(self.width);
+        self.y = center.1 - half(self.height);
+    }
+
+    // AM_changeWindowLoc: pans, keeping the window's center on the map.
+    fn change_window_loc(&mut self) {
+        if self.pan != (Fixed::ZERO, Fixed::ZERO) {
+            self.follow = false;
+            self.old_location = None;
+        }
+
+        self.x += self.pan.0;
+        self.y += self.pan.1;
+
+        let center = (self.x + half(self.width), self.y + half(self.height));
+
+        if center.0 > self.max.0 {
+            self.x = self.max.0 - half(self.width);
+        } else if center.0 < self.min.0 {
+            self.x = self.min.0 - half(self.width);
+        }
+
+        if center.1 > self.max.1 {
+            self.y = self.max.1 - half(self.height);
+        } else if center.1 < self.min.1 {
+            self.y = self.min.1 - half(self.height);
+        }
+    }
+
+    // AM_changeWindowScale, clamped between the whole map and the closest zoom.
+    fn change_window_scale(&mut self) {
+        self.scale_mtof = self.scale_mtof * self.zoom.0;
+        self.scale_ftom = Fixed::ONE / self.scale_mtof;
+
+        if self.scale_mtof < self.min_scale_mtof {
+            self.min_out_window_scale();
+        } else if self.scale_mtof > self.max_scale_mtof {
+            self.scale_mtof = self.max_scale_mtof;
+            self.scale_ftom = Fixed::ONE / self.scale_mtof;
+            self.activate_new_scale();
+        } else {
+            self.activate_new_scale();
+        }
+    }
+
+    // AM_doFollowPlayer: snapped to whole pixels, so the walls don't shimmer as the
+    // player moves.
+    fn follow_player(&mut self, player: &Player) {
+        if self.old_location == Some((player.position.x, player.position.y)) {
+            return;
+        }
+
+        let x = Fixed::from(player.position.x).0 as i64;
+        let y = Fixed::from(player.position.y).0 as i64;
+
+        self.x = self.ftom(self.mtof(x) as i32) - half(self.width);
+        self.y = self.ftom(self.mtof(y) as i32) - half(self.height);
+        self.old_location = Some((player.position.x, player.position.y));
+    }
+
+    // AM_Responder: whether the automap took the event. Toggles are announced on the HUD.
+    pub fn responder(&mut self, event: Event, map: &GameMap, hud: &mut Hud) -> bool {
+        if !self.active {
+            if event == Event::KeyDown(START_KEY) {
+                self.start(map);
+                return true;
+            }
+
+            return false;
+        }
+
+        match event {
+            // Following, the arrows still turn and move the player.
+            Event::KeyDown(Key::Right | Key::Left | Key::Up | Key::Down) if self.follow => false,
+            Event::KeyDown(key @ (Key::Right | Key::Left | Key::Up | Key::Down)) => {
+                let step = self.ftom(PAN_STEP * self.pixel_scale);
+
+                match key {
+                    Key::Right => self.pan.0 = step,
+                    Key::Left => self.pan.0 = -step,
+                    Key::Up => self.pan.1 = step,
+                    _ => self.pan.1 = -step
+                }
+
+                true
+            },
+            Event::KeyDown(ZOOM_OUT_KEY) => {
+                self.zoom = (ZOOM_OUT, ZOOM_IN);
+                true
+            },
+            Event::KeyDown(ZOOM_IN_KEY) => {
+                self.zoom = (ZOOM_IN, ZOOM_OUT);
+                true
+            },
+            Event::KeyDown(END_KEY) => {
+                self.big = false;
+                self.stop();
+                true
+            },
+            Event::KeyDown(GO_BIG_KEY) => {
+                self.big = !self.big;
+
+                if self.big {
+                    self.save_scale_and_loc();
+                    self.min_out_window_scale();
+                } else {
+                    self.restore_scale_and_loc(&map.player);
+                }
+
+                true
+            },
+            Event::KeyDown(FOLLOW_KEY) => {
+                self.follow = !self.follow;
+                self.old_location = None;
+                hud.post_message(if self.follow { FOLLOW_ON } else { FOLLOW_OFF });
+                true
+            },
+            Event::KeyDown(GRID_KEY) => {
+                self.grid = !self.grid;
+                hud.post_message(if self.grid { GRID_ON } else { GRID_OFF });
+                true
+            },
+            Event::KeyDown(ROTATE_KEY) => {
+                self.rotate = !self.rotate;
+                hud.post_message(if self.rotate { ROTATE_ON } else { ROTATE_OFF });
+                true
+            },
+            Event::KeyUp(key) => {
+                match key {
+                    Key::Right | Key::Left if !self.follow => self.pan.0 = Fixed::ZERO,
+                    Key::Up | Key::Down if !self.follow => self.pan.1 = Fixed::ZERO,
+                    ZOOM_OUT_KEY | ZOOM_IN_KEY => self.zoom = (Fixed::ONE, Fixed::ONE),
+                    _ => {}
+                }
+
+                false
+            },
+            _ => false
+        }
+    }
+
+    // AM_Ticker
+    pub fn tick(&mut self, player: &Player) {
+        if !self.active {
+            return;
+        }
+
+        if self.follow {
+            self.follow_player(player);
+        }
+
+        if self.zoom.0 != Fixed::ONE {
+            self.change_window_scale();
+        }
+
+        if self.pan != (Fixed::ZERO, Fixed::ZERO) {
+            self.change_window_loc();
+        }
+    }
+
+    // AM_Drawer, into a target the size given to set_frame.
+    pub fn draw(&self, map: &GameMap, frame: &mut dyn DrawTarget) {
+        if !self.active {
+            return;
+        }
+
+        frame.clear(BACKGROUND);
+
+        // Rotating turns the map about the window's center so the player faces up.
+        let turn = self.rotate.then(|| ANG90 - map.player.angle);
+
+        if self.grid {
+            self.draw_grid(map, turn, frame);
+        }
+
+        self.draw_walls(map, turn, frame);
+        self.draw_player(&map.player, turn, frame);
+
+        // AM_drawCrosshair
+        frame.set_pixel(self.frame_width / 2, self.frame_height / 2, CROSSHAIR_COLORS);
+    }
+
+    fn center(&self) -> MapPoint {
+        ((self.x + half(self.width)).0 as i64, (self.y + half(self.height)).0 as i64)
+    }
+
+    // AM_drawGrid: the blockmap's squares. Turned, the window's corners reach past its
+    // edges, so the grid covers a square round the whole turning window instead.
+    fn draw_grid(&self, map: &GameMap, turn: Option<Bam>, frame: &mut dyn DrawTarget) {
+        let origin = (Fixed::from(map.blockmap_origin.x).0 as i64, Fixed::from(map.blockmap_origin.y).0 as i64);
+        let (mut left, mut bottom) = (self.x.0 as i64, self.y.0 as i64);
+        let (mut right, mut top) = (left + self.width.0 as i64, bottom + self.height.0 as i64);
+
+        if turn.is_some() {
+            let (center_x, center_y) = self.center();
+            let reach = (self.width.0 as i64 + self.height.0 as i64) / 2;
+
+            (left, bottom, right, top) = (center_x - reach, center_y - reach, center_x + reach, center_y + reach);
+        }
+
+        let first_line = |start: i64, origin: i64| start + (MAP_BLOCK_UNITS - (start - origin).rem_euclid(MAP_BLOCK_UNITS)) % MAP_BLOCK_UNITS;
+
+        for x in (first_line(left, origin.0)..right).step_by(MAP_BLOCK_UNITS as usize) {
+            self.draw_mline((x, bottom), (x, top), GRID_COLORS, turn, frame);
+        }
+
+        for y in (first_line(bottom, origin.1)..top).step_by(MAP_BLOCK_UNITS as usize) {
+            self.draw_mline((left, y), (right, y), GRID_COLORS, turn, frame);
+        }
+    }
+
+    // AM_drawWalls without the cheats. Every line counts as mapped, as nothing records which
+    // lines the player has seen yet.
+    fn draw_walls(&self, map: &GameMap, turn: Option<Bam>, frame: &mut dyn DrawTarget) {
+        for linedef in &map.linedefs {
+            if linedef.has_flag(Linedef::NOT_ON_MAP) {
+                continue;
+            }
+
+            let (Some(start), Some(end)) = (
+                map.vertexes.get(linedef.start_vertex_id as usize),
+                map.vertexes.get(linedef.end_vertex_id as usize)
+            ) else {
+                continue;
+            };
+
+            let color = match map.linedef_sectors(linedef) {
+                (_, None) => WALL_COLORS,
+                // The teleporter line special.
+                _ if linedef.line_type == 39 => WALL_COLORS + WALL_RANGE / 2,
+                _ if linedef.has_flag(Linedef::SECRET) => WALL_COLORS,
+                (Some(front), Some(back)) if front.floor_height != back.floor_height => FLOOR_STEP_COLORS,
+                (Some(front), Some(back)) if front.ceiling_height != back.ceiling_height => CEILING_STEP_COLORS,
+                _ => continue
+            };
+
+            self.draw_mline(map_point(start.x, start.y), map_point(end.x, end.y), color, turn, frame);
+        }
+    }
+
+    // AM_drawPlayers for the one player: the arrow turned to face where they look.
+    fn draw_player(&self, player: &Player, turn: Option<Bam>, frame: &mut dyn DrawTarget) {
+        let position = map_point(player.position.x, player.position.y);
+
+        for ((x1, y1), (x2, y2)) in PLAYER_ARROW {
+            let a = rotate((x1 as i64, y1 as i64), player.angle);
+            let b = rotate((x2 as i64, y2 as i64), player.angle);
+
+            self.draw_mline(
+                (position.0 + a.0, position.1 + a.1),
+                (position.0 + b.0, position.1 + b.1),
+                PLAYER_COLORS,
+                turn,
+                frame
+            );
+        }
+    }
+
+    // AM_drawMline: turned about the window's center when rotating, then clipped to the
+    // frame.
+    fn draw_mline(&self, a: MapPoint, b: MapPoint, color: u8, turn: Option<Bam>, frame: &mut dyn DrawTarget) {
+        let (a, b) = match turn {
+            Some(angle) => {
+                let center = self.center();
+                let turn_point = |(x, y): MapPoint| {
+                    let (x, y) = rotate((x - center.0, y - center.1), angle);
+                    (x + center.0, y + center.1)
+                };
+
+                (turn_point(a), turn_point(b))
+            },
+            None => (a, b)
+        };
+
+        let to_frame = |(x, y): MapPoint| {
+            (self.mtof(x - self.x.0 as i64), self.frame_height as i64 - self.mtof(y - self.y.0 as i64))
+        };
+
+        if let Some(((x1, y1), (x2, y2))) = clip_line(to_frame(a), to_frame(b), self.frame_width as i64, self.frame_height as i64) {
+            frame.line(x1 as i32, y1 as i32, x2 as i32, y2 as i32, color);
+        }
+    }
+}
+
+// m_w/2 and m_h/2.
+fn half(value: Fixed) -> Fixed {
+    Fixed(value.0 / 2)
+}
+
+fn map_point(x: i16, y: i16) -> MapPoint {
+    ((x as i64) << FRACBITS, (y as i64) << FRACBITS)
+}
+
+// AM_rotate
+fn rotate((x, y): MapPoint, angle: Bam) -> MapPoint {
+    let (cos, sin) = (angle.cos().0 as i64, angle.sin().0 as i64);
+
+    ((x * cos - y * sin) >> FRACBITS, (x * sin + y * cos) >> FRACBITS)
+}
+
+// AM_clipMline's Cohen-Sutherland clipping, in frame pixels: the part of the line inside
+// the frame, if any.
+fn clip_line(mut a: (i64, i64), mut b: (i64, i64), width: i64, height: i64) -> Option<((i64, i64), (i64, i64))> {
+    const LEFT: u8 = 1;
+    const RIGHT: u8 = 2;
+    const TOP: u8 = 4;
+    const BOTTOM: u8 = 8;
+
+    let outcode = |(x, y): (i64, i64)| {
+        let mut code = 0;
+
+        if x < 0 {
+            code |= LEFT;
+        } else if x >= width {
+            code |= RIGHT;
+        }
+
+        if y < 0 {
+            code |= TOP;
+        } else if y >= height {
+            code |= BOTTOM;
+        }
+
+        code
+    };
+
+    loop {
+        let (code_a, code_b) = (outcode(a), outcode(b));
+
+        if code_a | code_b == 0 {
+            return Some((a, b));
+        }
+
+        if code_a & code_b != 0 {
+            return None;
+        }
+
+        let code = if code_a != 0 { code_a } else { code_b };
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+
+        let point = if code & LEFT != 0 {
+            (0, a.1 + dy * -a.0 / dx)
+        } else if code & RIGHT != 0 {
+            (width - 1, a.1 + dy * (width - 1 - a.0) / dx)
+        } else if code & TOP != 0 {
+            (a.0 + dx * -a.1 / dy, 0)
+        } else {
+            (a.0 + dx * (height - 1 - a.1) / dy, height - 1)
+        };
+
+        if code == code_a {
+            a = point;
+        } else {
+            b = point;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::MapBounds;
+    use crate::wad::Point;
+
+    // A map from the origin to (max_x, max_y), with the player in the middle of it.
+    fn map(max_x: i16, max_y: i16) -> GameMap {
+        let mut map = GameMap::with_name("TEST");
+
+        map.bounds = MapBounds { min_x: 0, max_x, min_y: 0, max_y };
+        map.player.position = Point { x: max_x / 2, y: max_y / 2 };
+        map
+    }
+
+    // The automap started on `map` in a 320x200 frame.
+    fn started(map: &GameMap) -> Automap {
+        let mut automap = Automap::new();
+
+        automap.set_frame(320, 200, 1);
+        automap.start(map);
+        automap
+    }
+
+    fn center(automap: &Automap) -> (Fixed, Fixed) {
+        (automap.x + half(automap.width), automap.y + half(automap.height))
+    }
+
+    #[test]
+    fn lines_outside_the_frame_are_dropped() {
+        assert_eq!(clip_line((-10, -10), (-5, 300), 320, 200), None);
+        assert_eq!(clip_line((400, 10), (500, 300), 320, 200), None);
+        assert_eq!(clip_line((10, 250), (300, 210), 320, 200), None);
+
+        // Past the corner without going in.
+        assert_eq!(clip_line((-10, 5), (5, -10), 320, 200), None);
+    }
+
+    #[test]
+    fn lines_are_clipped_to_the_frame() {
+        assert_eq!(clip_line((10, 20), (30, 40), 320, 200), Some(((10, 20), (30, 40))));
+        assert_eq!(clip_line((-10, -10), (10, 10), 320, 200), Some(((0, 0), (10, 10))));
+        assert_eq!(clip_line((330, 210), (310, 190), 320, 200), Some(((319, 199), (310, 190))));
+        assert_eq!(clip_line((100, -50), (100, 250), 320, 200), Some(((100, 0), (100, 199))));
+        assert_eq!(clip_line((-50, 100), (400, 100), 320, 200), Some(((0, 100), (319, 100))));
+    }
+
+    #[test]
+    fn levels_start_zoomed_in_from_the_whole_map() {
+        let automap = started(&map(1024, 512));
+
+        // 320/1024 is tighter than 200/512.
+        assert_eq!(automap.min_scale_mtof, Fixed(20480));
+        // 200 pixels to 32 units.
+        assert_eq!(automap.max_scale_mtof, Fixed(409600));
+        assert_eq!(automap.scale_mtof, Fixed(29257));
+        assert_eq!(automap.scale_ftom, Fixed::ONE / Fixed(29257));
+    }
+
+    #[test]
+    fn small_levels_start_at_the_whole_map() {
+        let automap = started(&map(16, 16));
+
+        assert_eq!(automap.min_scale_mtof, Fixed(819200));
+        assert_eq!(automap.scale_mtof, automap.min_scale_mtof);
+    }
+
+    #[test]
+    fn zooming_stops_at_the_scale_limits() {
+        let mut automap = started(&map(1024, 512));
+        let start = center(&automap);
+
+        automap.zoom = (ZOOM_IN, ZOOM_OUT);
+
+        for _ in 0..200 {
+            automap.change_window_scale();
+            assert!(automap.scale_mtof <= automap.max_scale_mtof);
+        }
+
+        assert_eq!(automap.scale_mtof, automap.max_scale_mtof);
+        assert_eq!(automap.height.0 >> FRACBITS, 31);
+
+        automap.zoom = (ZOOM_OUT, ZOOM_IN);
+
+        for _ in 0..300 {
+            automap.change_window_scale();
+            assert!(automap.scale_mtof >= automap.min_scale_mtof);
+        }
+
+        assert_eq!(automap.scale_mtof, automap.min_scale_mtof);
+        assert_eq!(automap.width.0 >> FRACBITS, 1023);
+
+        // Zooming happens about the middle of the window.
+        let end = center(&automap);
+        assert!((end.0 - start.0).abs() < Fixed::ONE && (end.1 - start.1).abs() < Fixed::ONE);
+    }
+
+    #[test]
+    fn panning_keeps_the_center_on_the_map() {
+        let mut automap = started(&map(1024, 512));
+
+        automap.pan = (Fixed::from_int(5000), Fixed::from_int(-5000));
+        automap.change_window_loc();
+
+        assert_eq!(center(&automap), (Fixed::from_int(1024), Fixed::ZERO));
+        assert!(!automap.is_following());
+
+        automap.pan = (Fixed::from_int(-5000), Fixed::from_int(5000));
+        automap.change_window_loc();
+
+        assert_eq!(center(&automap), (Fixed::ZERO, Fixed::from_int(512)));
+
+        // Inside the map it moves freely.
+        automap.pan = (Fixed::from_int(100), Fixed::from_int(-100));
+        automap.change_window_loc();
+
+        assert_eq!(center(&automap), (Fixed::from_int(100), Fixed::from_int(412)));
+    }
+}
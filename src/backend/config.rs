@@ -0,0 +1,72 @@
+// Vanilla's screen, which 2D graphics are laid out on and the 3D view is scaled from.
+pub const BASE_WIDTH: u32 = 320;
+pub const BASE_HEIGHT: u32 = 200;
+
+pub const MAX_SCALE: u32 = 6;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum AspectRatio {
+    // The 320 wide image: 4:3 with aspect correction, 16:10 without.
+    #[default]
+    Standard,
+    Wide16x9,
+    Wide21x9
+}
+
+impl AspectRatio {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "4:3" | "16:10" | "standard" => Some(Self::Standard),
+            "16:9" => Some(Self::Wide16x9),
+            "21:9" => Some(Self::Wide21x9),
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VideoConfig {
+    pub scale: u32,
+    pub aspect_ratio: AspectRatio,
+    // Show the 200 line image 240 lines tall, as a 320x200 mode was on a 4:3 monitor.
+    pub aspect_correction: bool,
+    // Threads the 3D view is drawn with. The picture is the same whatever the count.
+    pub render_threads: usize
+}
+
+impl Default for VideoConfig {
+    fn default() -> Self {
+        Self {
+            scale: 1,
+            aspect_ratio: AspectRatio::Standard,
+            aspect_correction: true,
+            render_threads: std::thread::available_parallelism().map_or(1, |threads| threads.get())
+        }
+    }
+}
+
+impl VideoConfig {
+    pub fn height(&self) -> u32 {
+        BASE_HEIGHT * self.scale.clamp(1, MAX_SCALE)
+    }
+
+    // How tall the frame is once it is on screen, with pixels stretched 6:5 by aspect correction.
+    pub fn display_height(&self) -> u32 {
+        if self.aspect_correction {
+            self.height() * 6 / 5
+        } else {
+            self.height()
+        }
+    }
+
+    // Wide frames get the extra columns on the sides, at the pixel shape of the display.
+    pub fn width(&self) -> u32 {
+        let width = match self.aspect_ratio {
+            AspectRatio::Standard => return BASE_WIDTH * self.scale.clamp(1, MAX_SCALE),
+            AspectRatio::Wide16x9 => self.display_height() * 16 / 9,
+            AspectRatio::Wide21x9 => self.display_height() * 64 / 27
+        };
+
+        width & !1
+    }
+}
@@ -0,0 +1,204 @@
+use anyhow::Result;
+use crate::backend::{Backend, DrawTarget};
+use crate::wad::Palette;
+
+// A palette-indexed screen kept in memory. Everything is drawn in palette indices and only
+// turned into RGB when the frame is presented, so it also serves as a headless backend.
+#[derive(Debug, Clone)]
+pub struct FrameBuffer {
+    width: i32,
+    height: i32,
+    pixels: Vec<u8>
+}
+
+impl FrameBuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width: width as i32,
+            height: height as i32,
+            pixels: vec![0; (width * height) as usize]
+        }
+    }
+
+    // A copy of whatever is on `target` right now.
+    pub fn capture(target: &dyn DrawTarget) -> Self {
+        let mut frame = Self::new(target.width().max(0) as u32, target.height().max(0) as u32);
+        let width = frame.width.max(1) as usize;
+
+        for (y, row) in frame.pixels.chunks_exact_mut(width).enumerate() {
+            target.read_row(0, y as i32, row);
+        }
+
+        frame
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [u8] {
+        &mut self.pixels
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        (x >= 0 && y >= 0 && x < self.width && y < self.height).then(|| (y * self.width + x) as usize)
+    }
+
+    // The part of a row run that is on screen, as a range of `pixels`.
+    fn row_range(&self, x: i32, y: i32, length: usize) -> Option<(usize, std::ops::Range<usize>)> {
+        let first = x.max(0);
+        let last = (x + length as i32).min(self.width);
+
+        (y >= 0 && y < self.height && first < last).then(|| {
+            let start = (y * self.width + first) as usize;
+            ((first - x) as usize, start..start + (last - first) as usize)
+        })
+    }
+
+    // Expands the frame into packed RGB24 rows, `pitch` bytes apart.
+    pub fn to_rgb24(&self, palette: &Palette, rgb: &mut [u8], pitch: usize) {
+        for (row, line) in self.pixels.chunks_exact(self.width as usize).enumerate() {
+            let out = &mut rgb[row * pitch..row * pitch + line.len() * 3];
+
+            for (pixel, &index) in out.chunks_exact_mut(3).zip(line) {
+                pixel.copy_from_slice(&palette[index as usize]);
+            }
+        }
+    }
+}
+
+impl DrawTarget for FrameBuffer {
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn pixel(&self, x: i32, y: i32) -> Option<u8> {
+        self.index(x, y).map(|i| self.pixels[i])
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, color: u8) {
+        if let Some(i) = self.index(x, y) {
+            self.pixels[i] = color;
+        }
+    }
+
+    fn read_row(&self, x: i32, y: i32, row: &mut [u8]) {
+        row.fill(0);
+
+        if let Some((skip, range)) = self.row_range(x, y, row.len()) {
+            row[skip..skip + range.len()].copy_from_slice(&self.pixels[range]);
+        }
+    }
+
+    fn write_row(&mut self, x: i32, y: i32, row: &[u8]) {
+        if let Some((skip, range)) = self.row_range(x, y, row.len()) {
+            let length = range.len();
+            self.pixels[range].copy_from_slice(&row[skip..skip + length]);
+        }
+    }
+
+    fn clear(&mut self, color: u8) {
+        self.pixels.fill(color);
+    }
+}
+
+// Nothing to show: the frame stays in `pixels` for whoever wants to look at it.
+impl Backend for FrameBuffer {
+    fn present(&mut self, _palette: &Palette) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad::{Patch, Post};
+
+    // Each row of the frame as a string, '.' for color 0 and the color's digit otherwise.
+    fn rows(frame: &FrameBuffer) -> Vec<String> {
+        frame.pixels.chunks_exact(frame.width as usize)
+            .map(|row| row.iter().map(|&color| if color == 0 { '.' } else { (b'0' + color) as char }).collect())
+            .collect()
+    }
+
+    #[test]
+    fn lines_and_rectangles() {
+        let mut frame = FrameBuffer::new(6, 5);
+        let target: &mut dyn DrawTarget = &mut frame;
+
+        target.rectangle(0, 0, 5, 4, 1);
+        target.line(1, 1, 4, 3, 2);
+        target.vline(3, 3, 1, 3);
+
+        assert_eq!(rows(&frame), [
+            "111111",
+            "12.3.1",
+            "1.23.1",
+            "1..321",
+            "111111"
+        ]);
+    }
+
+    #[test]
+    fn writes_off_screen_are_clipped() {
+        let mut frame = FrameBuffer::new(4, 2);
+        let target: &mut dyn DrawTarget = &mut frame;
+
+        target.clear(1);
+        target.set_pixel(-1, 0, 9);
+        target.set_pixel(4, 1, 9);
+        target.write_row(-2, 0, &[5, 6, 7, 8]);
+        target.write_row(2, 1, &[5, 6, 7, 8]);
+        target.line(-3, 1, 1, 1, 2);
+
+        assert_eq!(rows(&frame), ["7811", "2256"]);
+        assert_eq!(frame.pixel(4, 0), None);
+
+        let mut row = [9; 4];
+        frame.read_row(-1, 1, &mut row);
+        assert_eq!(row, [0, 2, 2, 5]);
+    }
+
+    #[test]
+    fn patches_are_scaled_and_centered() {
+        // 640x440 is 320x200 twice over with 20 rows left above and below.
+        let mut frame = FrameBuffer::new(640, 440);
+        let patch = Patch {
+            name: "DOT".to_string(),
+            width: 2,
+            height: 2,
+            left_offset: 1,
+            top_offset: 0,
+            columns: vec![vec![Post { top_delta: 0, pixels: vec![3] }], vec![Post { top_delta: 1, pixels: vec![4] }]]
+        };
+
+        frame.draw_patch(10, 5, &patch);
+
+        assert_eq!(frame.base_scale(), 2);
+        assert_eq!(frame.base_origin(), (0, 20));
+        assert_eq!([frame.pixel(18, 30), frame.pixel(19, 31), frame.pixel(20, 30)], [Some(3), Some(3), Some(0)]);
+        assert_eq!([frame.pixel(20, 32), frame.pixel(21, 33), frame.pixel(20, 31)], [Some(4), Some(4), Some(0)]);
+        assert_eq!(frame.pixels.iter().filter(|&&color| color != 0).count(), 8);
+    }
+
+    #[test]
+    fn frames_expand_to_rgb() {
+        let mut palette: Palette = [[0; 3]; 256];
+        palette[1] = [10, 20, 30];
+        palette[2] = [40, 50, 60];
+
+        let mut frame = FrameBuffer::new(2, 2);
+        frame.write_row(0, 0, &[1, 2]);
+        frame.set_pixel(1, 1, 1);
+
+        let copy = FrameBuffer::capture(&frame);
+        let mut rgb = [0xff; 16];
+        copy.to_rgb24(&palette, &mut rgb, 8);
+
+        assert_eq!(rgb, [10, 20, 30, 40, 50, 60, 0xff, 0xff, 0, 0, 0, 10, 20, 30, 0xff, 0xff]);
+    }
+}
@@ -0,0 +1,162 @@
+use anyhow::Result;
+use crate::wad::{Flat, Palette, Patch};
+
+mod config;
+pub use config::{AspectRatio, VideoConfig, BASE_HEIGHT, BASE_WIDTH, MAX_SCALE};
+
+mod memory;
+pub use memory::FrameBuffer;
+
+mod sdl;
+pub use sdl::SdlBackend;
+
+mod viewport;
+pub use viewport::Viewport;
+
+// What the automap and the 3D view draw into: a palette-indexed surface. Only `set_pixel`
+// and the size have to be provided, the primitives are built on top of them.
+pub trait DrawTarget {
+    fn width(&self) -> i32;
+
+    fn height(&self) -> i32;
+
+    fn pixel(&self, x: i32, y: i32) -> Option<u8>;
+
+    // Out-of-bounds writes are ignored.
+    fn set_pixel(&mut self, x: i32, y: i32, color: u8);
+
+    // Reads a run of pixels from row `y`, starting at column `x`.
+    fn read_row(&self, x: i32, y: i32, row: &mut [u8]) {
+        for (i, pixel) in row.iter_mut().enumerate() {
+            *pixel = self.pixel(x + i as i32, y).unwrap_or(0);
+        }
+    }
+
+    fn write_row(&mut self, x: i32, y: i32, row: &[u8]) {
+        for (i, &pixel) in row.iter().enumerate() {
+            self.set_pixel(x + i as i32, y, pixel);
+        }
+    }
+
+    fn clear(&mut self, color: u8) {
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                self.set_pixel(x, y, color);
+            }
+        }
+    }
+
+    fn vline(&mut self, x: i32, y1: i32, y2: i32, color: u8) {
+        for y in y1.min(y2)..=y1.max(y2) {
+            self.set_pixel(x, y, color);
+        }
+    }
+
+    // Bresenham
+    fn line(&mut self, x1: i32, y1: i32, x2: i32, y2: i32, color: u8) {
+        let (dx, dy) = ((x2 - x1).abs(), -(y2 - y1).abs());
+        let (step_x, step_y) = ((x2 - x1).signum(), (y2 - y1).signum());
+        let (mut x, mut y) = (x1, y1);
+        let mut error = dx + dy;
+
+        loop {
+            self.set_pixel(x, y, color);
+
+            if x == x2 && y == y2 {
+                break;
+            }
+
+            let error2 = error * 2;
+
+            if error2 >= dy {
+                error += dy;
+                x += step_x;
+            }
+
+            if error2 <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    fn rectangle(&mut self, x1: i32, y1: i32, x2: i32, y2: i32, color: u8) {
+        self.line(x1, y1, x2, y1, color);
+        self.line(x2, y1, x2, y2, color);
+        self.line(x2, y2, x1, y2, color);
+        self.line(x1, y2, x1, y1, color);
+    }
+
+    fn filled_circle(&mut self, x: i32, y: i32, radius: i32, color: u8) {
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                if dx * dx + dy * dy <= radius * radius {
+                    self.set_pixel(x + dx, y + dy, color);
+                }
+            }
+        }
+    }
+
+    // 2D graphics are laid out on vanilla's 320x200 screen. It is blown up by a whole factor
+    // and centered, so wider frames get pillarboxed rather than stretched.
+    fn base_scale(&self) -> i32 {
+        (self.width() / BASE_WIDTH as i32).min(self.height() / BASE_HEIGHT as i32).max(1)
+    }
+
+    fn base_origin(&self) -> (i32, i32) {
+        let scale = self.base_scale();
+
+        ((self.width() - BASE_WIDTH as i32 * scale) / 2, (self.height() - BASE_HEIGHT as i32 * scale) / 2)
+    }
+
+    // V_DrawPatch, in 320x200 coordinates.
+    fn draw_patch(&mut self, x: i32, y: i32, patch: &Patch) {
+        let scale = self.base_scale();
+        let (origin_x, origin_y) = self.base_origin();
+
+        self.draw_scaled_patch(origin_x + x * scale, origin_y + y * scale, patch);
+    }
+
+    // A patch blown up like the 320x200 screen, but placed in screen coordinates.
+    fn draw_scaled_patch(&mut self, x: i32, y: i32, patch: &Patch) {
+        let scale = self.base_scale();
+        let (x, y) = (x - patch.left_offset as i32 * scale, y - patch.top_offset as i32 * scale);
+
+        for (column, posts) in patch.columns.iter().enumerate() {
+            let screen_x = x + column as i32 * scale;
+
+            for post in posts {
+                for (row, &color) in post.pixels.iter().enumerate() {
+                    let screen_y = y + (post.top_delta as i32 + row as i32) * scale;
+
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            self.set_pixel(screen_x + dx, screen_y + dy, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // R_FillBackScreen's tiling of the border flat, in screen coordinates. The flat is blown
+    // up with the 320x200 screen and lines up with its corner.
+    fn tile_flat(&mut self, x1: i32, y1: i32, x2: i32, y2: i32, flat: &Flat) {
+        let scale = self.base_scale();
+        let (origin_x, origin_y) = self.base_origin();
+
+        for y in y1..=y2 {
+            let v = (y - origin_y).div_euclid(scale) & 63;
+
+            for x in x1..=x2 {
+                let u = (x - origin_x).div_euclid(scale) & 63;
+                self.set_pixel(x, y, flat.pixels.get((v * 64 + u) as usize).copied().unwrap_or(0));
+            }
+        }
+    }
+}
+
+// A draw target that can show a finished frame somewhere.
+pub trait Backend: DrawTarget {
+    fn present(&mut self, palette: &Palette) -> Result<()>;
+}
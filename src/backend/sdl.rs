@@ -0,0 +1,88 @@
+use anyhow::{anyhow, Result};
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::{Texture, WindowCanvas};
+use sdl2::video::Window;
+use crate::backend::{Backend, DrawTarget, FrameBuffer, VideoConfig};
+use crate::wad::Palette;
+
+// Draws into a FrameBuffer and presents it through a streaming texture, scaled to the window
+// and stretched to the display height when aspect correction is on.
+pub struct SdlBackend {
+    canvas: WindowCanvas,
+    screen: Texture,
+    frame: FrameBuffer
+}
+
+impl SdlBackend {
+    pub fn new(window: Window, config: &VideoConfig) -> Result<Self> {
+        let (width, height) = (config.width(), config.height());
+
+        let mut canvas = sdl2::render::CanvasBuilder::new(window)
+            .software()
+            .present_vsync()
+            .build()?;
+
+        canvas.set_logical_size(width, config.display_height())?;
+
+        let screen = canvas.texture_creator()
+            .create_texture_streaming(PixelFormatEnum::RGB24, width, height)?;
+
+        Ok(
+            Self {
+                canvas,
+                screen,
+                frame: FrameBuffer::new(width, height)
+            }
+        )
+    }
+
+    pub fn frame(&self) -> &FrameBuffer {
+        &self.frame
+    }
+}
+
+impl DrawTarget for SdlBackend {
+    fn width(&self) -> i32 {
+        self.frame.width()
+    }
+
+    fn height(&self) -> i32 {
+        self.frame.height()
+    }
+
+    fn pixel(&self, x: i32, y: i32) -> Option<u8> {
+        self.frame.pixel(x, y)
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, color: u8) {
+        self.frame.set_pixel(x, y, color);
+    }
+
+    fn read_row(&self, x: i32, y: i32, row: &mut [u8]) {
+        self.frame.read_row(x, y, row);
+    }
+
+    fn write_row(&mut self, x: i32, y: i32, row: &[u8]) {
+        self.frame.write_row(x, y, row);
+    }
+
+    fn clear(&mut self, color: u8) {
+        self.frame.clear(color);
+    }
+}
+
+impl Backend for SdlBackend {
+    // The one upload and copy per frame: palette indices to RGB, into the streaming texture.
+    fn present(&mut self, palette: &Palette) -> Result<()> {
+        let frame = &self.frame;
+
+        self.screen
+            .with_lock(None, |buffer, pitch| frame.to_rgb24(palette, buffer, pitch))
+            .map_err(|e| anyhow!(e))?;
+
+        self.canvas.copy(&self.screen, None, None).map_err(|e| anyhow!(e))?;
+        self.canvas.present();
+
+        Ok(())
+    }
+}
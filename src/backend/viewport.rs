@@ -0,0 +1,56 @@
+use crate::backend::DrawTarget;
+
+// A window onto part of another target with its own origin, like vanilla's viewwindowx and
+// viewwindowy. The 3D view and the automap draw into one so they leave the status bar alone.
+pub struct Viewport<'a> {
+    target: &'a mut dyn DrawTarget,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32
+}
+
+impl<'a> Viewport<'a> {
+    pub fn new(target: &'a mut dyn DrawTarget, x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self { target, x, y, width, height }
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < self.width && y < self.height
+    }
+
+    // How much of a row run starting at `x` is inside the window.
+    fn row_length(&self, x: i32, y: i32, length: usize) -> usize {
+        if self.contains(x, y) { length.min((self.width - x) as usize) } else { 0 }
+    }
+}
+
+impl DrawTarget for Viewport<'_> {
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn pixel(&self, x: i32, y: i32) -> Option<u8> {
+        self.contains(x, y).then(|| self.target.pixel(self.x + x, self.y + y)).flatten()
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, color: u8) {
+        if self.contains(x, y) {
+            self.target.set_pixel(self.x + x, self.y + y, color);
+        }
+    }
+
+    fn read_row(&self, x: i32, y: i32, row: &mut [u8]) {
+        let length = self.row_length(x, y, row.len());
+        self.target.read_row(self.x + x, self.y + y, &mut row[..length]);
+    }
+
+    fn write_row(&mut self, x: i32, y: i32, row: &[u8]) {
+        let length = self.row_length(x, y, row.len());
+        self.target.write_row(self.x + x, self.y + y, &row[..length]);
+    }
+}
@@ -0,0 +1,39 @@
+use std::fs;
+use std::path::PathBuf;
+use anyhow::{bail, Result};
+
+use doom::{map_mesh, mesh_gltf, mesh_mtl, mesh_obj, GameMap, Loader};
+
+const USAGE: &str = "Usage: doom-export <WAD> <MAP> <OUTPUT.obj|OUTPUT.gltf>";
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let [wad_path, map_name, output] = args.as_slice() else {
+        bail!(USAGE);
+    };
+
+    let output = PathBuf::from(output);
+
+    let mut loader = Loader::new(PathBuf::from(wad_path))?;
+    let textures = loader.load_texture_defs()?;
+
+    let mut map = GameMap::with_name(map_name);
+    loader.load_map_data(&mut map)?;
+
+    let mesh = map_mesh(&map, &textures);
+
+    match output.extension().and_then(|extension| extension.to_str()) {
+        Some("obj") => {
+            let mtl_path = output.with_extension("mtl");
+            let mtl_file_name = mtl_path.file_name().unwrap().to_string_lossy().to_string();
+
+            fs::write(&output, mesh_obj(&mesh, &mtl_file_name))?;
+            fs::write(&mtl_path, mesh_mtl(&mesh))?;
+        },
+        Some("gltf") => fs::write(&output, mesh_gltf(&mesh, map_name))?,
+        _ => bail!(USAGE)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+use anyhow::{bail, Result};
+use serde::Serialize;
+
+use doom::{lint_map, GameMap, Issue, Loader, Severity};
+
+#[derive(Serialize)]
+struct MapReport {
+    map: String,
+    issues: Vec<Issue>
+}
+
+fn run() -> Result<bool> {
+    let mut json = false;
+    let mut paths: Vec<String> = Vec::new();
+
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => json = true,
+            _ => paths.push(arg)
+        }
+    }
+
+    let Some((wad_path, map_names)) = paths.split_first() else {
+        bail!("Usage: doom-lint <WAD> [MAP...] [--json]");
+    };
+
+    let mut loader = Loader::new(PathBuf::from(wad_path))?;
+    let texture_names = loader.load_texture_names()?;
+
+    let map_names = if map_names.is_empty() {
+        loader.map_names()
+    } else {
+        map_names.to_vec()
+    };
+
+    let mut reports: Vec<MapReport> = Vec::new();
+
+    for map_name in map_names {
+        let mut map = GameMap::with_name(&map_name);
+        loader.load_map_data(&mut map)?;
+
+        let issues = lint_map(&map, Some(&texture_names));
+        reports.push(MapReport { map: map_name, issues });
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    } else {
+        for report in &reports {
+            for issue in &report.issues {
+                println!("{}: {}", report.map, issue);
+            }
+
+            println!("{}: {} issues", report.map, report.issues.len());
+        }
+    }
+
+    let has_errors = reports.iter()
+        .flat_map(|report| &report.issues)
+        .any(|issue| issue.severity == Severity::Error);
+
+    Ok(!has_errors)
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+use anyhow::{bail, Result};
+
+use doom::{map_stats, GameMap, Loader, MapStats};
+
+fn main() -> Result<()> {
+    let mut json = false;
+    let mut paths: Vec<String> = Vec::new();
+
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => json = true,
+            _ => paths.push(arg)
+        }
+    }
+
+    let Some((wad_path, map_names)) = paths.split_first() else {
+        bail!("Usage: doom-stats <WAD> [MAP...] [--json]");
+    };
+
+    let mut loader = Loader::new(PathBuf::from(wad_path))?;
+
+    let map_names = if map_names.is_empty() {
+        loader.map_names()
+    } else {
+        map_names.to_vec()
+    };
+
+    let mut reports: Vec<MapStats> = Vec::new();
+
+    for map_name in map_names {
+        let mut map = GameMap::with_name(&map_name);
+        loader.load_map_data(&mut map)?;
+
+        reports.push(map_stats(&map));
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    } else {
+        for report in &reports {
+            println!("{}", report);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use std::fs;
+use std::path::PathBuf;
+use anyhow::{bail, Context, Result};
+
+use doom::{automap_svg, GameMap, Loader, SvgOptions};
+
+const USAGE: &str = "Usage: doom-svg <WAD> <MAP> [-o FILE] [--width N] [--things] [--partitions] [--subsectors]";
+
+fn main() -> Result<()> {
+    let mut options = SvgOptions::default();
+    let mut output: Option<PathBuf> = None;
+    let mut paths: Vec<String> = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--things" => options.things = true,
+            "--partitions" => options.partitions = true,
+            "--subsectors" => options.subsectors = true,
+            "--width" => {
+                let width = args.next().context(USAGE)?;
+                options.width = Some(width.parse().context("Invalid --width")?);
+            },
+            "-o" => output = Some(PathBuf::from(args.next().context(USAGE)?)),
+            _ => paths.push(arg)
+        }
+    }
+
+    let [wad_path, map_name] = paths.as_slice() else {
+        bail!(USAGE);
+    };
+
+    let mut loader = Loader::new(PathBuf::from(wad_path))?;
+    let mut map = GameMap::with_name(map_name);
+    loader.load_map_data(&mut map)?;
+
+    let svg = automap_svg(&map, &options);
+
+    match output {
+        Some(path) => fs::write(path, svg)?,
+        None => print!("{}", svg)
+    }
+
+    Ok(())
+}
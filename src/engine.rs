@@ -1,66 +1,504 @@
-use std::path::PathBuf;
-use sdl2::render::WindowCanvas;
-use sdl2::sys::SDL_Renderer;
+use std::path::{Path, PathBuf};
 use anyhow::Result;
-use sdl2::gfx::primitives::DrawRenderer;
-use sdl2::pixels::Color;
 use sdl2::video::Window;
 
+use crate::automap::Automap;
+use crate::backend::{Backend, DrawTarget, FrameBuffer, SdlBackend, VideoConfig, Viewport, BASE_HEIGHT};
 use crate::wad::Loader;
+use crate::hud::Hud;
+use crate::input::Event;
 use crate::map::GameMap;
+use crate::math::{Fixed, Random};
+use crate::menu::{Menu, MenuAction, MenuContext};
 use crate::Player;
+use crate::render::{RenderData, RenderStats, Renderer, TicSnapshot, ViewBorder};
+use crate::savegame::SaveGame;
+use crate::screenshot::{save_screenshot, ScreenshotFormat};
+use crate::settings::{Settings, MAX_SCREEN_BLOCKS};
+use crate::stats::Skill;
+use crate::status_bar::{StatusBar, BORDER_FLAT, STATUS_BAR_HEIGHT};
+use crate::wad::{Palette, Patch};
+use crate::wipe::Melt;
 
-const DOOM_W: u32 = 320;
-const DOOM_H: u32 = 200;
+// The simulation always steps at vanilla's rate, whatever the frame rate.
+pub(crate) const TIC_RATE: u32 = 35;
 
-pub struct DoomEngine {
-    canvas: WindowCanvas,
+const TIC_SECONDS: f64 = 1.0 / TIC_RATE as f64;
+
+// After a stall (a breakpoint, a dragged window) the game skips ahead instead of running
+// a burst of tics to catch up.
+const MAX_CATCH_UP_TICS: u32 = 10;
+
+// GGSAVED
+const GAME_SAVED: &str = "game saved.";
+
+// screenblocks 10: the view fills the width above the status bar.
+const FULL_WIDTH_BLOCKS: i32 = 10;
+
+pub struct DoomEngine<B: Backend = SdlBackend> {
+    backend: B,
     player: Player,
     map: GameMap,
     loader: Loader,
+    renderer: Renderer,
+    status_bar: Option<StatusBar>,
+    hud: Hud,
+    menu: Menu,
+    settings: Settings,
+    view_border: ViewBorder,
+    title: Option<Patch>,
+    commercial: bool,
+    skill: Skill,
+    save_dir: PathBuf,
+    // usergame: false on the title screen after End Game.
+    playing: bool,
+    previous: TicSnapshot,
+    game_tic: u64,
+    tic_time: f64,
+    random: Random,
+    wipe: Option<Melt>,
+    wipe_pending: bool,
+    automap: Automap,
     is_over: bool
 }
 
-impl DoomEngine {
-    pub fn new(window: Window) -> Result<Self> {
-        let mut canvas = sdl2::render::CanvasBuilder::new(window)
-            .software()
-            .present_vsync()
-            .build()?;
+impl DoomEngine<SdlBackend> {
+    pub fn new(window: Window, config: VideoConfig) -> Result<Self> {
+        let mut engine = Self::with_backend(SdlBackend::new(window, &config)?, "wad/DOOM1.wad")?;
+        engine.set_render_threads(config.render_threads);
 
-        canvas.set_logical_size(DOOM_W, DOOM_H);
+        Ok(engine)
+    }
+}
 
+impl<B: Backend> DoomEngine<B> {
+    pub fn with_backend(backend: B, wad_path: impl AsRef<Path>) -> Result<Self> {
         let player = Player::new(1);
-        let map = GameMap::new("E1M1", player, &canvas);
-        let loader = Loader::new(PathBuf::from("wad/DOOM1.wad")).unwrap();
-
-        Ok(
-            Self {
-                canvas,
-                map,
-                player,
-                loader,
-                is_over: false
-            }
-        )
+        let map = GameMap::new("E1M1", player);
+        let mut loader = Loader::new(PathBuf::from(wad_path.as_ref()))?;
+        let renderer = Renderer::new(backend.width() as u32, backend.height() as u32, RenderData::load(&mut loader)?);
+
+        // WADs without the status bar graphics get the whole screen for the view.
+        let status_bar = StatusBar::load(&mut loader, &player).ok();
+        let hud = Hud::load(&mut loader);
+
+        let map_names = loader.map_names();
+        let commercial = map_names.iter().any(|name| name == "MAP01");
+        let episodes = std::array::from_fn(|episode| map_names.contains(&format!("E{}M1", episode + 1)));
+        let menu = Menu::load(&mut loader, commercial, episodes);
+        let view_border = ViewBorder::load(&mut loader);
+        let title = loader.load_patch("TITLEPIC").ok();
+
+        let mut engine = Self {
+            backend,
+            map,
+            player,
+            loader,
+            renderer,
+            status_bar,
+            hud,
+            menu,
+            settings: Settings::default(),
+            view_border,
+            title,
+            commercial,
+            skill: Skill::Medium,
+            save_dir: PathBuf::from("."),
+            playing: true,
+            previous: TicSnapshot::default(),
+            game_tic: 0,
+            tic_time: 0.0,
+            random: Random::new(),
+            wipe: None,
+            wipe_pending: false,
+            automap: Automap::new(),
+            is_over: false
+        };
+
+        engine.apply_settings();
+        Ok(engine)
     }
 
     pub fn init(&mut self) -> Result<()> {
-        self.loader.load_map_data(&mut self.map);
+        let map_name = self.map.map_name.clone();
+        self.load_map(&map_name)
+    }
+
+    // G_DoLoadLevel: the next frame melts from whatever was on screen into the new level.
+    pub fn load_map(&mut self, map_name: &str) -> Result<()> {
+        let mut map = GameMap::new(map_name, Player::new(1));
+        self.loader.load_map_data(&mut map)?;
+
+        self.map = map;
+        self.automap.stop();
+        self.renderer.set_sky_for_map(&self.map.map_name);
+        self.previous = TicSnapshot::capture(&self.map);
+        self.wipe_pending = true;
+
+        if let Some(status_bar) = &mut self.status_bar {
+            status_bar.start(&self.map.player);
+        }
+
+        self.hud.start(&self.map.map_name);
 
         Ok(())
     }
 
+    pub fn is_wiping(&self) -> bool {
+        self.wipe_pending || self.wipe.is_some()
+    }
+
+    pub fn set_render_threads(&mut self, threads: usize) {
+        self.renderer.set_threads(threads);
+    }
+
+    pub fn player(&self) -> &Player {
+        &self.map.player
+    }
+
+    pub fn player_mut(&mut self) -> &mut Player {
+        &mut self.map.player
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn render_stats(&self) -> RenderStats {
+        self.renderer.stats()
+    }
+
+    pub fn hud(&self) -> &Hud {
+        &self.hud
+    }
+
+    pub fn hud_mut(&mut self) -> &mut Hud {
+        &mut self.hud
+    }
+
+    // For gameplay code: a line on the HUD for the console player.
+    pub fn post_message(&mut self, text: &str) {
+        self.hud.post_message(text);
+    }
+
+    pub fn toggle_automap(&mut self) {
+        if self.automap.is_active() {
+            self.automap.stop();
+        } else {
+            self.automap.start(&self.map);
+        }
+    }
+
+    pub fn automap(&self) -> &Automap {
+        &self.automap
+    }
+
+    pub fn game_tic(&self) -> u64 {
+        self.game_tic
+    }
+
+    // Set once Quit has been answered yes.
+    pub fn is_over(&self) -> bool {
+        self.is_over
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn menu(&self) -> &Menu {
+        &self.menu
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: Settings) {
+        self.settings = settings;
+        self.apply_settings();
+    }
+
+    // Where the save slots live, the working directory unless told otherwise.
+    pub fn set_save_dir(&mut self, dir: impl AsRef<Path>) {
+        self.save_dir = dir.as_ref().to_path_buf();
+    }
+
+    fn is_netgame(&self) -> bool {
+        self.hud.player_count() > 1
+    }
+
+    // Where the status bar starts, or the bottom of the screen without one.
+    fn status_bar_top(&self) -> i32 {
+        match self.status_bar {
+            Some(_) => self.backend.base_origin().1 + (BASE_HEIGHT as i32 - STATUS_BAR_HEIGHT) * self.backend.base_scale(),
+            None => self.backend.height()
+        }
+    }
+
+    // R_ExecuteSetViewSize and R_InitBuffer: the view's window on the screen as x, y, width
+    // and height. Under 10 blocks it shrinks into the middle of what the status bar leaves.
+    fn view_window(&self) -> (i32, i32, i32, i32) {
+        let (width, height) = (self.backend.width(), self.backend.height());
+        let (blocks, bar_top) = (self.settings.screen_blocks, self.status_bar_top());
+
+        if blocks >= MAX_SCREEN_BLOCKS {
+            return (0, 0, width, height);
+        }
+
+        if blocks == FULL_WIDTH_BLOCKS {
+            return (0, 0, width, bar_top);
+        }
+
+        let view_width = (width * blocks / FULL_WIDTH_BLOCKS) & !1;
+        let view_height = ((blocks * (BASE_HEIGHT as i32 - STATUS_BAR_HEIGHT) / FULL_WIDTH_BLOCKS) & !7) * self.backend.base_scale();
+
+        ((width - view_width) / 2, (bar_top - view_height) / 2, view_width, view_height)
+    }
+
+    fn shows_status_bar(&self) -> bool {
+        self.playing && (self.settings.screen_blocks < MAX_SCREEN_BLOCKS || self.automap.is_active())
+    }
+
+    // R_SetViewSize and the message setting. The automap always has everything above the
+    // status bar.
+    fn apply_settings(&mut self) {
+        let (_, _, width, height) = self.view_window();
+
+        self.renderer.set_view_size(width as u32, height as u32, self.settings.low_detail);
+        self.hud.set_show_messages(self.settings.show_messages);
+        self.automap.set_frame(self.backend.width(), self.status_bar_top(), self.backend.base_scale());
+    }
+
+    // G_InitNew: a fresh player on the episode's first map.
+    pub fn new_game(&mut self, episode: usize, skill: Skill) -> Result<()> {
+        let map_name = if self.commercial { "MAP01".to_string() } else { format!("E{}M1", episode + 1) };
+
+        self.skill = skill;
+        self.load_map(&map_name)?;
+        self.playing = true;
+
+        Ok(())
+    }
+
+    // G_DoSaveGame
+    pub fn save_game(&mut self, slot: usize, description: &str) -> Result<()> {
+        let save = SaveGame {
+            description: description.to_string(),
+            map_name: self.map.map_name.clone(),
+            skill: self.skill,
+            game_tic: self.game_tic,
+            player: self.map.player
+        };
+
+        save.save(&self.save_dir, slot)?;
+        self.hud.post_message(GAME_SAVED);
+
+        Ok(())
+    }
+
+    // G_DoLoadGame
+    pub fn load_game(&mut self, slot: usize) -> Result<()> {
+        let save = SaveGame::load(&self.save_dir, slot)?;
+
+        self.load_map(&save.map_name)?;
+        self.map.player = save.player;
+        self.previous = TicSnapshot::capture(&self.map);
+        self.skill = save.skill;
+        self.game_tic = save.game_tic;
+        self.playing = true;
+
+        if let Some(status_bar) = &mut self.status_bar {
+            status_bar.start(&self.map.player);
+        }
+
+        Ok(())
+    }
+
+    // D_StartTitle: back to the title screen, melting into it.
+    pub fn end_game(&mut self) {
+        self.playing = false;
+        self.automap.stop();
+        self.wipe_pending = true;
+    }
+
+    fn do_menu_action(&mut self, action: MenuAction) -> Result<()> {
+        match action {
+            MenuAction::NewGame { episode, skill } => self.new_game(episode, skill)?,
+            MenuAction::LoadGame(slot) => self.load_game(slot)?,
+            MenuAction::SaveGame { slot, description } => self.save_game(slot, &description)?,
+            MenuAction::EndGame => self.end_game(),
+            MenuAction::Quit => self.is_over = true,
+            MenuAction::Message(text) => self.hud.post_message(text),
+            MenuAction::ForcedMessage(text) => self.hud.force_message(text)
+        }
+
+        Ok(())
+    }
+
+    // D_ProcessEvents and G_Responder: whether the event was used. The menu gets first go,
+    // then the HUD, so keys typed into chat don't reach the game, then the automap.
+    pub fn responder(&mut self, event: Event) -> bool {
+        let context = MenuContext {
+            playing: self.playing,
+            netgame: self.is_netgame(),
+            automap: self.automap.is_active(),
+            chatting: self.hud.is_chatting(),
+            game_tic: self.game_tic,
+            save_dir: &self.save_dir
+        };
+
+        let eaten = self.menu.responder(event, &context, &mut self.settings);
+        self.apply_settings();
+
+        for action in self.menu.take_actions() {
+            if let Err(error) = self.do_menu_action(action) {
+                self.hud.post_message(&error.to_string());
+            }
+        }
+
+        if eaten || !self.playing {
+            return eaten;
+        }
+
+        self.hud.responder(event) || self.automap.responder(event, &self.map, &mut self.hud)
+    }
+
+    // Runs however many whole tics have elapsed. What is left over is how far the next frame
+    // is drawn between the last two tics.
     pub fn update(&mut self, delta_time: f64) {
+        self.tic_time += delta_time;
+
+        let mut tics = 0;
+
+        while self.tic_time >= TIC_SECONDS {
+            if tics == MAX_CATCH_UP_TICS {
+                self.tic_time = 0.0;
+                break;
+            }
 
+            // The game waits for the wipe, as vanilla's wipe loop holds up everything else.
+            match &mut self.wipe {
+                Some(melt) => {
+                    melt.tick();
+
+                    if melt.is_done() {
+                        self.wipe = None;
+                    }
+                },
+                None => {
+                    self.previous = TicSnapshot::capture(&self.map);
+                    self.ticker();
+                }
+            }
+
+            self.tic_time -= TIC_SECONDS;
+            tics += 1;
+        }
+    }
+
+    // G_Ticker and M_Ticker. A single player game stands still while the menu is up.
+    fn ticker(&mut self) {
+        self.menu.tick();
+
+        if !self.playing {
+            return;
+        }
+
+        let paused = self.menu.is_active() && !self.is_netgame();
+
+        if !paused {
+            self.map.player.tick();
+        }
+
+        if let Some(status_bar) = &mut self.status_bar {
+            status_bar.tick(&self.map.player, &mut self.random);
+        }
+
+        self.automap.tick(&self.map.player);
+        self.hud.tick();
+
+        self.game_tic += 1;
+    }
+
+    fn tic_frac(&self) -> Fixed {
+        Fixed::from_f64((self.tic_time / TIC_SECONDS).clamp(0.0, 1.0))
+    }
+
+    // The palette the frame is shown with, tinted by the player's flashes and powers.
+    fn palette(&self) -> &Palette {
+        self.renderer.data().palette(self.map.player.palette())
+    }
+
+    // Saves the last drawn frame as the next free DOOMnn file in `dir`.
+    pub fn screenshot(&self, dir: &Path, format: ScreenshotFormat) -> Result<PathBuf> {
+        save_screenshot(&self.backend, self.palette(), dir, format)
+    }
+
+    pub fn render(&mut self) -> Result<()> {
+        // The backend still holds the last frame shown, which is where the melt starts.
+        if self.wipe_pending {
+            self.wipe_pending = false;
+
+            let start = FrameBuffer::capture(&self.backend);
+            self.draw_frame();
+            let end = FrameBuffer::capture(&self.backend);
+
+            self.wipe = Some(Melt::new(start, end, &mut self.random));
+        }
+
+        // The menu stays on top while the screen melts under it.
+        match &self.wipe {
+            Some(melt) => {
+                melt.draw(&mut self.backend);
+                self.menu.draw(&mut self.backend, &self.settings);
+            },
+            None => self.draw_frame()
+        }
+
+        self.backend.present(self.renderer.data().palette(self.map.player.palette()))
+    }
+
+    fn draw_frame(&mut self) {
+        self.backend.clear(0);
+
+        if self.playing {
+            self.draw_level();
+        } else if let Some(title) = &self.title {
+            self.backend.draw_patch(0, 0, title);
+        }
+
+        self.menu.draw(&mut self.backend, &self.settings);
     }
 
-    pub fn render(&mut self) {
-        self.canvas.set_draw_color(Color::BLACK);
-        self.canvas.clear();
+    // The automap takes everything above the status bar; the view takes its window, with
+    // the border round it when that is smaller than the screen.
+    fn draw_level(&mut self) {
+        let frac = self.tic_frac();
+        let screen_width = self.backend.width();
+        let bar_top = self.status_bar_top();
+        let window = self.view_window();
+        let border_id = self.renderer.data().flat_id(BORDER_FLAT);
+
+        if self.automap.is_active() {
+            let mut view = Viewport::new(&mut self.backend, 0, 0, screen_width, bar_top);
+            self.automap.draw(&self.map, &mut view);
+        } else {
+            let (x, y, width, height) = window;
 
-        self.map.render_automap(&mut self.canvas);
+            if let (true, Some(id)) = (width < screen_width, border_id) {
+                self.view_border.draw(&mut self.backend, &self.renderer.data().flats[id], window, bar_top);
+            }
+
+            let mut view = Viewport::new(&mut self.backend, x, y, width, height);
+            self.renderer.render_player_view(&self.map, &self.previous, frac, &mut view);
+        }
 
-        self.canvas.present();
+        if let (true, Some(status_bar)) = (self.shows_status_bar(), &self.status_bar) {
+            let border = border_id.map(|id| &self.renderer.data().flats[id]);
+            status_bar.draw(&self.map.player, border, &mut self.backend);
+        }
+
+        self.hud.draw(&mut self.backend, self.automap.is_active());
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,156 @@
+use serde_json::{json, Value};
+use crate::export::mesh::Mesh;
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+const TRIANGLES: u32 = 4;
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - i * 6)) as usize & 63] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+fn float_bytes<const N: usize>(values: &[[f32; N]]) -> Vec<u8> {
+    values.iter().flatten().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+pub fn mesh_gltf(mesh: &Mesh, name: &str) -> String {
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut buffer_views: Vec<Value> = Vec::new();
+    let mut accessors: Vec<Value> = Vec::new();
+
+    let mut push_view = |buffer: &mut Vec<u8>, data: Vec<u8>, target: u32| {
+        let offset = buffer.len();
+        buffer.extend(data);
+        buffer_views.push(json!({
+            "buffer": 0,
+            "byteOffset": offset,
+            "byteLength": buffer.len() - offset,
+            "target": target
+        }));
+        buffer_views.len() - 1
+    };
+
+    let mut primitives: Vec<Value> = Vec::new();
+    let mut materials: Vec<Value> = Vec::new();
+
+    // glTF has no empty buffers or primitives, so a mesh with nothing in it is left out.
+    if !mesh.positions.is_empty() {
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+
+        for p in &mesh.positions {
+            for i in 0..3 {
+                min[i] = min[i].min(p[i]);
+                max[i] = max[i].max(p[i]);
+            }
+        }
+
+        let view = push_view(&mut buffer, float_bytes(&mesh.positions), ARRAY_BUFFER);
+        accessors.push(json!({
+            "bufferView": view, "componentType": FLOAT, "count": mesh.positions.len(), "type": "VEC3",
+            "min": min, "max": max
+        }));
+
+        let view = push_view(&mut buffer, float_bytes(&mesh.normals), ARRAY_BUFFER);
+        accessors.push(json!({ "bufferView": view, "componentType": FLOAT, "count": mesh.normals.len(), "type": "VEC3" }));
+
+        let view = push_view(&mut buffer, float_bytes(&mesh.uvs), ARRAY_BUFFER);
+        accessors.push(json!({ "bufferView": view, "componentType": FLOAT, "count": mesh.uvs.len(), "type": "VEC2" }));
+
+        for group in mesh.groups.iter().filter(|group| !group.indices.is_empty()) {
+            let indices = group.indices.iter().flat_map(|index| index.to_le_bytes()).collect();
+            let view = push_view(&mut buffer, indices, ELEMENT_ARRAY_BUFFER);
+
+            accessors.push(json!({
+                "bufferView": view, "componentType": UNSIGNED_INT, "count": group.indices.len(), "type": "SCALAR"
+            }));
+
+            primitives.push(json!({
+                "attributes": { "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2 },
+                "indices": accessors.len() - 1,
+                "material": materials.len(),
+                "mode": TRIANGLES
+            }));
+
+            materials.push(json!({
+                "name": group.material,
+                "pbrMetallicRoughness": { "metallicFactor": 0.0, "roughnessFactor": 1.0 }
+            }));
+        }
+    }
+
+    let mut gltf = json!({
+        "asset": { "version": "2.0", "generator": "doom-rs" },
+        "scene": 0,
+        "scenes": [{ "name": name, "nodes": [0] }],
+        "nodes": [{ "name": name }]
+    });
+
+    if !primitives.is_empty() {
+        gltf["nodes"][0]["mesh"] = json!(0);
+        gltf["meshes"] = json!([{ "name": name, "primitives": primitives }]);
+        gltf["materials"] = json!(materials);
+        gltf["buffers"] = json!([{
+            "byteLength": buffer.len(),
+            "uri": format!("data:application/octet-stream;base64,{}", base64(&buffer))
+        }]);
+        gltf["bufferViews"] = json!(buffer_views);
+        gltf["accessors"] = json!(accessors);
+    }
+
+    serde_json::to_string_pretty(&gltf).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::mesh::MeshGroup;
+
+    #[test]
+    fn empty_meshes_have_no_buffers() {
+        let gltf: Value = serde_json::from_str(&mesh_gltf(&Mesh::default(), "E1M1")).unwrap();
+
+        assert!(gltf.get("buffers").is_none());
+        assert!(gltf.get("meshes").is_none());
+        assert!(gltf["nodes"][0].get("mesh").is_none());
+    }
+
+    #[test]
+    fn empty_groups_are_skipped() {
+        let mesh = Mesh {
+            positions: vec![[0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            normals: vec![[0.0, 0.0, 1.0]; 3],
+            uvs: vec![[0.0; 2]; 3],
+            groups: vec![
+                MeshGroup { material: "EMPTY".to_string(), indices: Vec::new() },
+                MeshGroup { material: "FLAT".to_string(), indices: vec![0, 1, 2] }
+            ]
+        };
+        let gltf: Value = serde_json::from_str(&mesh_gltf(&mesh, "E1M1")).unwrap();
+
+        assert_eq!(gltf["meshes"][0]["primitives"].as_array().unwrap().len(), 1);
+        assert_eq!(gltf["meshes"][0]["primitives"][0]["material"], 0);
+        assert_eq!(gltf["materials"][0]["name"], "FLAT");
+        assert!(gltf["bufferViews"].as_array().unwrap().iter().all(|view| view["byteLength"].as_u64() > Some(0)));
+        assert_eq!(gltf["buffers"][0]["byteLength"], 3 * 12 + 3 * 12 + 3 * 8 + 3 * 4);
+    }
+}
@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use crate::export::triangulate::triangulate_sector;
+use crate::map::GameMap;
+use crate::wad::{Linedef, Sidedef, TextureDef, Vertex};
+
+const SKY_FLAT: &str = "F_SKY1";
+const FLAT_SIZE: f32 = 64.0;
+const DEFAULT_TEXTURE_SIZE: (f32, f32) = (64.0, 128.0);
+
+#[derive(Debug, Default, Clone)]
+pub struct MeshGroup {
+    pub material: String,
+    pub indices: Vec<u32>
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Mesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+    pub groups: Vec<MeshGroup>
+}
+
+struct WallPart<'a> {
+    start: Vertex,
+    end: Vertex,
+    bottom: f32,
+    top: f32,
+    texture: &'a str,
+    texture_top: f32,
+    sidedef: &'a Sidedef
+}
+
+struct MeshBuilder<'a> {
+    mesh: Mesh,
+    group_ids: HashMap<String, usize>,
+    texture_sizes: HashMap<String, (f32, f32)>,
+    map: &'a GameMap
+}
+
+// Doom's map is x east, y north, z up. Exports use a y-up right-handed space.
+fn to_3d(x: f32, y: f32, z: f32) -> [f32; 3] {
+    [x, z, -y]
+}
+
+impl<'a> MeshBuilder<'a> {
+    fn texture_size(&self, texture: &str) -> (f32, f32) {
+        self.texture_sizes.get(&texture.to_uppercase()).copied().unwrap_or(DEFAULT_TEXTURE_SIZE)
+    }
+
+    fn group(&mut self, material: &str) -> usize {
+        if let Some(&id) = self.group_ids.get(material) {
+            return id;
+        }
+
+        self.mesh.groups.push(MeshGroup { material: material.to_string(), indices: Vec::new() });
+        self.group_ids.insert(material.to_string(), self.mesh.groups.len() - 1);
+
+        self.mesh.groups.len() - 1
+    }
+
+    fn add_polygon(&mut self, material: &str, corners: &[[f32; 3]], uvs: &[[f32; 2]], normal: [f32; 3]) {
+        let group = self.group(material);
+        let first = self.mesh.positions.len() as u32;
+
+        for (&corner, &uv) in corners.iter().zip(uvs) {
+            self.mesh.positions.push(corner);
+            self.mesh.normals.push(normal);
+            self.mesh.uvs.push(uv);
+        }
+
+        for i in 1..corners.len() as u32 - 1 {
+            self.mesh.groups[group].indices.extend([first, first + i, first + i + 1]);
+        }
+    }
+
+    fn add_wall(&mut self, part: WallPart) {
+        if part.texture == Sidedef::NO_TEXTURE || part.top <= part.bottom {
+            return;
+        }
+
+        let (dx, dy) = (part.end.x as f32 - part.start.x as f32, part.end.y as f32 - part.start.y as f32);
+        let length = (dx * dx + dy * dy).sqrt();
+
+        if length == 0.0 {
+            return;
+        }
+
+        let (width, height) = self.texture_size(part.texture);
+        let u0 = part.sidedef.x_offset as f32 / width;
+        let u1 = (part.sidedef.x_offset as f32 + length) / width;
+        let v = |z: f32| (part.texture_top - z + part.sidedef.y_offset as f32) / height;
+
+        let (x1, y1) = (part.start.x as f32, part.start.y as f32);
+        let (x2, y2) = (part.end.x as f32, part.end.y as f32);
+
+        // Counterclockwise when seen from the side the wall faces.
+        self.add_polygon(
+            part.texture,
+            &[to_3d(x1, y1, part.bottom), to_3d(x2, y2, part.bottom), to_3d(x2, y2, part.top), to_3d(x1, y1, part.top)],
+            &[[u0, v(part.bottom)], [u1, v(part.bottom)], [u1, v(part.top)], [u0, v(part.top)]],
+            to_3d(dy / length, -dx / length, 0.0)
+        );
+    }
+
+    fn add_linedef(&mut self, linedef: &Linedef) {
+        let map = self.map;
+
+        let (Some(&v1), Some(&v2)) = (
+            map.vertexes.get(linedef.start_vertex_id as usize),
+            map.vertexes.get(linedef.end_vertex_id as usize)
+        ) else {
+            return;
+        };
+
+        let side = |sidedef_id: u16| map.sidedefs.get(sidedef_id as usize)
+            .and_then(|sidedef| map.sectors.get(sidedef.sector_id as usize).map(|sector| (sidedef, sector)));
+
+        let Some((front, front_sector)) = side(linedef.front_sidedef_id) else {
+            return;
+        };
+
+        let front_floor = front_sector.floor_height as f32;
+        let front_ceiling = front_sector.ceiling_height as f32;
+
+        if !linedef.has_back_sidedef() {
+            let (_, height) = self.texture_size(&front.middle_texture);
+            let texture_top = if linedef.has_flag(Linedef::LOWER_UNPEGGED) { front_floor + height } else { front_ceiling };
+
+            self.add_wall(WallPart {
+                start: v1, end: v2, bottom: front_floor, top: front_ceiling,
+                texture: &front.middle_texture, texture_top, sidedef: front
+            });
+            return;
+        }
+
+        let Some((back, back_sector)) = side(linedef.back_sidedef_id) else {
+            return;
+        };
+
+        let both_sky = front_sector.ceiling_texture == SKY_FLAT && back_sector.ceiling_texture == SKY_FLAT;
+
+        for (start, end, sidedef, sector, other) in [
+            (v1, v2, front, front_sector, back_sector),
+            (v2, v1, back, back_sector, front_sector)
+        ] {
+            let (floor, ceiling) = (sector.floor_height as f32, sector.ceiling_height as f32);
+            let (other_floor, other_ceiling) = (other.floor_height as f32, other.ceiling_height as f32);
+
+            if !both_sky {
+                let (_, height) = self.texture_size(&sidedef.upper_texture);
+                let texture_top = if linedef.has_flag(Linedef::UPPER_UNPEGGED) { ceiling } else { other_ceiling + height };
+
+                self.add_wall(WallPart {
+                    start, end, bottom: other_ceiling, top: ceiling,
+                    texture: &sidedef.upper_texture, texture_top, sidedef
+                });
+            }
+
+            let texture_top = if linedef.has_flag(Linedef::LOWER_UNPEGGED) { ceiling } else { other_floor };
+
+            self.add_wall(WallPart {
+                start, end, bottom: floor, top: other_floor,
+                texture: &sidedef.lower_texture, texture_top, sidedef
+            });
+
+            // A masked middle texture is drawn once, not tiled, and clipped to the opening.
+            let (_, height) = self.texture_size(&sidedef.middle_texture);
+            let (opening_bottom, opening_top) = (floor.max(other_floor), ceiling.min(other_ceiling));
+            let texture_top = if linedef.has_flag(Linedef::LOWER_UNPEGGED) { opening_bottom + height } else { opening_top };
+            let masked_top = texture_top + sidedef.y_offset as f32;
+
+            self.add_wall(WallPart {
+                start, end, bottom: opening_bottom.max(masked_top - height), top: opening_top.min(masked_top),
+                texture: &sidedef.middle_texture, texture_top, sidedef
+            });
+        }
+    }
+
+    fn add_sector(&mut self, sector_id: usize) {
+        let sector = &self.map.sectors[sector_id];
+        let (floor, ceiling) = (sector.floor_height as f32, sector.ceiling_height as f32);
+
+        let flat_uv = |p: [f64; 2]| [p[0] as f32 / FLAT_SIZE, -p[1] as f32 / FLAT_SIZE];
+
+        for [a, b, c] in triangulate_sector(self.map, sector_id) {
+            let point = |p: [f64; 2], z: f32| to_3d(p[0] as f32, p[1] as f32, z);
+
+            self.add_polygon(
+                &sector.floor_texture,
+                &[point(a, floor), point(b, floor), point(c, floor)],
+                &[flat_uv(a), flat_uv(b), flat_uv(c)],
+                [0.0, 1.0, 0.0]
+            );
+
+            self.add_polygon(
+                &sector.ceiling_texture,
+                &[point(c, ceiling), point(b, ceiling), point(a, ceiling)],
+                &[flat_uv(c), flat_uv(b), flat_uv(a)],
+                [0.0, -1.0, 0.0]
+            );
+        }
+    }
+}
+
+pub fn map_mesh(map: &GameMap, textures: &[TextureDef]) -> Mesh {
+    let texture_sizes = textures.iter()
+        .map(|texture| (texture.name.to_uppercase(), (texture.width as f32, texture.height as f32)))
+        .collect();
+
+    let mut builder = MeshBuilder {
+        mesh: Mesh::default(),
+        group_ids: HashMap::new(),
+        texture_sizes,
+        map
+    };
+
+    for linedef in &map.linedefs {
+        builder.add_linedef(linedef);
+    }
+
+    for sector_id in 0..map.sectors.len() {
+        builder.add_sector(sector_id);
+    }
+
+    builder.mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad::Sector;
+
+    fn sector(floor_height: i16, ceiling_height: i16) -> Sector {
+        Sector {
+            floor_height,
+            ceiling_height,
+            floor_texture: "FLAT".to_string(),
+            ceiling_texture: "FLAT".to_string(),
+            light_level: 160,
+            special: 0,
+            tag: 0
+        }
+    }
+
+    fn sidedef(sector_id: u16, y_offset: i16) -> Sidedef {
+        Sidedef {
+            x_offset: 0,
+            y_offset,
+            upper_texture: Sidedef::NO_TEXTURE.to_string(),
+            lower_texture: Sidedef::NO_TEXTURE.to_string(),
+            middle_texture: "GRATE".to_string(),
+            sector_id
+        }
+    }
+
+    // A lone two-sided line with a 32 high grate between a room and a lower-ceilinged step.
+    fn grate_map(flags: u16, y_offset: i16) -> GameMap {
+        let mut map = GameMap::with_name("TEST");
+
+        map.vertexes = vec![Vertex { x: 0, y: 0 }, Vertex { x: 64, y: 0 }];
+        map.linedefs = vec![Linedef {
+            start_vertex_id: 0,
+            end_vertex_id: 1,
+            flags: Linedef::TWO_SIDED | flags,
+            line_type: 0,
+            sector_tag: 0,
+            front_sidedef_id: 0,
+            back_sidedef_id: 1
+        }];
+        map.sidedefs = vec![sidedef(0, y_offset), sidedef(1, y_offset)];
+        map.sectors = vec![sector(0, 128), sector(16, 112)];
+        map
+    }
+
+    fn grate_heights(map: &GameMap) -> Vec<(f32, f32)> {
+        let textures = [TextureDef { name: "GRATE".to_string(), width: 64, height: 32, patches: Vec::new() }];
+        let mesh = map_mesh(map, &textures);
+        let group = mesh.groups.iter().find(|group| group.material == "GRATE").unwrap();
+
+        group.indices.chunks(6)
+            .map(|quad| {
+                let heights = quad.iter().map(|&i| mesh.positions[i as usize][1]);
+                (heights.clone().fold(f32::MAX, f32::min), heights.fold(f32::MIN, f32::max))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn masked_middles_hang_from_the_opening() {
+        assert_eq!(grate_heights(&grate_map(0, 0)), [(80.0, 112.0), (80.0, 112.0)]);
+    }
+
+    #[test]
+    fn lower_unpegged_masked_middles_stand_on_the_opening() {
+        assert_eq!(grate_heights(&grate_map(Linedef::LOWER_UNPEGGED, 0)), [(16.0, 48.0), (16.0, 48.0)]);
+    }
+
+    #[test]
+    fn masked_middles_are_clipped_to_the_opening() {
+        assert_eq!(grate_heights(&grate_map(0, 8)), [(88.0, 112.0), (88.0, 112.0)]);
+    }
+}
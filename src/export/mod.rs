@@ -0,0 +1,13 @@
+mod svg;
+pub use svg::{automap_svg, SvgOptions};
+
+mod triangulate;
+
+mod mesh;
+pub use mesh::{map_mesh, Mesh, MeshGroup};
+
+mod obj;
+pub use obj::{mesh_mtl, mesh_obj};
+
+mod gltf;
+pub use gltf::mesh_gltf;
\ No newline at end of file
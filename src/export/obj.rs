@@ -0,0 +1,46 @@
+use std::fmt::Write;
+use crate::export::mesh::Mesh;
+
+pub fn mesh_obj(mesh: &Mesh, mtl_file_name: &str) -> String {
+    let mut obj = String::new();
+
+    writeln!(obj, "mtllib {}", mtl_file_name).unwrap();
+
+    for p in &mesh.positions {
+        writeln!(obj, "v {} {} {}", p[0], p[1], p[2]).unwrap();
+    }
+
+    // OBJ texture coordinates start at the bottom-left.
+    for uv in &mesh.uvs {
+        writeln!(obj, "vt {} {}", uv[0], 1.0 - uv[1]).unwrap();
+    }
+
+    for n in &mesh.normals {
+        writeln!(obj, "vn {} {} {}", n[0], n[1], n[2]).unwrap();
+    }
+
+    for group in &mesh.groups {
+        writeln!(obj, "g {}", group.material).unwrap();
+        writeln!(obj, "usemtl {}", group.material).unwrap();
+
+        for triangle in group.indices.chunks(3) {
+            let [a, b, c] = [triangle[0] + 1, triangle[1] + 1, triangle[2] + 1];
+            writeln!(obj, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}").unwrap();
+        }
+    }
+
+    obj
+}
+
+pub fn mesh_mtl(mesh: &Mesh) -> String {
+    let mut mtl = String::new();
+
+    for group in &mesh.groups {
+        writeln!(mtl, "newmtl {}", group.material).unwrap();
+        writeln!(mtl, "Kd 1 1 1").unwrap();
+        writeln!(mtl, "map_Kd {}.png", group.material).unwrap();
+        writeln!(mtl).unwrap();
+    }
+
+    mtl
+}
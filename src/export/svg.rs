@@ -0,0 +1,207 @@
+use std::fmt::Write;
+use crate::map::{GameMap, LineKind};
+use crate::things::{thing_info, ThingKind};
+
+const PADDING: f64 = 64.0;
+const PLAYER_START_TYPES: [i16; 5] = [1, 2, 3, 4, 11];
+
+#[derive(Debug, Copy, Clone)]
+pub struct SvgOptions {
+    pub width: Option<u32>,
+    pub things: bool,
+    pub partitions: bool,
+    pub subsectors: bool
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            width: Some(1024),
+            things: false,
+            partitions: false,
+            subsectors: false
+        }
+    }
+}
+
+fn line_color(kind: LineKind) -> &'static str {
+    match kind {
+        LineKind::OneSided => "#fc0000",
+        LineKind::Secret => "#ff00ff",
+        LineKind::Special => "#4080ff",
+        LineKind::FloorStep => "#bc7844",
+        LineKind::CeilingStep => "#fcfc00",
+        LineKind::TwoSided => "#808080"
+    }
+}
+
+fn thing_color(ed_type: i16) -> &'static str {
+    if PLAYER_START_TYPES.contains(&ed_type) {
+        return "#00ff00";
+    }
+
+    match thing_info(ed_type).map(|info| info.kind) {
+        Some(ThingKind::Monster { .. }) => "#ff4040",
+        Some(ThingKind::Weapon { .. }) | Some(ThingKind::Ammo { .. }) | Some(ThingKind::Backpack) => "#ffa000",
+        Some(ThingKind::Key) => "#ffff00",
+        Some(_) => "#40c0ff",
+        None => "#a0a0a0"
+    }
+}
+
+// Map names come from lump names or the command line, so they may hold markup.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+pub fn automap_svg(map: &GameMap, options: &SvgOptions) -> String {
+    let bounds = &map.bounds;
+    let map_w = (bounds.max_x as f64 - bounds.min_x as f64) + PADDING * 2.0;
+    let map_h = (bounds.max_y as f64 - bounds.min_y as f64) + PADDING * 2.0;
+    let stroke = (map_w.max(map_h) / 1000.0).max(1.0);
+
+    let x = |x: i16| x as f64 - bounds.min_x as f64 + PADDING;
+    let y = |y: i16| bounds.max_y as f64 - y as f64 + PADDING;
+
+    let mut svg = String::new();
+    let size = match options.width {
+        Some(width) => format!(r#" width="{}" height="{:.0}""#, width, width as f64 * map_h / map_w),
+        None => String::new()
+    };
+
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg"{} viewBox="0 0 {} {}" stroke-linecap="round">"#,
+        size, map_w, map_h
+    ).unwrap();
+    writeln!(svg, r#"<title>{}</title>"#, escape(&map.map_name)).unwrap();
+    writeln!(svg, r#"<rect width="100%" height="100%" fill="black"/>"#).unwrap();
+
+    writeln!(svg, r#"<g id="linedefs" stroke-width="{}">"#, stroke).unwrap();
+    for linedef in &map.linedefs {
+        let (Some(start), Some(end)) = (
+            map.vertexes.get(linedef.start_vertex_id as usize),
+            map.vertexes.get(linedef.end_vertex_id as usize)
+        ) else {
+            continue;
+        };
+
+        writeln!(
+            svg,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}"/>"#,
+            x(start.x), y(start.y), x(end.x), y(end.y), line_color(map.linedef_kind(linedef))
+        ).unwrap();
+    }
+    writeln!(svg, "</g>").unwrap();
+
+    if options.subsectors {
+        writeln!(svg, r#"<g id="subsectors" stroke-width="{}">"#, stroke * 2.0).unwrap();
+        for (sub_sector_id, sub_sector) in map.ssectors.iter().enumerate() {
+            let color = map.get_color(sub_sector_id as u64);
+            let first = sub_sector.first_seg_id as u16 as usize;
+            let count = sub_sector.seg_count as u16 as usize;
+
+            for seg in map.segs.iter().skip(first).take(count) {
+                let (Some(start), Some(end)) = (
+                    map.vertexes.get(seg.start_vertex_id as u16 as usize),
+                    map.vertexes.get(seg.end_vertex_id as u16 as usize)
+                ) else {
+                    continue;
+                };
+
+                writeln!(
+                    svg,
+                    "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#{:02x}{:02x}{:02x}\"/>",
+                    x(start.x), y(start.y), x(end.x), y(end.y), color.r, color.g, color.b
+                ).unwrap();
+            }
+        }
+        writeln!(svg, "</g>").unwrap();
+    }
+
+    if options.partitions {
+        writeln!(
+            svg,
+            r##"<g id="partitions" stroke="#0000ff" stroke-width="{}" stroke-dasharray="{} {}">"##,
+            stroke, stroke * 4.0, stroke * 4.0
+        ).unwrap();
+        for node in &map.nodes {
+            writeln!(
+                svg,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}"/>"#,
+                x(node.x_partition),
+                y(node.y_partition),
+                x(node.x_partition.saturating_add(node.dx_partition)),
+                y(node.y_partition.saturating_add(node.dy_partition))
+            ).unwrap();
+        }
+        writeln!(svg, "</g>").unwrap();
+    }
+
+    if options.things {
+        writeln!(svg, r#"<g id="things">"#).unwrap();
+        for thing in &map.things {
+            writeln!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+                x(thing.position.x), y(thing.position.y), stroke * 6.0, thing_color(thing.ed_type)
+            ).unwrap();
+        }
+        writeln!(svg, "</g>").unwrap();
+    }
+
+    writeln!(svg, "</svg>").unwrap();
+
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad::{Linedef, Point, Thing, Vertex};
+
+    fn linedef(start_vertex_id: u16, end_vertex_id: u16) -> Linedef {
+        Linedef {
+            start_vertex_id,
+            end_vertex_id,
+            flags: 0,
+            line_type: 0,
+            sector_tag: 0,
+            front_sidedef_id: 0,
+            back_sidedef_id: Linedef::NO_SIDEDEF
+        }
+    }
+
+    // The elements between `<g id="{id}"` and the group's end.
+    fn group_count(svg: &str, id: &str, element: &str) -> Option<usize> {
+        let start = svg.find(&format!(r#"<g id="{}""#, id))?;
+        let group = &svg[start..start + svg[start..].find("</g>").unwrap()];
+
+        Some(group.matches(element).count())
+    }
+
+    #[test]
+    fn groups_hold_every_line_and_thing() {
+        let mut map = GameMap::with_name("A&B <E1M1>");
+
+        map.vertexes = vec![Vertex { x: 0, y: 0 }, Vertex { x: 128, y: 0 }, Vertex { x: 128, y: 64 }, Vertex { x: 0, y: 64 }];
+        // The last line's missing vertex leaves it out.
+        map.linedefs = vec![linedef(0, 1), linedef(1, 2), linedef(2, 3), linedef(3, 0), linedef(3, 9)];
+        map.things = [1, 3004, 2001]
+            .map(|ed_type| Thing { position: Point { x: 32, y: 32 }, angle: 0, ed_type, flags: 7 })
+            .to_vec();
+        map.calc_map_bounds();
+
+        let options = SvgOptions { things: true, ..SvgOptions::default() };
+        let svg = automap_svg(&map, &options);
+
+        assert!(svg.contains("<title>A&amp;B &lt;E1M1&gt;</title>"));
+        assert_eq!(group_count(&svg, "linedefs", "<line "), Some(4));
+        assert_eq!(group_count(&svg, "things", "<circle "), Some(3));
+        assert_eq!(group_count(&svg, "partitions", "<line "), None);
+        assert_eq!(group_count(&svg, "subsectors", "<line "), None);
+
+        let svg = automap_svg(&map, &SvgOptions::default());
+        assert_eq!(group_count(&svg, "things", "<circle "), None);
+    }
+}
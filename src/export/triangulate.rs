@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+use crate::map::GameMap;
+
+pub(crate) type Point2 = [f64; 2];
+
+pub(crate) fn triangulate_sector(map: &GameMap, sector_id: usize) -> Vec<[Point2; 3]> {
+    let loops = trace_sector_loops(map, sector_id);
+
+    let mut outers: Vec<Vec<Point2>> = Vec::new();
+    let mut holes: Vec<Vec<Point2>> = Vec::new();
+
+    // The sector is on the right of every traced edge, so outer boundaries come out
+    // clockwise and holes counterclockwise.
+    for mut polygon in loops {
+        let area = signed_area(&polygon);
+
+        if area < 0.0 {
+            polygon.reverse();
+            outers.push(polygon);
+        } else if area > 0.0 {
+            polygon.reverse();
+            holes.push(polygon);
+        }
+    }
+
+    let mut outer_holes: Vec<Vec<Vec<Point2>>> = vec![Vec::new(); outers.len()];
+
+    for hole in holes {
+        let container = outers.iter()
+            .enumerate()
+            .filter(|(_, outer)| contains_point(outer, hole[0]))
+            .min_by(|(_, a), (_, b)| signed_area(a).total_cmp(&signed_area(b)))
+            .map(|(i, _)| i);
+
+        if let Some(i) = container {
+            outer_holes[i].push(hole);
+        }
+    }
+
+    let mut triangles: Vec<[Point2; 3]> = Vec::new();
+
+    for (outer, holes) in outers.into_iter().zip(outer_holes) {
+        let polygon = bridge_holes(outer, holes);
+        triangles.extend(ear_clip(polygon));
+    }
+
+    triangles
+}
+
+fn trace_sector_loops(map: &GameMap, sector_id: usize) -> Vec<Vec<Point2>> {
+    let sidedef_sector = |sidedef_id: u16| map.sidedefs.get(sidedef_id as usize).map(|sidedef| sidedef.sector_id as usize);
+
+    let mut edge_counts: HashMap<(usize, usize), i32> = HashMap::new();
+
+    for linedef in &map.linedefs {
+        let (v1, v2) = (linedef.start_vertex_id as usize, linedef.end_vertex_id as usize);
+
+        if v1 >= map.vertexes.len() || v2 >= map.vertexes.len() || v1 == v2 {
+            continue;
+        }
+
+        if sidedef_sector(linedef.front_sidedef_id) == Some(sector_id) {
+            *edge_counts.entry((v1, v2)).or_default() += 1;
+        }
+
+        if linedef.has_back_sidedef() && sidedef_sector(linedef.back_sidedef_id) == Some(sector_id) {
+            *edge_counts.entry((v2, v1)).or_default() += 1;
+        }
+    }
+
+    // Lines with the sector on both sides cancel out and don't bound anything.
+    let mut edges: Vec<(usize, usize)> = Vec::new();
+
+    for (&(a, b), &count) in &edge_counts {
+        let reverse = edge_counts.get(&(b, a)).copied().unwrap_or_default();
+
+        for _ in 0..(count - reverse).max(0) {
+            edges.push((a, b));
+        }
+    }
+
+    edges.sort();
+
+    let mut outgoing: HashMap<usize, Vec<usize>> = HashMap::new();
+
+    for (i, &(a, _)) in edges.iter().enumerate() {
+        outgoing.entry(a).or_default().push(i);
+    }
+
+    let point = |vertex_id: usize| {
+        let vertex = map.vertexes[vertex_id];
+        [vertex.x as f64, vertex.y as f64]
+    };
+
+    let mut used = vec![false; edges.len()];
+    let mut loops: Vec<Vec<Point2>> = Vec::new();
+
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+
+        used[first] = true;
+
+        let start = edges[first].0;
+        let mut polygon = vec![point(start)];
+        let (mut previous, mut current) = edges[first];
+
+        let closed = loop {
+            if current == start {
+                break true;
+            }
+
+            polygon.push(point(current));
+
+            let back = angle(point(current), point(previous));
+
+            // Take the sharpest right turn so the walk hugs the sector interior.
+            let next = outgoing.get(&current)
+                .into_iter()
+                .flatten()
+                .copied()
+                .filter(|&edge| !used[edge])
+                .min_by(|&a, &b| {
+                    let turn_a = (angle(point(current), point(edges[a].1)) - back).rem_euclid(TAU);
+                    let turn_b = (angle(point(current), point(edges[b].1)) - back).rem_euclid(TAU);
+                    turn_a.total_cmp(&turn_b)
+                });
+
+            let Some(next) = next else {
+                break false;
+            };
+
+            used[next] = true;
+            (previous, current) = edges[next];
+        };
+
+        if closed && polygon.len() >= 3 {
+            loops.push(polygon);
+        }
+    }
+
+    loops
+}
+
+fn angle(from: Point2, to: Point2) -> f64 {
+    (to[1] - from[1]).atan2(to[0] - from[0])
+}
+
+fn signed_area(polygon: &[Point2]) -> f64 {
+    let mut area = 0.0;
+
+    for i in 0..polygon.len() {
+        let a = polygon[i];
+        let b = polygon[(i + 1) % polygon.len()];
+        area += a[0] * b[1] - b[0] * a[1];
+    }
+
+    area / 2.0
+}
+
+fn cross(a: Point2, b: Point2, c: Point2) -> f64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+fn contains_point(polygon: &[Point2], point: Point2) -> bool {
+    let mut inside = false;
+    let mut j = polygon.len() - 1;
+
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[i], polygon[j]);
+
+        if (a[1] > point[1]) != (b[1] > point[1])
+            && point[0] < (b[0] - a[0]) * (point[1] - a[1]) / (b[1] - a[1]) + a[0] {
+            inside = !inside;
+        }
+
+        j = i;
+    }
+
+    inside
+}
+
+fn segments_cross(a1: Point2, a2: Point2, b1: Point2, b2: Point2) -> bool {
+    let d1 = cross(b1, b2, a1);
+    let d2 = cross(b1, b2, a2);
+    let d3 = cross(a1, a2, b1);
+    let d4 = cross(a1, a2, b2);
+
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
+fn bridge_holes(outer: Vec<Point2>, mut holes: Vec<Vec<Point2>>) -> Vec<Point2> {
+    let max_x = |polygon: &[Point2]| polygon.iter().map(|p| p[0]).fold(f64::MIN, f64::max);
+
+    holes.sort_by(|a, b| max_x(b).total_cmp(&max_x(a)));
+
+    let mut polygon = outer;
+
+    for (i, hole) in holes.iter().enumerate() {
+        let hole_id = (0..hole.len())
+            .max_by(|&a, &b| hole[a][0].total_cmp(&hole[b][0]))
+            .unwrap();
+        let m = hole[hole_id];
+
+        let blocked = |p: Point2| {
+            let edges = std::iter::once(&polygon).chain(holes[i..].iter())
+                .flat_map(|ring| (0..ring.len()).map(move |k| (ring[k], ring[(k + 1) % ring.len()])));
+
+            for (a, b) in edges {
+                if segments_cross(m, p, a, b) {
+                    return true;
+                }
+            }
+
+            false
+        };
+
+        let distance = |p: Point2| (p[0] - m[0]).powi(2) + (p[1] - m[1]).powi(2);
+
+        let bridge_id = (0..polygon.len())
+            .filter(|&k| !blocked(polygon[k]))
+            .min_by(|&a, &b| distance(polygon[a]).total_cmp(&distance(polygon[b])));
+
+        let Some(bridge_id) = bridge_id else {
+            continue;
+        };
+
+        let mut merged: Vec<Point2> = Vec::with_capacity(polygon.len() + hole.len() + 2);
+        merged.extend_from_slice(&polygon[..=bridge_id]);
+        merged.extend(hole[hole_id..].iter().chain(hole[..=hole_id].iter()));
+        merged.extend_from_slice(&polygon[bridge_id..]);
+
+        polygon = merged;
+    }
+
+    polygon
+}
+
+fn ear_clip(mut polygon: Vec<Point2>) -> Vec<[Point2; 3]> {
+    let mut triangles: Vec<[Point2; 3]> = Vec::new();
+
+    while polygon.len() > 3 {
+        let n = polygon.len();
+        let mut clipped = false;
+
+        for i in 0..n {
+            let (a, b, c) = (polygon[(i + n - 1) % n], polygon[i], polygon[(i + 1) % n]);
+            let turn = cross(a, b, c);
+
+            if turn.abs() < f64::EPSILON {
+                polygon.remove(i);
+                clipped = true;
+                break;
+            }
+
+            if turn < 0.0 {
+                continue;
+            }
+
+            let is_ear = polygon.iter().all(|&p| {
+                p == a || p == b || p == c
+                    || cross(a, b, p) < 0.0 || cross(b, c, p) < 0.0 || cross(c, a, p) < 0.0
+            });
+
+            if is_ear {
+                triangles.push([a, b, c]);
+                polygon.remove(i);
+                clipped = true;
+                break;
+            }
+        }
+
+        // Self-touching outlines can leave no valid ear, so force progress rather than spin.
+        if !clipped {
+            let (a, b, c) = (polygon[n - 1], polygon[0], polygon[1]);
+
+            if cross(a, b, c) > 0.0 {
+                triangles.push([a, b, c]);
+            }
+
+            polygon.remove(0);
+        }
+    }
+
+    if polygon.len() == 3 && cross(polygon[0], polygon[1], polygon[2]) > 0.0 {
+        triangles.push([polygon[0], polygon[1], polygon[2]]);
+    }
+
+    triangles
+}
@@ -0,0 +1,74 @@
+use anyhow::Result;
+use crate::backend::{DrawTarget, BASE_WIDTH};
+use crate::wad::{Loader, Patch};
+
+// STCFN033 to STCFN095: '!' to '_'. Lower case is drawn in upper case, anything else
+// leaves a gap.
+const FONT_START: u8 = b'!';
+const FONT_END: u8 = b'_';
+const SPACE_WIDTH: i32 = 4;
+
+// hu_font: the small font of the HUD and menus.
+pub struct HudFont {
+    glyphs: Vec<Patch>
+}
+
+impl HudFont {
+    pub fn load(loader: &mut Loader) -> Result<Self> {
+        Ok(
+            Self {
+                glyphs: (FONT_START..=FONT_END)
+                    .map(|c| loader.load_patch(&format!("STCFN{:03}", c)))
+                    .collect::<Result<_>>()?
+            }
+        )
+    }
+
+    pub fn height(&self) -> i32 {
+        self.glyphs[0].height as i32
+    }
+
+    pub fn glyph(&self, c: char) -> Option<&Patch> {
+        let c = c.to_ascii_uppercase();
+
+        (c.is_ascii() && (FONT_START..=FONT_END).contains(&(c as u8)))
+            .then(|| &self.glyphs[(c as u8 - FONT_START) as usize])
+    }
+
+    fn char_width(&self, c: char) -> i32 {
+        self.glyph(c).map_or(SPACE_WIDTH, |glyph| glyph.width as i32)
+    }
+
+    // M_StringWidth
+    pub fn text_width(&self, text: &str) -> i32 {
+        text.chars().map(|c| self.char_width(c)).sum()
+    }
+
+    // HUlib_drawTextLine, in 320x200 coordinates: stops at the right edge of the screen and
+    // returns where the next character would go.
+    pub fn draw_text(&self, target: &mut dyn DrawTarget, x: i32, y: i32, text: &str) -> i32 {
+        let mut x = x;
+
+        for c in text.chars() {
+            match self.glyph(c) {
+                Some(glyph) if c != ' ' => {
+                    if x + glyph.width as i32 > BASE_WIDTH as i32 {
+                        break;
+                    }
+
+                    target.draw_patch(x, y, glyph);
+                    x += glyph.width as i32;
+                },
+                _ => {
+                    x += SPACE_WIDTH;
+
+                    if x >= BASE_WIDTH as i32 {
+                        break;
+                    }
+                }
+            }
+        }
+
+        x
+    }
+}
@@ -0,0 +1,452 @@
+use std::collections::VecDeque;
+use crate::backend::{DrawTarget, BASE_WIDTH};
+use crate::engine::TIC_RATE;
+use crate::input::{shift_char, Event, Key};
+use crate::wad::Loader;
+
+mod font;
+pub use font::HudFont;
+
+mod titles;
+pub use titles::map_title;
+
+pub const MAX_PLAYERS: usize = 4;
+
+// hu_stuff.c's layout: messages top left, the chat line under them and the map title just
+// above the status bar.
+const MESSAGE_X: i32 = 0;
+const MESSAGE_Y: i32 = 0;
+const TITLE_X: i32 = 0;
+const TITLE_BOTTOM: i32 = 167;
+
+const MESSAGE_TICS: i32 = 4 * TIC_RATE as i32;
+const MAX_LINE_LENGTH: usize = 80;
+
+const PLAYER_NAMES: [&str; MAX_PLAYERS] = ["Green: ", "Indigo: ", "Brown: ", "Red: "];
+const DESTINATION_KEYS: [char; MAX_PLAYERS] = ['g', 'i', 'b', 'r'];
+const CHAT_KEY: char = 't';
+
+// HUSTR_CHATMACRO0 to 9, sent with Alt and a digit.
+const CHAT_MACROS: [&str; 10] = [
+    "No",
+    "I'm ready to kick butt!",
+    "I'm OK.",
+    "I'm not looking too good!",
+    "Help!",
+    "You suck!",
+    "Next time, scumbag...",
+    "Come here!",
+    "I'll take care of it.",
+    "Yes"
+];
+
+// What trying to talk to yourself says, more worrying after 3, 6, 9 and 32 tries.
+const TALK_TO_SELF: [&str; 5] = [
+    "You mumble to yourself",
+    "Who's there?",
+    "You scare yourself",
+    "You start to rave",
+    "You've lost it..."
+];
+const TALK_TO_SELF_TRIES: [u32; 4] = [3, 6, 9, 32];
+
+// A line typed into chat, for the netcode to send. No destination means everybody.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMessage {
+    pub destination: Option<usize>,
+    pub text: String
+}
+
+struct ChatInput {
+    destination: Option<usize>,
+    text: String
+}
+
+// hu_stuff.c: the message line, the chat line and the automap title.
+pub struct Hud {
+    font: Option<HudFont>,
+    title: String,
+    message: String,
+    message_on: bool,
+    message_tics: i32,
+    // Chat and forced messages can't be pushed off by a pickup until they time out.
+    message_held: bool,
+    pending: Option<(String, bool)>,
+    show_messages: bool,
+    console_player: usize,
+    players_in_game: [bool; MAX_PLAYERS],
+    chat: Option<ChatInput>,
+    outgoing: VecDeque<ChatMessage>,
+    shift_down: bool,
+    alt_down: bool,
+    talked_to_self: u32
+}
+
+impl Hud {
+    // HU_Init. Without the font messages are still kept, just not drawn.
+    pub fn load(loader: &mut Loader) -> Self {
+        Self {
+            font: HudFont::load(loader).ok(),
+            ..Self::new()
+        }
+    }
+
+    // A single player HUD without a font.
+    fn new() -> Self {
+        let mut players_in_game = [false; MAX_PLAYERS];
+        players_in_game[0] = true;
+
+        Self {
+            font: None,
+            title: String::new(),
+            message: String::new(),
+            message_on: false,
+            message_tics: 0,
+            message_held: false,
+            pending: None,
+            show_messages: true,
+            console_player: 0,
+            players_in_game,
+            chat: None,
+            outgoing: VecDeque::new(),
+            shift_down: false,
+            alt_down: false,
+            talked_to_self: 0
+        }
+    }
+
+    pub fn font(&self) -> Option<&HudFont> {
+        self.font.as_ref()
+    }
+
+    // HU_Start
+    pub fn start(&mut self, map_name: &str) {
+        self.title = map_title(map_name).map_or_else(|| map_name.to_uppercase(), str::to_string);
+        self.message_on = false;
+        self.message_held = false;
+        self.chat = None;
+    }
+
+    pub fn set_players(&mut self, console_player: usize, players_in_game: [bool; MAX_PLAYERS]) {
+        self.console_player = console_player;
+        self.players_in_game = players_in_game;
+    }
+
+    pub fn player_count(&self) -> usize {
+        self.players_in_game.iter().filter(|&&in_game| in_game).count()
+    }
+
+    pub fn show_messages(&self) -> bool {
+        self.show_messages
+    }
+
+    pub fn set_show_messages(&mut self, show_messages: bool) {
+        self.show_messages = show_messages;
+    }
+
+    // Sets plr->message: shown on the next tic, or once a chat message has had its time.
+    pub fn post_message(&mut self, text: &str) {
+        self.pending = Some((text.to_string(), false));
+    }
+
+    // message_dontfuckwithme: shown even with messages off, and held like a chat message.
+    pub fn force_message(&mut self, text: &str) {
+        self.pending = Some((text.to_string(), true));
+    }
+
+    pub fn is_chatting(&self) -> bool {
+        self.chat.is_some()
+    }
+
+    // The next line the console player has sent, oldest first.
+    pub fn take_chat(&mut self) -> Option<ChatMessage> {
+        self.outgoing.pop_front()
+    }
+
+    // A line from another player, shown if it was meant for everybody or for us.
+    pub fn receive_chat(&mut self, from: usize, destination: Option<usize>, text: &str) {
+        if text.is_empty() || destination.is_some_and(|player| player != self.console_player) {
+            return;
+        }
+
+        let name = PLAYER_NAMES.get(from).copied().unwrap_or_default();
+
+        self.message = format!("{}{}", name, text);
+        self.message_on = true;
+        self.message_tics = MESSAGE_TICS;
+        self.message_held = true;
+    }
+
+    // HU_Ticker
+    pub fn tick(&mut self) {
+        if self.message_tics > 0 {
+            self.message_tics -= 1;
+
+            if self.message_tics == 0 {
+                self.message_on = false;
+                self.message_held = false;
+            }
+        }
+
+        // A message that can't be shown yet waits, as plr->message does.
+        if let Some((message, forced)) = self.pending.take() {
+            if forced || (self.show_messages && !self.message_held) {
+                self.message = message;
+                self.message_on = true;
+                self.message_tics = MESSAGE_TICS;
+                self.message_held = forced;
+            } else {
+                self.pending = Some((message, forced));
+            }
+        }
+    }
+
+    fn send(&mut self, destination: Option<usize>, text: &str) {
+        self.outgoing.push_back(ChatMessage { destination, text: text.to_string() });
+        self.post_message(text);
+    }
+
+    // HU_Responder: whether the HUD took the event.
+    pub fn responder(&mut self, event: Event) -> bool {
+        let key = match event {
+            Event::KeyDown(Key::Shift) | Event::KeyUp(Key::Shift) => {
+                self.shift_down = event == Event::KeyDown(Key::Shift);
+                return false;
+            },
+            Event::KeyDown(Key::Alt) | Event::KeyUp(Key::Alt) => {
+                self.alt_down = event == Event::KeyDown(Key::Alt);
+                return false;
+            },
+            Event::KeyDown(key) => key,
+            _ => return false
+        };
+
+        let Some(chat) = &mut self.chat else {
+            return self.start_chat(key);
+        };
+
+        match key {
+            Key::Char(digit @ '0'..='9') if self.alt_down => {
+                let destination = chat.destination;
+                let macro_message = CHAT_MACROS[digit as usize - '0' as usize];
+
+                self.chat = None;
+                self.send(destination, macro_message);
+            },
+            Key::Char(c) => {
+                let c = if self.shift_down || c.is_ascii_lowercase() { shift_char(c) } else { c };
+
+                if (' '..='_').contains(&c) && chat.text.len() < MAX_LINE_LENGTH {
+                    chat.text.push(c);
+                }
+            },
+            Key::Backspace => {
+                chat.text.pop();
+            },
+            Key::Enter => {
+                let (destination, text) = (chat.destination, std::mem::take(&mut chat.text));
+
+                self.chat = None;
+
+                if !text.is_empty() {
+                    self.send(destination, &text);
+                }
+            },
+            Key::Escape => self.chat = None,
+            _ => return false
+        }
+
+        true
+    }
+
+    // Enter brings the last message back. In a netgame T talks to everybody, and with more
+    // than two players G, I, B and R talk to one of them.
+    fn start_chat(&mut self, key: Key) -> bool {
+        let netgame = self.player_count() > 1;
+
+        match key {
+            Key::Enter => {
+                self.message_on = true;
+                self.message_tics = MESSAGE_TICS;
+                true
+            },
+            Key::Char(CHAT_KEY) if netgame => {
+                self.chat = Some(ChatInput { destination: None, text: String::new() });
+                true
+            },
+            Key::Char(c) if netgame && self.player_count() > 2 => {
+                let Some(player) = DESTINATION_KEYS.iter().position(|&key| key == c) else {
+                    return false;
+                };
+
+                if player == self.console_player {
+                    self.talked_to_self += 1;
+
+                    let level = TALK_TO_SELF_TRIES.iter().filter(|&&tries| self.talked_to_self >= tries).count();
+                    self.post_message(TALK_TO_SELF[level]);
+                    false
+                } else if self.players_in_game[player] {
+                    self.chat = Some(ChatInput { destination: Some(player), text: String::new() });
+                    true
+                } else {
+                    false
+                }
+            },
+            _ => false
+        }
+    }
+
+    // HU_Drawer, in 320x200 coordinates. The title only shows on the automap.
+    pub fn draw(&self, target: &mut dyn DrawTarget, show_title: bool) {
+        let Some(font) = &self.font else {
+            return;
+        };
+
+        if self.message_on {
+            font.draw_text(target, MESSAGE_X, MESSAGE_Y, &self.message);
+        }
+
+        if let Some(chat) = &self.chat {
+            let y = MESSAGE_Y + font.height() + 1;
+            let x = font.draw_text(target, MESSAGE_X, y, &chat.text);
+
+            if let Some(cursor) = font.glyph('_').filter(|cursor| x + cursor.width as i32 <= BASE_WIDTH as i32) {
+                target.draw_patch(x, y, cursor);
+            }
+        }
+
+        if show_title {
+            font.draw_text(target, TITLE_X, TITLE_BOTTOM - font.height(), &self.title);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The message on screen after a tic, if any.
+    fn shown(hud: &mut Hud) -> Option<String> {
+        hud.tick();
+        hud.message_on.then(|| hud.message.clone())
+    }
+
+    fn ticks(hud: &mut Hud, tics: i32) {
+        for _ in 0..tics {
+            hud.tick();
+        }
+    }
+
+    fn press(hud: &mut Hud, keys: &[Key]) {
+        for &key in keys {
+            hud.responder(Event::KeyDown(key));
+        }
+    }
+
+    #[test]
+    fn messages_replace_each_other_and_time_out() {
+        let mut hud = Hud::new();
+
+        hud.post_message("Picked up a clip.");
+        assert_eq!(shown(&mut hud).as_deref(), Some("Picked up a clip."));
+
+        hud.post_message("Picked up a stimpack.");
+        assert_eq!(shown(&mut hud).as_deref(), Some("Picked up a stimpack."));
+
+        ticks(&mut hud, MESSAGE_TICS - 1);
+        assert_eq!(shown(&mut hud), None);
+    }
+
+    #[test]
+    fn chat_holds_off_pickups_until_it_times_out() {
+        let mut hud = Hud::new();
+
+        hud.receive_chat(1, None, "Come here!");
+        hud.post_message("Picked up a clip.");
+
+        for _ in 0..MESSAGE_TICS - 1 {
+            assert_eq!(shown(&mut hud).as_deref(), Some("Indigo: Come here!"));
+        }
+
+        assert_eq!(shown(&mut hud).as_deref(), Some("Picked up a clip."));
+    }
+
+    #[test]
+    fn forced_messages_show_with_messages_off_and_hold() {
+        let mut hud = Hud::new();
+        hud.set_show_messages(false);
+
+        hud.force_message("Messages OFF");
+        assert_eq!(shown(&mut hud).as_deref(), Some("Messages OFF"));
+
+        hud.set_show_messages(true);
+        hud.post_message("Picked up a clip.");
+        ticks(&mut hud, MESSAGE_TICS - 2);
+        assert_eq!(shown(&mut hud).as_deref(), Some("Messages OFF"));
+        assert_eq!(shown(&mut hud).as_deref(), Some("Picked up a clip."));
+    }
+
+    #[test]
+    fn messages_wait_while_they_are_off() {
+        let mut hud = Hud::new();
+        hud.set_show_messages(false);
+
+        hud.post_message("Picked up a clip.");
+        ticks(&mut hud, 2 * MESSAGE_TICS);
+        assert_eq!(shown(&mut hud), None);
+
+        hud.set_show_messages(true);
+        assert_eq!(shown(&mut hud).as_deref(), Some("Picked up a clip."));
+    }
+
+    #[test]
+    fn talking_to_yourself_gets_worse() {
+        let mut hud = Hud::new();
+        hud.set_players(0, [true; MAX_PLAYERS]);
+
+        let mut said = Vec::new();
+
+        for _ in 0..32 {
+            press(&mut hud, &[Key::Char('g')]);
+            said.push(shown(&mut hud).unwrap());
+        }
+
+        assert!(!hud.is_chatting());
+        assert_eq!(said[..2], [TALK_TO_SELF[0]; 2]);
+        assert_eq!(said[2..5], [TALK_TO_SELF[1]; 3]);
+        assert_eq!(said[5..8], [TALK_TO_SELF[2]; 3]);
+        assert_eq!(said[8..31], [TALK_TO_SELF[3]; 23]);
+        assert_eq!(said[31], TALK_TO_SELF[4]);
+    }
+
+    #[test]
+    fn alt_and_a_digit_send_a_macro() {
+        let mut hud = Hud::new();
+        hud.set_players(0, [true, true, true, false]);
+
+        press(&mut hud, &[Key::Char('t'), Key::Alt, Key::Char('4')]);
+        assert_eq!(hud.take_chat(), Some(ChatMessage { destination: None, text: "Help!".to_string() }));
+        assert!(!hud.is_chatting());
+        assert_eq!(shown(&mut hud).as_deref(), Some("Help!"));
+
+        // Only to the players who are there.
+        press(&mut hud, &[Key::Char('r')]);
+        assert!(!hud.is_chatting());
+
+        press(&mut hud, &[Key::Char('i'), Key::Char('0')]);
+        assert_eq!(hud.take_chat(), Some(ChatMessage { destination: Some(1), text: "No".to_string() }));
+    }
+
+    #[test]
+    fn chat_lines_are_capped() {
+        let mut hud = Hud::new();
+        hud.set_players(0, [true, true, false, false]);
+
+        press(&mut hud, &[Key::Char('t')]);
+        press(&mut hud, &[Key::Char('a'); 100]);
+        press(&mut hud, &[Key::Enter]);
+
+        assert_eq!(hud.take_chat(), Some(ChatMessage { destination: None, text: "A".repeat(MAX_LINE_LENGTH) }));
+        assert_eq!(hud.take_chat(), None);
+    }
+}
@@ -0,0 +1,98 @@
+// HU_TITLE and HU_TITLE2: the names shown on the automap, from d_englsh.h.
+const EPISODE_TITLES: [[&str; 9]; 4] = [
+    [
+        "E1M1: Hangar",
+        "E1M2: Nuclear Plant",
+        "E1M3: Toxin Refinery",
+        "E1M4: Command Control",
+        "E1M5: Phobos Lab",
+        "E1M6: Central Processing",
+        "E1M7: Computer Station",
+        "E1M8: Phobos Anomaly",
+        "E1M9: Military Base"
+    ],
+    [
+        "E2M1: Deimos Anomaly",
+        "E2M2: Containment Area",
+        "E2M3: Refinery",
+        "E2M4: Deimos Lab",
+        "E2M5: Command Center",
+        "E2M6: Halls of the Damned",
+        "E2M7: Spawning Vats",
+        "E2M8: Tower of Babel",
+        "E2M9: Fortress of Mystery"
+    ],
+    [
+        "E3M1: Hell Keep",
+        "E3M2: Slough of Despair",
+        "E3M3: Pandemonium",
+        "E3M4: House of Pain",
+        "E3M5: Unholy Cathedral",
+        "E3M6: Mt. Erebus",
+        "E3M7: Limbo",
+        "E3M8: Dis",
+        "E3M9: Warrens"
+    ],
+    [
+        "E4M1: Hell Beneath",
+        "E4M2: Perfect Hatred",
+        "E4M3: Sever The Wicked",
+        "E4M4: Unruly Evil",
+        "E4M5: They Will Repent",
+        "E4M6: Against Thee Wickedly",
+        "E4M7: And Hell Followed",
+        "E4M8: Unto The Cruel",
+        "E4M9: Fear"
+    ]
+];
+
+const MAP_TITLES: [&str; 32] = [
+    "level 1: entryway",
+    "level 2: underhalls",
+    "level 3: the gantlet",
+    "level 4: the focus",
+    "level 5: the waste tunnels",
+    "level 6: the crusher",
+    "level 7: dead simple",
+    "level 8: tricks and traps",
+    "level 9: the pit",
+    "level 10: refueling base",
+    "level 11: 'o' of destruction!",
+    "level 12: the factory",
+    "level 13: downtown",
+    "level 14: the inmost dens",
+    "level 15: industrial zone",
+    "level 16: suburbs",
+    "level 17: tenements",
+    "level 18: the courtyard",
+    "level 19: the citadel",
+    "level 20: gotcha!",
+    "level 21: nirvana",
+    "level 22: the catacombs",
+    "level 23: barrels o' fun",
+    "level 24: the chasm",
+    "level 25: bloodfalls",
+    "level 26: the abandoned mines",
+    "level 27: monster condo",
+    "level 28: the spirit world",
+    "level 29: the living end",
+    "level 30: icon of sin",
+    "level 31: wolfenstein",
+    "level 32: grosse"
+];
+
+// Maps outside the IWADs' numbering have no title.
+pub fn map_title(map_name: &str) -> Option<&'static str> {
+    let name = map_name.to_uppercase();
+
+    if let Some(number) = name.strip_prefix("MAP").and_then(|number| number.parse::<usize>().ok()) {
+        return MAP_TITLES.get(number.checked_sub(1)?).copied();
+    }
+
+    match name.as_bytes() {
+        [b'E', episode @ b'1'..=b'4', b'M', map @ b'1'..=b'9'] => {
+            Some(EPISODE_TITLES[(episode - b'1') as usize][(map - b'1') as usize])
+        },
+        _ => None
+    }
+}
@@ -0,0 +1,61 @@
+// What the engine responds to, like vanilla's event_t. Printable keys come through as the
+// character on the key, unshifted, the way vanilla's key codes are plain ASCII.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Escape,
+    Backspace,
+    Tab,
+    Up,
+    Down,
+    Left,
+    Right,
+    Shift,
+    Alt,
+    Ctrl,
+    Function(u8)
+}
+
+// Mouse and gamepad buttons, as a mask: the first is bit 0.
+pub const BUTTON_1: u8 = 1;
+pub const BUTTON_2: u8 = 2;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Event {
+    KeyDown(Key),
+    KeyUp(Key),
+    // The buttons held and how far the mouse moved, in vanilla's units: four per pixel,
+    // with up positive.
+    Mouse { buttons: u8, x: i32, y: i32 },
+    // The buttons held and which way the stick points on each axis: -1 for left or up, 0 or 1.
+    Joystick { buttons: u8, x: i32, y: i32 }
+}
+
+// shiftxform, for a US keyboard.
+pub fn shift_char(c: char) -> char {
+    match c {
+        '1' => '!',
+        '2' => '@',
+        '3' => '#',
+        '4' => '$',
+        '5' => '%',
+        '6' => '^',
+        '7' => '&',
+        '8' => '*',
+        '9' => '(',
+        '0' => ')',
+        '-' => '_',
+        '=' => '+',
+        '[' => '{',
+        ']' => '}',
+        ';' => ':',
+        '\'' => '"',
+        ',' => '<',
+        '.' => '>',
+        '/' => '?',
+        '\\' => '|',
+        '`' => '~',
+        _ => c.to_ascii_uppercase()
+    }
+}
@@ -4,7 +4,58 @@ mod engine;
 pub use engine::DoomEngine;
 
 mod wad;
+pub use wad::Loader;
+
 mod map;
+pub use map::{GameMap, LineKind};
+
+mod math;
+pub use math::{Bam, Fixed, Random};
 
 mod player;
-pub use player::Player;
\ No newline at end of file
+pub use player::{Card, Player, Power, Weapon};
+
+mod automap;
+pub use automap::Automap;
+
+mod backend;
+pub use backend::{AspectRatio, Backend, DrawTarget, FrameBuffer, SdlBackend, VideoConfig, Viewport};
+
+mod render;
+pub use render::{RenderData, RenderStats, Renderer, TicSnapshot, ViewBorder};
+
+mod status_bar;
+pub use status_bar::StatusBar;
+
+mod hud;
+pub use hud::{map_title, ChatMessage, Hud, HudFont, MAX_PLAYERS};
+
+mod input;
+pub use input::{Event, Key, BUTTON_1, BUTTON_2};
+
+mod menu;
+pub use menu::{Menu, MenuAction, MenuContext};
+
+mod settings;
+pub use settings::Settings;
+
+mod savegame;
+pub use savegame::{read_save_descriptions, save_path, SaveGame, SAVE_SLOTS};
+
+mod wipe;
+pub use wipe::Melt;
+
+mod screenshot;
+pub use screenshot::{encode_pcx, encode_png, encode_screenshot, next_screenshot_path, save_screenshot, ScreenshotFormat};
+
+mod lint;
+pub use lint::{lint_map, Issue, IssueKind, MapObject, Severity};
+
+mod things;
+pub use things::{thing_info, thing_sprite, AmmoType, ThingInfo, ThingKind, ThingSprite};
+
+mod stats;
+pub use stats::{map_stats, thing_stats, AmmoTotals, GameMode, MapStats, Skill, SkillStats, ThingStats};
+
+mod export;
+pub use export::{automap_svg, map_mesh, mesh_gltf, mesh_mtl, mesh_obj, Mesh, MeshGroup, SvgOptions};
\ No newline at end of file
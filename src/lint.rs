@@ -0,0 +1,623 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use serde::Serialize;
+use crate::map::GameMap;
+use crate::wad::{Linedef, Sidedef, Vertex};
+
+const SUB_SECTOR_IDENTIFIER: u16 = 0x8000;
+const SKY_FLAT: &str = "F_SKY1";
+
+const PLAYER_START_TYPES: [i16; 4] = [1, 2, 3, 4];
+const DEATHMATCH_START_TYPE: i16 = 11;
+const MIN_DEATHMATCH_STARTS: usize = 4;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    VertexOutOfRange,
+    SidedefOutOfRange,
+    SectorOutOfRange,
+    LinedefOutOfRange,
+    SegOutOfRange,
+    ChildOutOfRange,
+    ZeroLengthLinedef,
+    OverlappingLinedefs,
+    CrossingLinedefs,
+    UnclosedSector,
+    MissingTwoSidedFlag,
+    TwoSidedFlagWithoutBackSide,
+    MissingTexture,
+    UnknownTexture,
+    MissingPlayerStart,
+    NoVertexes
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", content = "id", rename_all = "snake_case")]
+pub enum MapObject {
+    Map,
+    Linedef(usize),
+    Sidedef(usize),
+    Sector(usize),
+    Seg(usize),
+    SubSector(usize),
+    Node(usize)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Issue {
+    pub severity: Severity,
+    pub kind: IssueKind,
+    pub object: MapObject,
+    pub message: String
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error")
+        }
+    }
+}
+
+impl fmt::Display for MapObject {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapObject::Map => write!(f, "map"),
+            MapObject::Linedef(id) => write!(f, "linedef {}", id),
+            MapObject::Sidedef(id) => write!(f, "sidedef {}", id),
+            MapObject::Sector(id) => write!(f, "sector {}", id),
+            MapObject::Seg(id) => write!(f, "seg {}", id),
+            MapObject::SubSector(id) => write!(f, "subsector {}", id),
+            MapObject::Node(id) => write!(f, "node {}", id)
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.severity, self.object, self.message)
+    }
+}
+
+pub fn lint_map(map: &GameMap, texture_names: Option<&[String]>) -> Vec<Issue> {
+    let mut linter = Linter {
+        map,
+        texture_names: texture_names.map(|names| names.iter().map(|name| name.to_uppercase()).collect()),
+        issues: Vec::new()
+    };
+
+    linter.check_vertexes();
+    linter.check_linedefs();
+    linter.check_sidedefs();
+    linter.check_segs();
+    linter.check_sub_sectors();
+    linter.check_nodes();
+    linter.check_line_intersections();
+    linter.check_unclosed_sectors();
+    linter.check_textures();
+    linter.check_player_starts();
+
+    linter.issues
+}
+
+struct Linter<'a> {
+    map: &'a GameMap,
+    texture_names: Option<HashSet<String>>,
+    issues: Vec<Issue>
+}
+
+impl<'a> Linter<'a> {
+    fn report(&mut self, severity: Severity, kind: IssueKind, object: MapObject, message: String) {
+        self.issues.push(Issue { severity, kind, object, message });
+    }
+
+    fn vertex(&self, vertex_id: usize) -> Option<Vertex> {
+        self.map.vertexes.get(vertex_id).copied()
+    }
+
+    fn sidedef(&self, sidedef_id: u16) -> Option<&'a Sidedef> {
+        self.map.sidedefs.get(sidedef_id as usize)
+    }
+
+    fn is_valid_linedef(&self, linedef: &Linedef) -> bool {
+        self.vertex(linedef.start_vertex_id as usize).is_some()
+            && self.vertex(linedef.end_vertex_id as usize).is_some()
+    }
+
+    fn linedef_sector(&self, sidedef_id: u16) -> Option<usize> {
+        self.sidedef(sidedef_id)
+            .map(|sidedef| sidedef.sector_id as usize)
+            .filter(|&sector_id| sector_id < self.map.sectors.len())
+    }
+
+    fn check_vertexes(&mut self) {
+        if self.map.vertexes.is_empty() {
+            self.report(Severity::Error, IssueKind::NoVertexes, MapObject::Map, "has no vertexes".to_string());
+        }
+    }
+
+    fn check_linedefs(&mut self) {
+        let map = self.map;
+
+        for (linedef_id, linedef) in map.linedefs.iter().enumerate() {
+            let object = MapObject::Linedef(linedef_id);
+
+            for vertex_id in [linedef.start_vertex_id, linedef.end_vertex_id] {
+                if vertex_id as usize >= map.vertexes.len() {
+                    self.report(Severity::Error, IssueKind::VertexOutOfRange, object, format!(
+                        "references vertex {} but the map has {} vertexes", vertex_id, map.vertexes.len()
+                    ));
+                }
+            }
+
+            if self.sidedef(linedef.front_sidedef_id).is_none() {
+                self.report(Severity::Error, IssueKind::SidedefOutOfRange, object, format!(
+                    "front sidedef {} does not exist (map has {} sidedefs)", linedef.front_sidedef_id, map.sidedefs.len()
+                ));
+            }
+
+            if linedef.has_back_sidedef() && self.sidedef(linedef.back_sidedef_id).is_none() {
+                self.report(Severity::Error, IssueKind::SidedefOutOfRange, object, format!(
+                    "back sidedef {} does not exist (map has {} sidedefs)", linedef.back_sidedef_id, map.sidedefs.len()
+                ));
+            }
+
+            if let (Some(start), Some(end)) = (
+                self.vertex(linedef.start_vertex_id as usize),
+                self.vertex(linedef.end_vertex_id as usize)
+            ) {
+                if start.x == end.x && start.y == end.y {
+                    self.report(Severity::Error, IssueKind::ZeroLengthLinedef, object, format!(
+                        "has zero length at ({}, {})", start.x, start.y
+                    ));
+                }
+            }
+
+            let is_two_sided = linedef.has_flag(Linedef::TWO_SIDED);
+
+            if linedef.has_back_sidedef() && !is_two_sided {
+                self.report(Severity::Warning, IssueKind::MissingTwoSidedFlag, object,
+                    "has a back sidedef but is missing the TWOSIDED flag".to_string()
+                );
+            } else if !linedef.has_back_sidedef() && is_two_sided {
+                self.report(Severity::Error, IssueKind::TwoSidedFlagWithoutBackSide, object,
+                    "has the TWOSIDED flag but no back sidedef".to_string()
+                );
+            }
+        }
+    }
+
+    fn check_sidedefs(&mut self) {
+        let map = self.map;
+
+        for (sidedef_id, sidedef) in map.sidedefs.iter().enumerate() {
+            if sidedef.sector_id as usize >= map.sectors.len() {
+                self.report(Severity::Error, IssueKind::SectorOutOfRange, MapObject::Sidedef(sidedef_id), format!(
+                    "references sector {} but the map has {} sectors", sidedef.sector_id, map.sectors.len()
+                ));
+            }
+        }
+    }
+
+    fn check_segs(&mut self) {
+        let map = self.map;
+
+        for (seg_id, seg) in map.segs.iter().enumerate() {
+            let object = MapObject::Seg(seg_id);
+
+            for vertex_id in [seg.start_vertex_id, seg.end_vertex_id] {
+                if self.vertex(vertex_id as u16 as usize).is_none() {
+                    self.report(Severity::Error, IssueKind::VertexOutOfRange, object, format!(
+                        "references vertex {} but the map has {} vertexes", vertex_id as u16, map.vertexes.len()
+                    ));
+                }
+            }
+
+            if seg.linedef_id as u16 as usize >= map.linedefs.len() {
+                self.report(Severity::Error, IssueKind::LinedefOutOfRange, object, format!(
+                    "references linedef {} but the map has {} linedefs", seg.linedef_id as u16, map.linedefs.len()
+                ));
+            }
+        }
+    }
+
+    fn check_sub_sectors(&mut self) {
+        let map = self.map;
+
+        for (sub_sector_id, sub_sector) in map.ssectors.iter().enumerate() {
+            let first = sub_sector.first_seg_id as u16 as usize;
+            let count = sub_sector.seg_count as u16 as usize;
+
+            if count == 0 || first + count > map.segs.len() {
+                self.report(Severity::Error, IssueKind::SegOutOfRange, MapObject::SubSector(sub_sector_id), format!(
+                    "references segs {}..{} but the map has {} segs", first, first + count, map.segs.len()
+                ));
+            }
+        }
+    }
+
+    fn check_nodes(&mut self) {
+        let map = self.map;
+
+        for (node_id, node) in map.nodes.iter().enumerate() {
+            for child_id in [node.right_child_id, node.left_child_id] {
+                let (kind, index, count) = if child_id & SUB_SECTOR_IDENTIFIER != 0 {
+                    ("subsector", (child_id & !SUB_SECTOR_IDENTIFIER) as usize, map.ssectors.len())
+                } else {
+                    ("node", child_id as usize, map.nodes.len())
+                };
+
+                if index >= count {
+                    self.report(Severity::Error, IssueKind::ChildOutOfRange, MapObject::Node(node_id), format!(
+                        "references {} {} but the map has {}", kind, index, count
+                    ));
+                }
+            }
+        }
+    }
+
+    fn check_line_intersections(&mut self) {
+        let map = self.map;
+
+        let mut lines: Vec<(usize, Vertex, Vertex)> = map.linedefs.iter()
+            .enumerate()
+            .filter(|(_, linedef)| self.is_valid_linedef(linedef))
+            .map(|(id, linedef)| (
+                id,
+                map.vertexes[linedef.start_vertex_id as usize],
+                map.vertexes[linedef.end_vertex_id as usize]
+            ))
+            .filter(|(_, start, end)| start.x != end.x || start.y != end.y)
+            .collect();
+
+        lines.sort_by_key(|(_, start, end)| start.x.min(end.x));
+
+        for i in 0..lines.len() {
+            let (id_a, a1, a2) = lines[i];
+            let max_x = a1.x.max(a2.x);
+
+            for &(id_b, b1, b2) in &lines[i + 1..] {
+                if b1.x.min(b2.x) > max_x {
+                    break;
+                }
+
+                if a1.y.max(a2.y) < b1.y.min(b2.y) || b1.y.max(b2.y) < a1.y.min(a2.y) {
+                    continue;
+                }
+
+                let (first, second) = (id_a.min(id_b), id_a.max(id_b));
+
+                match intersect(a1, a2, b1, b2) {
+                    Intersection::Overlapping => self.report(
+                        Severity::Error, IssueKind::OverlappingLinedefs, MapObject::Linedef(first),
+                        format!("overlaps linedef {}", second)
+                    ),
+                    Intersection::Crossing => self.report(
+                        Severity::Error, IssueKind::CrossingLinedefs, MapObject::Linedef(first),
+                        format!("crosses linedef {}", second)
+                    ),
+                    Intersection::None => {}
+                }
+            }
+        }
+    }
+
+    fn check_unclosed_sectors(&mut self) {
+        let map = self.map;
+        // Only the vertexes a sector touches, so big maps don't need sectors times vertexes.
+        let mut degrees: Vec<HashMap<u16, u32>> = vec![HashMap::new(); map.sectors.len()];
+
+        for linedef in map.linedefs.iter().filter(|linedef| self.is_valid_linedef(linedef)) {
+            let mut sides = vec![linedef.front_sidedef_id];
+
+            if linedef.has_back_sidedef() {
+                sides.push(linedef.back_sidedef_id);
+            }
+
+            for sector_id in sides.into_iter().filter_map(|sidedef_id| self.linedef_sector(sidedef_id)) {
+                *degrees[sector_id].entry(linedef.start_vertex_id).or_default() += 1;
+                *degrees[sector_id].entry(linedef.end_vertex_id).or_default() += 1;
+            }
+        }
+
+        for (sector_id, sector_degrees) in degrees.iter().enumerate() {
+            let mut open_vertexes: Vec<usize> = sector_degrees.iter()
+                .filter(|(_, &degree)| degree % 2 != 0)
+                .map(|(&vertex_id, _)| vertex_id as usize)
+                .collect();
+
+            open_vertexes.sort_unstable();
+
+            if let Some(&vertex_id) = open_vertexes.first() {
+                let vertex = map.vertexes[vertex_id];
+
+                self.report(Severity::Error, IssueKind::UnclosedSector, MapObject::Sector(sector_id), format!(
+                    "is not closed ({} dangling vertexes, first is vertex {} at ({}, {}))",
+                    open_vertexes.len(), vertex_id, vertex.x, vertex.y
+                ));
+            }
+        }
+    }
+
+    fn check_textures(&mut self) {
+        let map = self.map;
+
+        for (linedef_id, linedef) in map.linedefs.iter().enumerate() {
+            let Some(front) = self.sidedef(linedef.front_sidedef_id) else {
+                continue;
+            };
+
+            if !linedef.has_back_sidedef() {
+                self.require_texture(linedef_id, linedef.front_sidedef_id, "middle", &front.middle_texture);
+                continue;
+            }
+
+            let Some(back) = self.sidedef(linedef.back_sidedef_id) else {
+                continue;
+            };
+
+            let (Some(front_sector), Some(back_sector)) = (
+                map.sectors.get(front.sector_id as usize),
+                map.sectors.get(back.sector_id as usize)
+            ) else {
+                continue;
+            };
+
+            let both_sky = front_sector.ceiling_texture == SKY_FLAT && back_sector.ceiling_texture == SKY_FLAT;
+
+            if back_sector.ceiling_height < front_sector.ceiling_height && !both_sky {
+                self.require_texture(linedef_id, linedef.front_sidedef_id, "upper", &front.upper_texture);
+            }
+
+            if front_sector.ceiling_height < back_sector.ceiling_height && !both_sky {
+                self.require_texture(linedef_id, linedef.back_sidedef_id, "upper", &back.upper_texture);
+            }
+
+            if back_sector.floor_height > front_sector.floor_height {
+                self.require_texture(linedef_id, linedef.front_sidedef_id, "lower", &front.lower_texture);
+            }
+
+            if front_sector.floor_height > back_sector.floor_height {
+                self.require_texture(linedef_id, linedef.back_sidedef_id, "lower", &back.lower_texture);
+            }
+        }
+
+        let Some(texture_names) = &self.texture_names else {
+            return;
+        };
+
+        let mut unknown: Vec<(usize, String)> = Vec::new();
+
+        for (sidedef_id, sidedef) in map.sidedefs.iter().enumerate() {
+            for texture in [&sidedef.upper_texture, &sidedef.lower_texture, &sidedef.middle_texture] {
+                if texture != Sidedef::NO_TEXTURE && !texture_names.contains(&texture.to_uppercase()) {
+                    unknown.push((sidedef_id, texture.clone()));
+                }
+            }
+        }
+
+        for (sidedef_id, texture) in unknown {
+            self.report(Severity::Error, IssueKind::UnknownTexture, MapObject::Sidedef(sidedef_id), format!(
+                "uses texture {} which is not defined in TEXTURE1/TEXTURE2", texture
+            ));
+        }
+    }
+
+    fn require_texture(&mut self, linedef_id: usize, sidedef_id: u16, part: &str, texture: &str) {
+        if texture == Sidedef::NO_TEXTURE {
+            self.report(Severity::Warning, IssueKind::MissingTexture, MapObject::Linedef(linedef_id), format!(
+                "sidedef {} is missing its {} texture", sidedef_id, part
+            ));
+        }
+    }
+
+    fn check_player_starts(&mut self) {
+        let things = &self.map.things;
+
+        for player_type in PLAYER_START_TYPES {
+            if !things.iter().any(|thing| thing.ed_type == player_type) {
+                let severity = if player_type == 1 { Severity::Error } else { Severity::Warning };
+
+                self.report(severity, IssueKind::MissingPlayerStart, MapObject::Map, format!(
+                    "has no player {} start", player_type
+                ));
+            }
+        }
+
+        let deathmatch_starts = things.iter().filter(|thing| thing.ed_type == DEATHMATCH_START_TYPE).count();
+
+        if deathmatch_starts < MIN_DEATHMATCH_STARTS {
+            self.report(Severity::Warning, IssueKind::MissingPlayerStart, MapObject::Map, format!(
+                "has {} deathmatch starts, {} required", deathmatch_starts, MIN_DEATHMATCH_STARTS
+            ));
+        }
+    }
+}
+
+enum Intersection {
+    None,
+    Crossing,
+    Overlapping
+}
+
+fn orientation(a: Vertex, b: Vertex, c: Vertex) -> i64 {
+    let cross = (b.x as i64 - a.x as i64) * (c.y as i64 - a.y as i64)
+        - (b.y as i64 - a.y as i64) * (c.x as i64 - a.x as i64);
+
+    cross.signum()
+}
+
+fn intersect(a1: Vertex, a2: Vertex, b1: Vertex, b2: Vertex) -> Intersection {
+    let d1 = orientation(b1, b2, a1);
+    let d2 = orientation(b1, b2, a2);
+    let d3 = orientation(a1, a2, b1);
+    let d4 = orientation(a1, a2, b2);
+
+    if d1 == 0 && d2 == 0 && d3 == 0 && d4 == 0 {
+        let project = |v: Vertex| if a1.x != a2.x { v.x as i32 } else { v.y as i32 };
+
+        let (a_min, a_max) = (project(a1).min(project(a2)), project(a1).max(project(a2)));
+        let (b_min, b_max) = (project(b1).min(project(b2)), project(b1).max(project(b2)));
+
+        return if a_min.max(b_min) < a_max.min(b_max) {
+            Intersection::Overlapping
+        } else {
+            Intersection::None
+        };
+    }
+
+    if d1 * d2 < 0 && d3 * d4 < 0 {
+        Intersection::Crossing
+    } else {
+        Intersection::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad::{Point, Sector, Seg, SubSector, Thing};
+
+    fn vertex(x: i16, y: i16) -> Vertex {
+        Vertex { x, y }
+    }
+
+    fn linedef(start_vertex_id: u16, end_vertex_id: u16, front_sidedef_id: u16) -> Linedef {
+        Linedef {
+            start_vertex_id,
+            end_vertex_id,
+            flags: Linedef::BLOCKING,
+            line_type: 0,
+            sector_tag: 0,
+            front_sidedef_id,
+            back_sidedef_id: Linedef::NO_SIDEDEF
+        }
+    }
+
+    fn sidedef() -> Sidedef {
+        Sidedef {
+            x_offset: 0,
+            y_offset: 0,
+            upper_texture: Sidedef::NO_TEXTURE.to_string(),
+            lower_texture: Sidedef::NO_TEXTURE.to_string(),
+            middle_texture: "STARTAN3".to_string(),
+            sector_id: 0
+        }
+    }
+
+    // A closed square room with every player start and enough deathmatch starts.
+    fn room() -> GameMap {
+        let mut map = GameMap::with_name("TEST");
+
+        map.vertexes = vec![vertex(0, 0), vertex(0, 256), vertex(256, 256), vertex(256, 0)];
+        map.linedefs = (0..4).map(|i| linedef(i, (i + 1) % 4, i)).collect();
+        map.sidedefs = vec![sidedef(); 4];
+        map.sectors = vec![Sector {
+            floor_height: 0,
+            ceiling_height: 128,
+            floor_texture: "FLOOR4_8".to_string(),
+            ceiling_texture: "CEIL3_5".to_string(),
+            light_level: 160,
+            special: 0,
+            tag: 0
+        }];
+        map.segs = (0..4)
+            .map(|i| Seg { start_vertex_id: i, end_vertex_id: (i + 1) % 4, angle: 0, linedef_id: i, direction: 0, offset: 0 })
+            .collect();
+        map.ssectors = vec![SubSector { seg_count: 4, first_seg_id: 0 }];
+
+        let starts = [1, 2, 3, 4].into_iter().chain([DEATHMATCH_START_TYPE; MIN_DEATHMATCH_STARTS]);
+        map.things = starts
+            .map(|ed_type| Thing { position: Point { x: 128, y: 128 }, angle: 0, ed_type, flags: 7 })
+            .collect();
+
+        map
+    }
+
+    fn kinds(map: &GameMap) -> Vec<IssueKind> {
+        lint_map(map, None).into_iter().map(|issue| issue.kind).collect()
+    }
+
+    #[test]
+    fn intersect_tells_crossing_from_touching_and_overlapping() {
+        let crossing = intersect(vertex(0, 0), vertex(10, 10), vertex(0, 10), vertex(10, 0));
+        let touching = intersect(vertex(0, 0), vertex(10, 0), vertex(10, 0), vertex(10, 10));
+        let overlapping = intersect(vertex(0, 0), vertex(10, 0), vertex(5, 0), vertex(20, 0));
+        let end_to_end = intersect(vertex(0, 0), vertex(10, 0), vertex(10, 0), vertex(20, 0));
+        let parallel = intersect(vertex(0, 0), vertex(10, 0), vertex(0, 5), vertex(10, 5));
+
+        assert!(matches!(crossing, Intersection::Crossing));
+        assert!(matches!(touching, Intersection::None));
+        assert!(matches!(overlapping, Intersection::Overlapping));
+        assert!(matches!(end_to_end, Intersection::None));
+        assert!(matches!(parallel, Intersection::None));
+    }
+
+    #[test]
+    fn closed_room_is_clean() {
+        assert!(kinds(&room()).is_empty());
+    }
+
+    #[test]
+    fn missing_wall_leaves_the_sector_open() {
+        let mut map = room();
+        map.linedefs.pop();
+
+        let issues = lint_map(&map, None);
+        let unclosed: Vec<_> = issues.iter().filter(|issue| issue.kind == IssueKind::UnclosedSector).collect();
+
+        assert_eq!(unclosed.len(), 1);
+        assert_eq!(unclosed[0].object, MapObject::Sector(0));
+        assert!(unclosed[0].message.contains("2 dangling vertexes, first is vertex 0"));
+    }
+
+    #[test]
+    fn crossing_and_broken_references_are_errors() {
+        let mut map = room();
+        map.linedefs.push(linedef(0, 2, 0));
+        map.linedefs.push(linedef(1, 3, 9));
+        map.things.retain(|thing| thing.ed_type != 1);
+
+        let kinds = kinds(&map);
+
+        assert!(kinds.contains(&IssueKind::CrossingLinedefs));
+        assert!(kinds.contains(&IssueKind::SidedefOutOfRange));
+        assert!(kinds.contains(&IssueKind::MissingPlayerStart));
+    }
+
+    #[test]
+    fn maps_without_vertexes_are_reported() {
+        let mut map = room();
+        map.vertexes.clear();
+        map.calc_map_bounds();
+
+        let kinds = kinds(&map);
+
+        assert_eq!(kinds[0], IssueKind::NoVertexes);
+        assert!(kinds.contains(&IssueKind::VertexOutOfRange));
+    }
+
+    #[test]
+    fn unknown_textures_are_reported_when_names_are_given() {
+        let names = ["STARTAN3".to_string()];
+        let mut map = room();
+
+        assert!(lint_map(&map, Some(&names)).is_empty());
+
+        map.sidedefs[2].middle_texture = "NOPE".to_string();
+        let issues = lint_map(&map, Some(&names));
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, IssueKind::UnknownTexture);
+        assert_eq!(issues[0].object, MapObject::Sidedef(2));
+    }
+}
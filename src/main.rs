@@ -1,30 +1,193 @@
 #![allow(unused, dead_code)]
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
+use sdl2::controller::{Axis, Button};
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
-use anyhow::Result;
+use sdl2::mouse::MouseButton;
+use anyhow::{anyhow, bail, Result};
 
-use doom::DoomEngine;
+use doom::{AspectRatio, DoomEngine, Event as GameEvent, Key, ScreenshotFormat, VideoConfig, BUTTON_1, BUTTON_2};
 
-const SCREEN_WIDTH: u32 = 1600;
 const SCREEN_HEIGHT: u32 = 1000;
 
+// M_ScreenShot's message.
+const SCREEN_SHOT: &str = "screen shot";
+
+// How far a stick has to lean before it counts as pushed.
+const STICK_DEAD_ZONE: i16 = 8000;
+
+struct Options {
+    video: VideoConfig,
+    screenshot_format: ScreenshotFormat
+}
+
+fn options() -> Result<Options> {
+    let mut config = VideoConfig::default();
+    let mut screenshot_format = ScreenshotFormat::default();
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--scale" => {
+                config.scale = args.next()
+                    .and_then(|scale| scale.parse().ok())
+                    .ok_or_else(|| anyhow!("--scale takes a whole number"))?;
+            },
+            "--aspect" => {
+                config.aspect_ratio = args.next()
+                    .and_then(|name| AspectRatio::from_name(&name))
+                    .ok_or_else(|| anyhow!("--aspect takes 4:3, 16:9 or 21:9"))?;
+            },
+            "--no-aspect-correction" => config.aspect_correction = false,
+            "--threads" => {
+                config.render_threads = args.next()
+                    .and_then(|threads| threads.parse().ok())
+                    .ok_or_else(|| anyhow!("--threads takes a whole number"))?;
+            },
+            "--screenshot-format" => {
+                screenshot_format = args.next()
+                    .and_then(|name| ScreenshotFormat::from_name(&name))
+                    .ok_or_else(|| anyhow!("--screenshot-format takes png or pcx"))?;
+            },
+            _ => bail!(
+                "Usage: doom [--scale N] [--aspect 4:3|16:9|21:9] [--no-aspect-correction] [--threads N] [--screenshot-format png|pcx]"
+            )
+        }
+    }
+
+    Ok(Options { video: config, screenshot_format })
+}
+
+// The engine's name for an SDL key, if it cares about it.
+fn key(keycode: Keycode) -> Option<Key> {
+    let key = match keycode {
+        Keycode::Return | Keycode::KpEnter => Key::Enter,
+        Keycode::Escape => Key::Escape,
+        Keycode::Backspace => Key::Backspace,
+        Keycode::Tab => Key::Tab,
+        Keycode::Up => Key::Up,
+        Keycode::Down => Key::Down,
+        Keycode::Left => Key::Left,
+        Keycode::Right => Key::Right,
+        Keycode::LShift | Keycode::RShift => Key::Shift,
+        Keycode::LAlt | Keycode::RAlt => Key::Alt,
+        Keycode::LCtrl | Keycode::RCtrl => Key::Ctrl,
+        Keycode::F1 => Key::Function(1),
+        Keycode::F2 => Key::Function(2),
+        Keycode::F3 => Key::Function(3),
+        Keycode::F4 => Key::Function(4),
+        Keycode::F5 => Key::Function(5),
+        Keycode::F6 => Key::Function(6),
+        Keycode::F7 => Key::Function(7),
+        Keycode::F8 => Key::Function(8),
+        Keycode::F9 => Key::Function(9),
+        Keycode::F10 => Key::Function(10),
+        Keycode::F11 => Key::Function(11),
+        Keycode::F12 => Key::Function(12),
+        // Printable keys are their ASCII code, as in vanilla.
+        _ => match keycode as i32 {
+            code @ 0x20..=0x7e => Key::Char(code as u8 as char),
+            _ => return None
+        }
+    };
+
+    Some(key)
+}
+
+fn mouse_button(button: MouseButton) -> u8 {
+    match button {
+        MouseButton::Left => BUTTON_1,
+        MouseButton::Right => BUTTON_2,
+        MouseButton::Middle => 4,
+        _ => 0
+    }
+}
+
+fn stick_direction(value: i16) -> i32 {
+    if value < -STICK_DEAD_ZONE {
+        -1
+    } else if value > STICK_DEAD_ZONE {
+        1
+    } else {
+        0
+    }
+}
+
+// A gamepad read like vanilla's digital joystick: two buttons and a direction on each axis,
+// from the left stick or the d-pad.
+#[derive(Default)]
+struct Gamepad {
+    buttons: u8,
+    stick: (i32, i32),
+    dpad: (i32, i32)
+}
+
+impl Gamepad {
+    fn button(&mut self, button: Button, down: bool) {
+        let (mask, dpad) = match button {
+            Button::A => (BUTTON_1, None),
+            Button::B => (BUTTON_2, None),
+            Button::DPadLeft => (0, Some((-1, 0))),
+            Button::DPadRight => (0, Some((1, 0))),
+            Button::DPadUp => (0, Some((0, -1))),
+            Button::DPadDown => (0, Some((0, 1))),
+            _ => return
+        };
+
+        if down {
+            self.buttons |= mask;
+        } else {
+            self.buttons &= !mask;
+        }
+
+        match dpad {
+            Some((x, 0)) => self.dpad.0 = if down { x } else { 0 },
+            Some((0, y)) => self.dpad.1 = if down { y } else { 0 },
+            _ => {}
+        }
+    }
+
+    fn axis(&mut self, axis: Axis, value: i16) {
+        match axis {
+            Axis::LeftX => self.stick.0 = stick_direction(value),
+            Axis::LeftY => self.stick.1 = stick_direction(value),
+            _ => {}
+        }
+    }
+
+    fn event(&self) -> GameEvent {
+        let pick = |dpad: i32, stick: i32| if dpad != 0 { dpad } else { stick };
+        GameEvent::Joystick { buttons: self.buttons, x: pick(self.dpad.0, self.stick.0), y: pick(self.dpad.1, self.stick.1) }
+    }
+}
+
 fn main() -> Result<()> {
+    let options = options()?;
+    let config = options.video;
     let sdl_context = sdl2::init().unwrap();
     let video_subsystem = sdl_context.video().unwrap();
 
     let window = video_subsystem
-        .window("Doom", SCREEN_WIDTH, SCREEN_HEIGHT)
+        .window("Doom", SCREEN_HEIGHT * config.width() / config.display_height(), SCREEN_HEIGHT)
         .position_centered()
         .build()?;
 
-    let mut engine = DoomEngine::new(window)?;
+    let mut engine = DoomEngine::new(window, config)?;
 
     engine.init();
 
+    // The first gamepad plugged in, if there is one.
+    let controller_subsystem = sdl_context.game_controller().ok();
+    let _controller = controller_subsystem.as_ref().and_then(|subsystem| {
+        let count = subsystem.num_joysticks().ok()?;
+        (0..count).find(|&i| subsystem.is_game_controller(i)).and_then(|i| subsystem.open(i).ok())
+    });
+
     let mut event_pump = sdl_context.event_pump().unwrap();
+    let mut mouse_buttons = 0;
+    let mut gamepad = Gamepad::default();
 
     let mut last_frame = Instant::now();
 
@@ -33,22 +196,58 @@ fn main() -> Result<()> {
             match event {
                 Event::Quit { .. } => break 'main,
                 Event::KeyDown { keycode: Some(keycode), .. } => {
-                    match keycode {
-                        Keycode::Escape => break 'main,
-                        _ => {}
+                    if key(keycode).is_some_and(|key| engine.responder(GameEvent::KeyDown(key))) {
+                        continue;
                     }
+
+                    // M_ScreenShot, which says so on the HUD rather than the console.
+                    if keycode == Keycode::F12 {
+                        match engine.screenshot(Path::new("."), options.screenshot_format) {
+                            Ok(_) => engine.post_message(SCREEN_SHOT),
+                            Err(error) => engine.post_message(&format!("screen shot failed: {}", error))
+                        }
+                    }
+                },
+                Event::KeyUp { keycode: Some(keycode), .. } => {
+                    if let Some(key) = key(keycode) {
+                        engine.responder(GameEvent::KeyUp(key));
+                    }
+                },
+                // Vanilla's mouse units are four to a pixel, with up positive.
+                Event::MouseMotion { xrel, yrel, .. } => {
+                    engine.responder(GameEvent::Mouse { buttons: mouse_buttons, x: xrel << 2, y: -yrel << 2 });
+                },
+                Event::MouseButtonDown { mouse_btn, .. } => {
+                    mouse_buttons |= mouse_button(mouse_btn);
+                    engine.responder(GameEvent::Mouse { buttons: mouse_buttons, x: 0, y: 0 });
+                },
+                Event::MouseButtonUp { mouse_btn, .. } => {
+                    mouse_buttons &= !mouse_button(mouse_btn);
+                    engine.responder(GameEvent::Mouse { buttons: mouse_buttons, x: 0, y: 0 });
+                },
+                Event::ControllerButtonDown { button, .. } | Event::ControllerButtonUp { button, .. } => {
+                    gamepad.button(button, matches!(event, Event::ControllerButtonDown { .. }));
+                    engine.responder(gamepad.event());
+                },
+                Event::ControllerAxisMotion { axis, value, .. } => {
+                    gamepad.axis(axis, value);
+                    engine.responder(gamepad.event());
                 },
                 _ => {}
             }
         }
 
+        if engine.is_over() {
+            break;
+        }
+
         let now = Instant::now();
         let delta = now - last_frame;
         let delta_time = delta.as_secs_f64();
         last_frame = now;
 
         engine.update(delta_time);
-        engine.render();
+        engine.render()?;
     }
 
     Ok(())
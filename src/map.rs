@@ -1,14 +1,35 @@
 use std::time::Duration;
 use rand::{Rng, SeedableRng};
 use rand_pcg::Pcg32;
-use sdl2::gfx::primitives::DrawRenderer;
 use sdl2::pixels::Color;
-use sdl2::render::WindowCanvas;
-use sdl2::sys::{u_int16_t, Window};
 use crate::Player;
-use crate::wad::{BoundingBox, Linedef, Node, Point, Seg, SubSector, Thing, Vertex};
+use crate::math::Fixed;
+use crate::wad::{Linedef, Node, Point, Sector, Seg, Sidedef, SubSector, Thing, Vertex};
 
-const SUB_SECTOR_IDENTIFIER: u16 = 0x8000;
+pub(crate) const SUB_SECTOR_IDENTIFIER: u16 = 0x8000;
+
+// R_PointOnSide / R_PointOnSegSide: 0 for the front (right) side of the line, 1 for the back.
+pub fn point_on_line_side(x: Fixed, y: Fixed, line_x: Fixed, line_y: Fixed, line_dx: Fixed, line_dy: Fixed) -> usize {
+    if line_dx.0 == 0 {
+        return if x <= line_x { (line_dy.0 > 0) as usize } else { (line_dy.0 < 0) as usize };
+    }
+
+    if line_dy.0 == 0 {
+        return if y <= line_y { (line_dx.0 < 0) as usize } else { (line_dx.0 > 0) as usize };
+    }
+
+    let dx = x - line_x;
+    let dy = y - line_y;
+
+    if (line_dy.0 ^ line_dx.0 ^ dx.0 ^ dy.0) < 0 {
+        return ((line_dy.0 ^ dx.0) < 0) as usize;
+    }
+
+    let left = Fixed(line_dy.0 >> 16) * dx;
+    let right = dy * Fixed(line_dx.0 >> 16);
+
+    (right >= left) as usize
+}
 
 #[derive(Debug, Default)]
 pub struct MapBounds {
@@ -18,165 +39,187 @@ pub struct MapBounds {
     pub max_y: i16
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LineKind {
+    OneSided,
+    Secret,
+    Special,
+    FloorStep,
+    CeilingStep,
+    TwoSided
+}
+
 #[derive(Default)]
 pub struct GameMap {
     pub map_name: String,
     pub player: Player,
     pub vertexes: Vec<Vertex>,
     pub linedefs: Vec<Linedef>,
+    pub sidedefs: Vec<Sidedef>,
+    pub sectors: Vec<Sector>,
     pub nodes: Vec<Node>,
     pub ssectors: Vec<SubSector>,
     pub segs: Vec<Seg>,
     pub things: Vec<Thing>,
     pub bounds: MapBounds,
-    pub automap_scale_factor: i16,
-    render_w: u32,
-    render_h: u32
+    // Where the blockmap's 128 unit grid starts, which the automap grid lines up with.
+    pub blockmap_origin: Point
 }
 
 impl GameMap {
-    pub fn new(map_name: &str, player: Player, canvas: &WindowCanvas) -> Self {
-
-        let (mut render_w, mut render_h) = canvas.logical_size();
-
+    pub fn new(map_name: &str, player: Player) -> Self {
         Self {
             map_name: map_name.to_string(),
             player,
-            automap_scale_factor: 15,
-            render_w,
-            render_h,
             ..Default::default()
         }
     }
 
-    pub fn calc_map_bounds(&mut self) {
-        self.bounds.min_x = self.vertexes.iter().min_by(|a, b| a.x.cmp(&b.x)).unwrap().x;
-        self.bounds.max_x = self.vertexes.iter().max_by(|a, b| a.x.cmp(&b.x)).unwrap().x;
-        self.bounds.min_y = self.vertexes.iter().min_by(|a, b| a.y.cmp(&b.y)).unwrap().y;
-        self.bounds.max_y = self.vertexes.iter().max_by(|a, b| a.y.cmp(&b.y)).unwrap().y;
+    pub fn with_name(map_name: &str) -> Self {
+        Self {
+            map_name: map_name.to_string(),
+            player: Player::new(1),
+            ..Default::default()
+        }
     }
 
-    fn is_point_on_left_side(&self, point: Point, node_id: usize) -> bool {
-        let dx: i32 = (point.x - self.nodes[node_id].x_partition) as i32;
-        let dy: i32 = (point.y - self.nodes[node_id].y_partition) as i32;
+    // A map without vertexes is left with empty bounds at the origin, for the linter to
+    // report rather than fall over on.
+    pub fn calc_map_bounds(&mut self) {
+        self.bounds = MapBounds::default();
+
+        let Some(first) = self.vertexes.first() else {
+            return;
+        };
 
-        ((dx * self.nodes[node_id].dy_partition as i32) - (dy * self.nodes[node_id].dx_partition as i32)) <= 0
+        self.bounds = MapBounds { min_x: first.x, max_x: first.x, min_y: first.y, max_y: first.y };
+
+        for vertex in &self.vertexes {
+            self.bounds.min_x = self.bounds.min_x.min(vertex.x);
+            self.bounds.max_x = self.bounds.max_x.max(vertex.x);
+            self.bounds.min_y = self.bounds.min_y.min(vertex.y);
+            self.bounds.max_y = self.bounds.max_y.max(vertex.y);
+        }
     }
 
-    fn render_bsp_node(&self, canvas: &mut WindowCanvas, node_id: u16) {
-        if node_id >= SUB_SECTOR_IDENTIFIER {
-            let sub_sector_id = (node_id - SUB_SECTOR_IDENTIFIER) as usize;
-            self.render_sub_sector(canvas, sub_sector_id);
-            return;
+    pub fn linedef_kind(&self, linedef: &Linedef) -> LineKind {
+        if linedef.has_flag(Linedef::SECRET) {
+            return LineKind::Secret;
         }
 
-        let node = self.nodes[node_id as usize];
+        if linedef.line_type != 0 {
+            return LineKind::Special;
+        }
 
-        if self.is_point_on_left_side(self.player.position, node_id as usize) {
-            self.render_bsp_node(canvas, node.left_child_id);
-            self.render_bsp_node(canvas, node.right_child_id);
-        } else {
-            self.render_bsp_node(canvas, node.right_child_id);
-            self.render_bsp_node(canvas, node.left_child_id);
+        match self.linedef_sectors(linedef) {
+            (Some(front), Some(back)) => {
+                if front.floor_height != back.floor_height {
+                    LineKind::FloorStep
+                } else if front.ceiling_height != back.ceiling_height {
+                    LineKind::CeilingStep
+                } else {
+                    LineKind::TwoSided
+                }
+            },
+            _ => LineKind::OneSided
         }
     }
 
-    fn render_sub_sector(&self, canvas: &mut WindowCanvas, sub_sector_id: usize) {
-        let sub_sector = self.ssectors[sub_sector_id];
+    // The linedef's frontsector and backsector, with no back for a one-sided line.
+    pub fn linedef_sectors(&self, linedef: &Linedef) -> (Option<&Sector>, Option<&Sector>) {
+        let sector = |sidedef_id: u16| self.sidedefs.get(sidedef_id as usize)
+            .and_then(|sidedef| self.sectors.get(sidedef.sector_id as usize));
+        let back = if linedef.has_back_sidedef() { sector(linedef.back_sidedef_id) } else { None };
 
-        for i in 0..sub_sector.seg_count {
-            let seg_id = (sub_sector.first_seg_id + i) as usize;
-            self.render_seg(canvas, seg_id, sub_sector_id);
-        }
+        (sector(linedef.front_sidedef_id), back)
     }
 
-    fn render_seg(&self, canvas: &mut WindowCanvas, seg_id: usize, sub_sector_id: usize) {
-        let seg = self.segs[seg_id];
-        let sub_sector = self.ssectors[sub_sector_id];
+    // The sectors on the front and back of a seg, by id.
+    pub fn seg_sector_ids(&self, seg: &Seg) -> (Option<usize>, Option<usize>) {
+        let Some(linedef) = self.linedefs.get(seg.linedef_id as u16 as usize) else {
+            return (None, None);
+        };
 
-        let v1 = self.vertexes[seg.start_vertex_id as usize];
-        let v2 = self.vertexes[seg.end_vertex_id as usize];
+        let (front_id, back_id) = if seg.direction == 0 {
+            (linedef.front_sidedef_id, linedef.back_sidedef_id)
+        } else {
+            (linedef.back_sidedef_id, linedef.front_sidedef_id)
+        };
+
+        let sector_id = |sidedef_id: u16| self.sidedefs.get(sidedef_id as usize).map(|sidedef| sidedef.sector_id as usize);
+        let back = if linedef.has_flag(Linedef::TWO_SIDED) { sector_id(back_id) } else { None };
 
-        canvas.line(
-            self.remap_x(v1.x),
-            self.remap_y(v1.y),
-            self.remap_x(v2.x),
-            self.remap_y(v2.y),
-            self.get_color(sub_sector_id as u64)
-        );
+        (sector_id(front_id), back)
     }
 
-    fn get_color(&self, seed: u64) -> Color {
-        let mut rng = Pcg32::seed_from_u64(seed);
+    pub fn seg_sides(&self, seg: &Seg) -> (Option<&Sidedef>, Option<&Sector>, Option<&Sector>) {
+        let Some(linedef) = self.linedefs.get(seg.linedef_id as u16 as usize) else {
+            return (None, None, None);
+        };
 
-        let r: u8 = rng.gen_range(100..=255);
-        let g: u8 = rng.gen_range(100..=255);
-        let b: u8 = rng.gen_range(100..=255);
+        let (front_id, back_id) = if seg.direction == 0 {
+            (linedef.front_sidedef_id, linedef.back_sidedef_id)
+        } else {
+            (linedef.back_sidedef_id, linedef.front_sidedef_id)
+        };
 
-        Color::RGB(r, g, b)
-    }
+        let sidedef = self.sidedefs.get(front_id as usize);
+        let front = sidedef.and_then(|sidedef| self.sectors.get(sidedef.sector_id as usize));
+        let back = if linedef.has_flag(Linedef::TWO_SIDED) {
+            self.sidedefs.get(back_id as usize).and_then(|sidedef| self.sectors.get(sidedef.sector_id as usize))
+        } else {
+            None
+        };
 
-    fn remap_x(&self, x: i16) -> i16 {
-        (x + (-self.bounds.min_x)) / self.automap_scale_factor
+        (sidedef, front, back)
     }
 
-    fn remap_y(&self, y: i16) -> i16 {
-        self.render_h as i16 - (y + (-self.bounds.min_y)) / self.automap_scale_factor
+    pub fn sub_sector_sector(&self, sub_sector_id: usize) -> Option<&Sector> {
+        self.sectors.get(self.sub_sector_sector_id(sub_sector_id)?)
     }
 
-    pub fn render_automap(&self, canvas: &mut WindowCanvas) {
-        self.render_automap_player(canvas);
-        self.render_automap_walls(canvas);
+    pub fn sub_sector_sector_id(&self, sub_sector_id: usize) -> Option<usize> {
+        let sub_sector = self.ssectors.get(sub_sector_id)?;
+        let seg = self.segs.get(sub_sector.first_seg_id as u16 as usize)?;
+        let linedef = self.linedefs.get(seg.linedef_id as u16 as usize)?;
+
+        let sidedef_id = if seg.direction == 0 { linedef.front_sidedef_id } else { linedef.back_sidedef_id };
+        let sector_id = self.sidedefs.get(sidedef_id as usize)?.sector_id as usize;
 
-        let root_node_id = (self.nodes.len() - 1) as usize;
-        self.render_bsp_node(canvas, root_node_id as u16);
+        (sector_id < self.sectors.len()).then_some(sector_id)
     }
 
-    fn render_automap_player(&self, canvas: &WindowCanvas) {
-        canvas.filled_circle(
-            self.remap_x(self.player.position.x),
-            self.remap_y(self.player.position.y),
-            2,
-            Color::RED
-        );
+    pub fn point_on_side(&self, x: Fixed, y: Fixed, node: &Node) -> usize {
+        point_on_line_side(
+            x, y,
+            Fixed::from(node.x_partition), Fixed::from(node.y_partition),
+            Fixed::from(node.dx_partition), Fixed::from(node.dy_partition)
+        )
     }
 
-    fn render_automap_walls(&self, canvas: &WindowCanvas) {
-        for linedef in &self.linedefs {
-            let start = self.vertexes[linedef.start_vertex_id as usize];
-            let end = self.vertexes[linedef.end_vertex_id as usize];
-
-            canvas.line(
-                self.remap_x(start.x),
-                self.remap_y(start.y),
-                self.remap_x(end.x),
-                self.remap_y(end.y),
-                Color::RGB(70, 70, 70)
-            );
+    pub fn point_in_sub_sector(&self, x: Fixed, y: Fixed) -> usize {
+        if self.nodes.is_empty() {
+            return 0;
         }
-    }
 
-    fn render_automap_node(&self, canvas: &WindowCanvas, root_node_id: usize) {
-        let node = self.nodes[root_node_id];
+        let mut node_id = (self.nodes.len() - 1) as u16;
 
-        self.render_bbox(canvas, node.bbox_left, Color::RED);
-        self.render_bbox(canvas, node.bbox_right, Color::GREEN);
+        while node_id & SUB_SECTOR_IDENTIFIER == 0 {
+            let node = &self.nodes[node_id as usize];
 
-        canvas.line(
-            self.remap_x(node.x_partition),
-            self.remap_y(node.y_partition),
-            self.remap_x(node.x_partition + node.dx_partition),
-            self.remap_y(node.y_partition + node.dy_partition),
-            Color::BLUE
-        );
+            node_id = if self.point_on_side(x, y, node) == 0 { node.right_child_id } else { node.left_child_id };
+        }
+
+        (node_id & !SUB_SECTOR_IDENTIFIER) as usize
     }
 
-    fn render_bbox(&self, canvas: &WindowCanvas, bbox: BoundingBox, color: Color) {
-        let x = self.remap_x(bbox.left);
-        let y = self.remap_y(bbox.top);
-        let w = self.remap_x(bbox.right);
-        let h = self.remap_y(bbox.bottom);
+    pub(crate) fn get_color(&self, seed: u64) -> Color {
+        let mut rng = Pcg32::seed_from_u64(seed);
+
+        let r: u8 = rng.gen_range(100..=255);
+        let g: u8 = rng.gen_range(100..=255);
+        let b: u8 = rng.gen_range(100..=255);
 
-        canvas.rectangle(x, y, w, h, color);
+        Color::RGB(r, g, b)
     }
-}
\ No newline at end of file
+}
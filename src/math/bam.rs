@@ -0,0 +1,242 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use crate::math::{Fixed, FINESINE, FINETANGENT, FRACBITS, TANTOANGLE};
+
+pub const FINEANGLES: usize = 8192;
+pub const FINEMASK: usize = FINEANGLES - 1;
+pub const ANGLETOFINESHIFT: u32 = 19;
+
+pub const SLOPERANGE: u32 = 2048;
+pub const SLOPEBITS: u32 = 11;
+pub const DBITS: u32 = 16 - SLOPEBITS;
+
+pub const ANG45: Bam = Bam(0x2000_0000);
+pub const ANG90: Bam = Bam(0x4000_0000);
+pub const ANG180: Bam = Bam(0x8000_0000);
+pub const ANG270: Bam = Bam(0xc000_0000);
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bam(pub u32);
+
+impl Bam {
+    pub fn from_degrees(degrees: f64) -> Self {
+        Bam((degrees.rem_euclid(360.0) / 360.0 * 4294967296.0) as u64 as u32)
+    }
+
+    // Things store their facing in degrees, vanilla snaps it to 45 degree steps.
+    pub fn from_map_angle(angle: i16) -> Self {
+        Bam(ANG45.0.wrapping_mul((angle / 45) as u32))
+    }
+
+    pub fn to_degrees(self) -> f64 {
+        self.0 as f64 / 4294967296.0 * 360.0
+    }
+
+    pub const fn fine_index(self) -> usize {
+        (self.0 >> ANGLETOFINESHIFT) as usize
+    }
+
+    pub fn sin(self) -> Fixed {
+        Fixed(FINESINE[self.fine_index()])
+    }
+
+    pub fn cos(self) -> Fixed {
+        Fixed(FINESINE[self.fine_index() + FINEANGLES / 4])
+    }
+
+    // Only defined for angles within 90 degrees of the view direction, as in vanilla.
+    pub fn tan(self) -> Fixed {
+        Fixed(FINETANGENT[(self.0.wrapping_add(ANG90.0) >> ANGLETOFINESHIFT) as usize % (FINEANGLES / 2)])
+    }
+
+    // Turns the short way round, so 350 to 10 degrees passes through 0.
+    pub const fn lerp(self, to: Bam, frac: Fixed) -> Self {
+        let delta = to.0.wrapping_sub(self.0) as i32 as i64;
+
+        Bam(self.0.wrapping_add(((delta * frac.0 as i64) >> FRACBITS) as u32))
+    }
+}
+
+impl fmt::Display for Bam {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.2}°", self.to_degrees())
+    }
+}
+
+impl Add for Bam {
+    type Output = Bam;
+
+    fn add(self, other: Bam) -> Bam {
+        Bam(self.0.wrapping_add(other.0))
+    }
+}
+
+impl Sub for Bam {
+    type Output = Bam;
+
+    fn sub(self, other: Bam) -> Bam {
+        Bam(self.0.wrapping_sub(other.0))
+    }
+}
+
+impl AddAssign for Bam {
+    fn add_assign(&mut self, other: Bam) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Bam {
+    fn sub_assign(&mut self, other: Bam) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Bam {
+    type Output = Bam;
+
+    fn neg(self) -> Bam {
+        Bam(self.0.wrapping_neg())
+    }
+}
+
+fn slope_div(num: u32, den: u32) -> usize {
+    if den < 512 {
+        return SLOPERANGE as usize;
+    }
+
+    let ans = num.wrapping_shl(3) / (den >> 8);
+
+    ans.min(SLOPERANGE) as usize
+}
+
+// R_PointToAngle relative to the origin.
+pub fn point_to_angle(x: Fixed, y: Fixed) -> Bam {
+    let (mut x, mut y) = (x.0, y.0);
+
+    if x == 0 && y == 0 {
+        return Bam(0);
+    }
+
+    let angle = if x >= 0 {
+        if y >= 0 {
+            if x > y {
+                TANTOANGLE[slope_div(y as u32, x as u32)]
+            } else {
+                ANG90.0 - 1 - TANTOANGLE[slope_div(x as u32, y as u32)]
+            }
+        } else {
+            y = y.wrapping_neg();
+
+            if x > y {
+                TANTOANGLE[slope_div(y as u32, x as u32)].wrapping_neg()
+            } else {
+                ANG270.0 + TANTOANGLE[slope_div(x as u32, y as u32)]
+            }
+        }
+    } else {
+        x = x.wrapping_neg();
+
+        if y >= 0 {
+            if x > y {
+                ANG180.0 - 1 - TANTOANGLE[slope_div(y as u32, x as u32)]
+            } else {
+                ANG90.0 + TANTOANGLE[slope_div(x as u32, y as u32)]
+            }
+        } else {
+            y = y.wrapping_neg();
+
+            if x > y {
+                ANG180.0 + TANTOANGLE[slope_div(y as u32, x as u32)]
+            } else {
+                ANG270.0 - 1 - TANTOANGLE[slope_div(x as u32, y as u32)]
+            }
+        }
+    };
+
+    Bam(angle)
+}
+
+// R_PointToAngle2
+pub fn point_to_angle2(x1: Fixed, y1: Fixed, x2: Fixed, y2: Fixed) -> Bam {
+    point_to_angle(x2 - x1, y2 - y1)
+}
+
+// R_PointToDist from (x1, y1).
+pub fn point_to_dist(x1: Fixed, y1: Fixed, x2: Fixed, y2: Fixed) -> Fixed {
+    let mut dx = (x2 - x1).abs();
+    let mut dy = (y2 - y1).abs();
+
+    if dy > dx {
+        std::mem::swap(&mut dx, &mut dy);
+    }
+
+    let angle = if dx.0 != 0 {
+        let slope = (dy / dx).0 >> DBITS;
+        Bam(TANTOANGLE[slope as usize].wrapping_add(ANG90.0))
+    } else {
+        Bam(0)
+    };
+
+    dx / angle.sin()
+}
+
+// P_AproxDistance
+pub fn approx_distance(dx: Fixed, dy: Fixed) -> Fixed {
+    let (dx, dy) = (dx.abs(), dy.abs());
+
+    if dx < dy {
+        dx + dy - (dx >> 1)
+    } else {
+        dx + dy - (dy >> 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{FINESINE, FINETANGENT, TANTOANGLE};
+
+    #[test]
+    fn tables_match_vanilla() {
+        assert_eq!(FINESINE[..3], [25, 75, 125]);
+        assert_eq!(FINESINE[FINEANGLES / 4], 65535);
+        assert_eq!(FINESINE[FINEANGLES / 2], -25);
+        assert_eq!(FINETANGENT[..2], [-170910304, -56965752]);
+        assert_eq!(FINETANGENT[2047..2049], [-25, 25]);
+        assert_eq!(FINETANGENT[4095], 170910304);
+        assert_eq!(TANTOANGLE[..3], [0, 333772, 667544]);
+        assert_eq!(TANTOANGLE[2048], ANG45.0);
+    }
+
+    #[test]
+    fn slope_div_saturates_and_wraps_like_vanilla() {
+        assert_eq!(slope_div(100, 511), SLOPERANGE as usize);
+        assert_eq!(slope_div(u32::MAX, 1 << 16), SLOPERANGE as usize);
+        assert_eq!(slope_div(1 << 16, 1 << 17), 1024);
+        // num << 3 overflows to zero, as the unsigned shift does in C.
+        assert_eq!(slope_div(1 << 29, 1 << 28), 0);
+    }
+
+    #[test]
+    fn point_to_angle_keeps_vanilla_off_by_ones() {
+        let unit = Fixed::ONE;
+
+        assert_eq!(point_to_angle(Fixed::ZERO, Fixed::ZERO), Bam(0));
+        assert_eq!(point_to_angle(unit, Fixed::ZERO), Bam(0));
+        assert_eq!(point_to_angle(unit, unit), Bam(0x1fff_ffff));
+        assert_eq!(point_to_angle(Fixed::ZERO, unit), Bam(ANG90.0 - 1));
+        assert_eq!(point_to_angle(-unit, Fixed::ZERO), Bam(ANG180.0 - 1));
+        assert_eq!(point_to_angle(Fixed::ZERO, -unit), Bam(ANG270.0));
+        assert_eq!(point_to_angle(-unit, -unit), Bam(ANG270.0 - 1 - ANG45.0));
+    }
+
+    #[test]
+    fn point_to_dist_truncates_like_vanilla() {
+        let origin = Fixed::ZERO;
+
+        // finesine never quite reaches FRACUNIT, so even straight lines come out long.
+        assert_eq!(point_to_dist(origin, origin, Fixed::from_int(10), origin), Fixed(655370));
+        assert_eq!(point_to_dist(origin, origin, Fixed::from_int(3), Fixed::from_int(-4)), Fixed(327591));
+        assert_eq!(point_to_dist(Fixed::from_int(3), Fixed::from_int(4), origin, origin), Fixed(327591));
+    }
+}
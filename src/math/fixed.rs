@@ -0,0 +1,163 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Shl, Shr, Sub, SubAssign};
+
+pub const FRACBITS: i32 = 16;
+pub const FRACUNIT: i32 = 1 << FRACBITS;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed(pub i32);
+
+impl Fixed {
+    pub const ZERO: Fixed = Fixed(0);
+    pub const ONE: Fixed = Fixed(FRACUNIT);
+    pub const MAX: Fixed = Fixed(i32::MAX);
+    pub const MIN: Fixed = Fixed(i32::MIN);
+
+    pub const fn from_int(value: i32) -> Self {
+        Fixed(value << FRACBITS)
+    }
+
+    pub fn from_f64(value: f64) -> Self {
+        Fixed((value * FRACUNIT as f64) as i32)
+    }
+
+    pub const fn to_int(self) -> i32 {
+        self.0 >> FRACBITS
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / FRACUNIT as f64
+    }
+
+    pub const fn abs(self) -> Self {
+        Fixed(self.0.wrapping_abs())
+    }
+
+    pub const fn fixed_mul(self, other: Fixed) -> Self {
+        Fixed(((self.0 as i64 * other.0 as i64) >> FRACBITS) as i32)
+    }
+
+    // Saturates like vanilla instead of overflowing.
+    pub const fn fixed_div(self, other: Fixed) -> Self {
+        if (self.0.wrapping_abs() >> 14) >= other.0.wrapping_abs() {
+            return if (self.0 ^ other.0) < 0 { Fixed::MIN } else { Fixed::MAX };
+        }
+
+        Fixed((((self.0 as i64) << FRACBITS) / other.0 as i64) as i32)
+    }
+
+    // `frac` of the way from self to `to`, with 0 giving self and ONE giving `to`.
+    pub const fn lerp(self, to: Fixed, frac: Fixed) -> Self {
+        Fixed(self.0 + (((to.0 as i64 - self.0 as i64) * frac.0 as i64) >> FRACBITS) as i32)
+    }
+}
+
+impl From<i16> for Fixed {
+    fn from(value: i16) -> Self {
+        Fixed::from_int(value as i32)
+    }
+}
+
+impl fmt::Display for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_f64())
+    }
+}
+
+impl Add for Fixed {
+    type Output = Fixed;
+
+    fn add(self, other: Fixed) -> Fixed {
+        Fixed(self.0.wrapping_add(other.0))
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+
+    fn sub(self, other: Fixed) -> Fixed {
+        Fixed(self.0.wrapping_sub(other.0))
+    }
+}
+
+impl AddAssign for Fixed {
+    fn add_assign(&mut self, other: Fixed) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Fixed {
+    fn sub_assign(&mut self, other: Fixed) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Fixed;
+
+    fn neg(self) -> Fixed {
+        Fixed(self.0.wrapping_neg())
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Fixed;
+
+    fn mul(self, other: Fixed) -> Fixed {
+        self.fixed_mul(other)
+    }
+}
+
+impl Div for Fixed {
+    type Output = Fixed;
+
+    fn div(self, other: Fixed) -> Fixed {
+        self.fixed_div(other)
+    }
+}
+
+impl Shl<u32> for Fixed {
+    type Output = Fixed;
+
+    fn shl(self, bits: u32) -> Fixed {
+        Fixed(self.0.wrapping_shl(bits))
+    }
+}
+
+impl Shr<u32> for Fixed {
+    type Output = Fixed;
+
+    fn shr(self, bits: u32) -> Fixed {
+        Fixed(self.0 >> bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_mul_matches_vanilla() {
+        assert_eq!(Fixed(FRACUNIT * 3 / 2) * Fixed::from_int(-4), Fixed::from_int(-6));
+        // The shift rounds towards negative infinity, not zero.
+        assert_eq!(Fixed(-1) * Fixed(1), Fixed(-1));
+        assert_eq!(Fixed(i32::MAX) * Fixed::ONE, Fixed(i32::MAX));
+    }
+
+    #[test]
+    fn fixed_div_matches_vanilla() {
+        assert_eq!(Fixed::from_int(3) / Fixed::from_int(2), Fixed(98304));
+        assert_eq!(Fixed::from_int(-7) / Fixed::from_int(2), Fixed(-229376));
+        assert_eq!(Fixed(1) / Fixed::from_int(3), Fixed(0));
+    }
+
+    #[test]
+    fn fixed_div_saturates() {
+        assert_eq!(Fixed(1 << 30) / Fixed(1), Fixed::MAX);
+        assert_eq!(Fixed(-(1 << 30)) / Fixed(1), Fixed::MIN);
+        assert_eq!(Fixed(1 << 30) / Fixed(-1), Fixed::MIN);
+        assert_eq!(Fixed::ONE / Fixed::ZERO, Fixed::MAX);
+        // Just under the limit still divides.
+        assert_eq!(Fixed(1 << 14) / Fixed(2), Fixed(1 << 29));
+    }
+}
@@ -0,0 +1,11 @@
+mod fixed;
+pub use fixed::{Fixed, FRACBITS, FRACUNIT};
+
+mod bam;
+pub use bam::*;
+
+mod tables;
+pub use tables::{FINESINE, FINETANGENT, TANTOANGLE};
+
+mod random;
+pub use random::{Random, RNDTABLE};
\ No newline at end of file
@@ -0,0 +1,200 @@
+// The menus of m_menu.c, in the order of MenuId.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) enum MenuId {
+    Main,
+    Episode,
+    NewGame,
+    Options,
+    ReadThis1,
+    ReadThis2,
+    Sound,
+    Load,
+    Save
+}
+
+// An item's status: -1 for a gap, 0 for an empty load slot, 1 for something to pick and 2
+// for a slider that left and right move.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) enum ItemKind {
+    Gap,
+    Disabled,
+    Select,
+    Slider
+}
+
+// What picking an item does, vanilla's routine pointer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) enum Choice {
+    None,
+    NewGame,
+    Options,
+    LoadGame,
+    SaveGame,
+    ReadThis,
+    ReadThis2,
+    FinishReadThis,
+    QuitGame,
+    Episode,
+    Skill,
+    EndGame,
+    Messages,
+    Detail,
+    ScreenSize,
+    MouseSensitivity,
+    Sound,
+    SfxVolume,
+    MusicVolume,
+    LoadSlot,
+    SaveSlot
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct MenuItem {
+    pub kind: ItemKind,
+    pub patch: &'static str,
+    pub hotkey: Option<char>,
+    pub choice: Choice
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct MenuDef {
+    pub items: Vec<MenuItem>,
+    pub previous: Option<MenuId>,
+    pub x: i32,
+    pub y: i32,
+    // The item the skull goes back to when the menu is shown again.
+    pub last_on: usize
+}
+
+// Rows of the menus whose drawers put something beside an item.
+pub(super) const OPTIONS_MESSAGES: usize = 1;
+pub(super) const OPTIONS_DETAIL: usize = 2;
+pub(super) const OPTIONS_SCREEN_SIZE: usize = 3;
+pub(super) const OPTIONS_MOUSE_SENSITIVITY: usize = 5;
+pub(super) const SOUND_SFX_VOLUME: usize = 0;
+pub(super) const SOUND_MUSIC_VOLUME: usize = 2;
+pub(super) const NIGHTMARE: usize = 4;
+
+const fn item(kind: ItemKind, patch: &'static str, hotkey: char, choice: Choice) -> MenuItem {
+    MenuItem { kind, patch, hotkey: Some(hotkey), choice }
+}
+
+const GAP: MenuItem = MenuItem { kind: ItemKind::Gap, patch: "", hotkey: None, choice: Choice::None };
+
+fn menu(items: Vec<MenuItem>, previous: Option<MenuId>, x: i32, y: i32) -> MenuDef {
+    MenuDef { items, previous, x, y, last_on: 0 }
+}
+
+fn save_slots(choice: Choice) -> Vec<MenuItem> {
+    ['1', '2', '3', '4', '5', '6'].into_iter().map(|hotkey| item(ItemKind::Select, "", hotkey, choice)).collect()
+}
+
+// MainDef to SaveDef, with M_Init's changes for DOOM II: no Read This! on the main menu and
+// a single help screen.
+pub(super) fn menus(commercial: bool) -> Vec<MenuDef> {
+    use ItemKind::{Select, Slider};
+
+    let mut main = menu(
+        vec![
+            item(Select, "M_NGAME", 'n', Choice::NewGame),
+            item(Select, "M_OPTION", 'o', Choice::Options),
+            item(Select, "M_LOADG", 'l', Choice::LoadGame),
+            item(Select, "M_SAVEG", 's', Choice::SaveGame),
+            item(Select, "M_RDTHIS", 'r', Choice::ReadThis),
+            item(Select, "M_QUITG", 'q', Choice::QuitGame)
+        ],
+        None, 97, 64
+    );
+
+    let episode = menu(
+        vec![
+            item(Select, "M_EPI1", 'k', Choice::Episode),
+            item(Select, "M_EPI2", 't', Choice::Episode),
+            item(Select, "M_EPI3", 'i', Choice::Episode),
+            item(Select, "M_EPI4", 't', Choice::Episode)
+        ],
+        Some(MenuId::Main), 48, 63
+    );
+
+    let mut new_game = menu(
+        vec![
+            item(Select, "M_JKILL", 'i', Choice::Skill),
+            item(Select, "M_ROUGH", 'h', Choice::Skill),
+            item(Select, "M_HURT", 'h', Choice::Skill),
+            item(Select, "M_ULTRA", 'u', Choice::Skill),
+            item(Select, "M_NMARE", 'n', Choice::Skill)
+        ],
+        Some(MenuId::Episode), 48, 63
+    );
+    new_game.last_on = 2;
+
+    let options = menu(
+        vec![
+            item(Select, "M_ENDGAM", 'e', Choice::EndGame),
+            item(Select, "M_MESSG", 'm', Choice::Messages),
+            item(Select, "M_DETAIL", 'g', Choice::Detail),
+            item(Slider, "M_SCRNSZ", 's', Choice::ScreenSize),
+            GAP,
+            item(Slider, "M_MSENS", 'm', Choice::MouseSensitivity),
+            GAP,
+            item(Select, "M_SVOL", 's', Choice::Sound)
+        ],
+        Some(MenuId::Main), 60, 37
+    );
+
+    let page = |choice| MenuItem { kind: Select, patch: "", hotkey: None, choice };
+    let mut read_this1 = menu(vec![page(Choice::ReadThis2)], Some(MenuId::Main), 280, 185);
+    let read_this2 = menu(vec![page(Choice::FinishReadThis)], Some(MenuId::ReadThis1), 330, 175);
+
+    let sound = menu(
+        vec![
+            item(Slider, "M_SFXVOL", 's', Choice::SfxVolume),
+            GAP,
+            item(Slider, "M_MUSVOL", 'm', Choice::MusicVolume),
+            GAP
+        ],
+        Some(MenuId::Options), 80, 64
+    );
+
+    let load = menu(save_slots(Choice::LoadSlot), Some(MenuId::Main), 80, 54);
+    let save = menu(save_slots(Choice::SaveSlot), Some(MenuId::Main), 80, 54);
+
+    if commercial {
+        main.items.remove(4);
+        main.y += 8;
+        new_game.previous = Some(MenuId::Main);
+        read_this1.items[0].choice = Choice::FinishReadThis;
+        (read_this1.x, read_this1.y) = (330, 165);
+    }
+
+    vec![main, episode, new_game, options, read_this1, read_this2, sound, load, save]
+}
+
+// d_englsh.h
+pub(super) const PRESS_YN: &str = "press y or n.";
+pub(super) const EMPTY_STRING: &str = "empty slot";
+pub(super) const LOAD_NET: &str = "you can't do load while in a net game!\n\npress a key.";
+pub(super) const QUICK_LOAD_NET: &str = "you can't quickload during a netgame!\n\npress a key.";
+pub(super) const QUICK_SAVE_SPOT: &str = "you haven't picked a quicksave slot yet!\n\npress a key.";
+pub(super) const SAVE_DEAD: &str = "you can't save if you aren't playing!\n\npress a key.";
+pub(super) const NEW_GAME: &str = "you can't start a new game\nwhile in a network game.\n\npress a key.";
+pub(super) const NIGHTMARE_PROMPT: &str = "are you sure? this skill level\nisn't even remotely fair.\n\npress y or n.";
+pub(super) const SHAREWARE: &str = "this is the shareware version of doom.\n\nyou need to order the entire trilogy.\n\npress a key.";
+pub(super) const NET_END: &str = "you can't end a netgame!\n\npress a key.";
+pub(super) const END_GAME: &str = "are you sure you want to end the game?\n\npress y or n.";
+pub(super) const MESSAGES_OFF: &str = "Messages OFF";
+pub(super) const MESSAGES_ON: &str = "Messages ON";
+pub(super) const DETAIL_HIGH: &str = "High detail";
+pub(super) const DETAIL_LOW: &str = "Low detail";
+pub(super) const QUIT_TO_DOS: &str = "(press y to quit to dos.)";
+
+// endmsg, without the plain QUITMSG that M_QuitDOOM never picks.
+pub(super) const QUIT_MESSAGES: [&str; 7] = [
+    "please don't leave, there's more\ndemons to toast!",
+    "let's beat it -- this is turning\ninto a bloodbath!",
+    "i wouldn't leave if i were you.\ndos is much worse.",
+    "you're trying to say you like dos\nbetter than me, right?",
+    "don't leave yet -- there's a\ndemon around that corner!",
+    "ya know, next time you come in here\ni'm gonna toast ya.",
+    "go ahead and leave. see if i care."
+];
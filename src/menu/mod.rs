@@ -0,0 +1,928 @@
+use std::collections::HashMap;
+use std::path::Path;
+use crate::backend::DrawTarget;
+use crate::hud::HudFont;
+use crate::input::{Event, Key, BUTTON_1, BUTTON_2};
+use crate::savegame::{read_save_descriptions, SAVE_SLOTS, SAVE_STRING_SIZE};
+use crate::settings::{Settings, MAX_MOUSE_SENSITIVITY, MAX_SCREEN_BLOCKS, MAX_VOLUME, MIN_SCREEN_BLOCKS};
+use crate::stats::Skill;
+use crate::wad::{Loader, Patch};
+
+mod defs;
+use defs::{
+    menus, Choice, ItemKind, MenuDef, MenuId, DETAIL_HIGH, DETAIL_LOW, EMPTY_STRING, END_GAME, LOAD_NET, MESSAGES_OFF,
+    MESSAGES_ON, NET_END, NEW_GAME, NIGHTMARE, NIGHTMARE_PROMPT, OPTIONS_DETAIL, OPTIONS_MESSAGES,
+    OPTIONS_MOUSE_SENSITIVITY, OPTIONS_SCREEN_SIZE, PRESS_YN, QUICK_LOAD_NET, QUICK_SAVE_SPOT, QUIT_MESSAGES,
+    QUIT_TO_DOS, SAVE_DEAD, SHAREWARE, SOUND_MUSIC_VOLUME, SOUND_SFX_VOLUME
+};
+
+const LINE_HEIGHT: i32 = 16;
+const SKULL_X_OFFSET: i32 = -32;
+const SKULL_NAMES: [&str; 2] = ["M_SKULL1", "M_SKULL2"];
+const SKULL_TICS: i32 = 8;
+
+// What the menu drawers put on screen beside the items themselves.
+const DRAWER_PATCHES: [&str; 22] = [
+    "M_DOOM", "M_EPISOD", "M_NEWG", "M_SKILL", "M_OPTTTL", "M_MSGON", "M_MSGOFF", "M_GDHIGH", "M_GDLOW",
+    "M_THERML", "M_THERMM", "M_THERMR", "M_THERMO", "M_LSLEFT", "M_LSCNTR", "M_LSRGHT", "M_SKULL1", "M_SKULL2",
+    "HELP", "HELP1", "HELP2", "CREDIT"
+];
+
+// Slider widths, in thermometer notches.
+const SCREEN_SIZE_NOTCHES: i32 = 9;
+const MOUSE_SENSITIVITY_NOTCHES: i32 = 10;
+const VOLUME_NOTCHES: i32 = 16;
+
+// How many menu tics a mouse or gamepad press waits before the next one counts, and how far
+// the mouse moves for one step.
+const AXIS_WAIT: i32 = 5;
+const JOYSTICK_SIDE_WAIT: i32 = 2;
+const MOUSE_BUTTON_WAIT: i32 = 15;
+const MOUSE_STEP: i32 = 30;
+
+// What the game has to do for the menu, taken with take_actions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MenuAction {
+    NewGame { episode: usize, skill: Skill },
+    LoadGame(usize),
+    SaveGame { slot: usize, description: String },
+    EndGame,
+    Quit,
+    Message(&'static str),
+    // message_dontfuckwithme: shown even with messages off.
+    ForcedMessage(&'static str)
+}
+
+// What the menu needs to know about the game.
+#[derive(Debug, Copy, Clone)]
+pub struct MenuContext<'a> {
+    // usergame: a level is being played, not a demo or the title screen.
+    pub playing: bool,
+    pub netgame: bool,
+    pub automap: bool,
+    pub chatting: bool,
+    pub game_tic: u64,
+    pub save_dir: &'a Path
+}
+
+// A message's routine, run on the key that answers it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Confirm {
+    Quit,
+    Nightmare,
+    EndGame,
+    QuickSave,
+    QuickLoad
+}
+
+struct Message {
+    text: String,
+    confirm: Option<Confirm>,
+    needs_input: bool,
+    last_menu_active: bool
+}
+
+// A save description being typed, and what it was before.
+struct SaveEntry {
+    slot: usize,
+    old: String
+}
+
+// m_menu.c
+pub struct Menu {
+    menus: Vec<MenuDef>,
+    episodes: [bool; 4],
+    commercial: bool,
+    patches: HashMap<&'static str, Patch>,
+    font: Option<HudFont>,
+    active: bool,
+    current: MenuId,
+    item_on: usize,
+    episode: usize,
+    message: Option<Message>,
+    save_entry: Option<SaveEntry>,
+    save_strings: [String; SAVE_SLOTS],
+    quick_save_slot: Option<usize>,
+    // quickSaveSlot == -2: the next save picks the quicksave slot.
+    picking_quick_save: bool,
+    which_skull: usize,
+    skull_tics: i32,
+    time: i32,
+    mouse_wait: i32,
+    joystick_wait: i32,
+    mouse: (i32, i32),
+    last_mouse: (i32, i32),
+    actions: Vec<MenuAction>
+}
+
+impl Menu {
+    // M_Init. `episodes` says which of E1 to E4 the WAD has; the fourth is only listed if
+    // it is there. Graphics the WAD lacks are left out.
+    pub fn load(loader: &mut Loader, commercial: bool, episodes: [bool; 4]) -> Self {
+        let mut menu = Self::new(commercial, episodes);
+
+        let names: Vec<&'static str> = menu.menus.iter()
+            .flat_map(|menu| menu.items.iter().map(|item| item.patch))
+            .chain(DRAWER_PATCHES)
+            .filter(|name| !name.is_empty())
+            .collect();
+
+        menu.patches = names.into_iter()
+            .filter_map(|name| loader.load_patch(name).ok().map(|patch| (name, patch)))
+            .collect();
+        menu.font = HudFont::load(loader).ok();
+        menu
+    }
+
+    // The menus without any graphics.
+    fn new(commercial: bool, episodes: [bool; 4]) -> Self {
+        let mut menus = menus(commercial);
+
+        if !episodes[3] {
+            menus[MenuId::Episode as usize].items.truncate(3);
+        }
+
+        Self {
+            menus,
+            episodes,
+            commercial,
+            patches: HashMap::new(),
+            font: None,
+            active: false,
+            current: MenuId::Main,
+            item_on: 0,
+            episode: 0,
+            message: None,
+            save_entry: None,
+            save_strings: Default::default(),
+            quick_save_slot: None,
+            picking_quick_save: false,
+            which_skull: 0,
+            skull_tics: SKULL_TICS,
+            time: 0,
+            mouse_wait: 0,
+            joystick_wait: 0,
+            mouse: (0, 0),
+            last_mouse: (0, 0),
+            actions: Vec::new()
+        }
+    }
+
+    // menuactive, which a message on screen counts as too.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn take_actions(&mut self) -> Vec<MenuAction> {
+        std::mem::take(&mut self.actions)
+    }
+
+    fn menu(&self) -> &MenuDef {
+        &self.menus[self.current as usize]
+    }
+
+    fn menu_mut(&mut self) -> &mut MenuDef {
+        &mut self.menus[self.current as usize]
+    }
+
+    // M_StartControlPanel
+    fn start_control_panel(&mut self) {
+        if self.active {
+            return;
+        }
+
+        self.active = true;
+        self.current = MenuId::Main;
+        self.item_on = self.menu().last_on;
+    }
+
+    // M_SetupNextMenu
+    fn setup_next_menu(&mut self, menu: MenuId) {
+        self.current = menu;
+        self.item_on = self.menu().last_on;
+    }
+
+    // M_StartMessage
+    fn start_message(&mut self, text: &str, confirm: Option<Confirm>, needs_input: bool) {
+        self.message = Some(Message { text: text.to_string(), confirm, needs_input, last_menu_active: self.active });
+        self.active = true;
+    }
+
+    // M_Ticker
+    pub fn tick(&mut self) {
+        self.time += 1;
+        self.skull_tics -= 1;
+
+        if self.skull_tics <= 0 {
+            self.which_skull ^= 1;
+            self.skull_tics = SKULL_TICS;
+        }
+    }
+
+    // The key a mouse or gamepad event stands for: moving steps through the menu and the
+    // buttons pick or go back.
+    fn event_key(&mut self, event: Event) -> Option<Key> {
+        let mut key = None;
+
+        match event {
+            Event::KeyDown(down) => key = Some(down),
+            Event::Joystick { buttons, x, y } if self.joystick_wait < self.time => {
+                if y == -1 {
+                    key = Some(Key::Up);
+                    self.joystick_wait = self.time + AXIS_WAIT;
+                } else if y == 1 {
+                    key = Some(Key::Down);
+                    self.joystick_wait = self.time + AXIS_WAIT;
+                }
+
+                if x == -1 {
+                    key = Some(Key::Left);
+                    self.joystick_wait = self.time + JOYSTICK_SIDE_WAIT;
+                } else if x == 1 {
+                    key = Some(Key::Right);
+                    self.joystick_wait = self.time + JOYSTICK_SIDE_WAIT;
+                }
+
+                if buttons & BUTTON_1 != 0 {
+                    key = Some(Key::Enter);
+                    self.joystick_wait = self.time + AXIS_WAIT;
+                }
+
+                if buttons & BUTTON_2 != 0 {
+                    key = Some(Key::Backspace);
+                    self.joystick_wait = self.time + AXIS_WAIT;
+                }
+            },
+            Event::Mouse { buttons, x, y } if self.mouse_wait < self.time => {
+                self.mouse.1 += y;
+
+                if self.mouse.1 < self.last_mouse.1 - MOUSE_STEP {
+                    key = Some(Key::Down);
+                    self.mouse_wait = self.time + AXIS_WAIT;
+                    self.last_mouse.1 -= MOUSE_STEP;
+                    self.mouse.1 = self.last_mouse.1;
+                } else if self.mouse.1 > self.last_mouse.1 + MOUSE_STEP {
+                    key = Some(Key::Up);
+                    self.mouse_wait = self.time + AXIS_WAIT;
+                    self.last_mouse.1 += MOUSE_STEP;
+                    self.mouse.1 = self.last_mouse.1;
+                }
+
+                self.mouse.0 += x;
+
+                if self.mouse.0 < self.last_mouse.0 - MOUSE_STEP {
+                    key = Some(Key::Left);
+                    self.mouse_wait = self.time + AXIS_WAIT;
+                    self.last_mouse.0 -= MOUSE_STEP;
+                    self.mouse.0 = self.last_mouse.0;
+                } else if self.mouse.0 > self.last_mouse.0 + MOUSE_STEP {
+                    key = Some(Key::Right);
+                    self.mouse_wait = self.time + AXIS_WAIT;
+                    self.last_mouse.0 += MOUSE_STEP;
+                    self.mouse.0 = self.last_mouse.0;
+                }
+
+                if buttons & BUTTON_1 != 0 {
+                    key = Some(Key::Enter);
+                    self.mouse_wait = self.time + MOUSE_BUTTON_WAIT;
+                }
+
+                if buttons & BUTTON_2 != 0 {
+                    key = Some(Key::Backspace);
+                    self.mouse_wait = self.time + MOUSE_BUTTON_WAIT;
+                }
+            },
+            _ => {}
+        }
+
+        key
+    }
+
+    // M_Responder: whether the menu took the event.
+    pub fn responder(&mut self, event: Event, context: &MenuContext, settings: &mut Settings) -> bool {
+        let Some(key) = self.event_key(event) else {
+            return false;
+        };
+
+        if let Some(entry) = &self.save_entry {
+            self.save_string_responder(key, entry.slot);
+            return true;
+        }
+
+        if let Some(message) = self.message.take() {
+            if message.needs_input && !matches!(key, Key::Char(' ' | 'n' | 'y') | Key::Escape) {
+                self.message = Some(message);
+                return false;
+            }
+
+            self.active = message.last_menu_active;
+
+            if let (Some(confirm), Key::Char('y')) = (message.confirm, key) {
+                self.confirm(confirm);
+            }
+
+            // As in vanilla, answering a message always leaves the menus.
+            self.active = false;
+            return true;
+        }
+
+        if !self.active {
+            return self.control_panel_responder(key, context, settings);
+        }
+
+        let item_count = self.menu().items.len();
+
+        match key {
+            Key::Down | Key::Up => {
+                loop {
+                    self.item_on = if key == Key::Down {
+                        (self.item_on + 1) % item_count
+                    } else {
+                        (self.item_on + item_count - 1) % item_count
+                    };
+
+                    if self.menu().items[self.item_on].kind != ItemKind::Gap {
+                        break;
+                    }
+                }
+            },
+            Key::Left | Key::Right => {
+                let item = &self.menu().items[self.item_on];
+
+                if item.kind == ItemKind::Slider {
+                    self.routine(item.choice, (key == Key::Right) as usize, context, settings);
+                }
+            },
+            Key::Enter => {
+                let item = &self.menu().items[self.item_on];
+                let (kind, choice) = (item.kind, item.choice);
+
+                if matches!(kind, ItemKind::Select | ItemKind::Slider) && choice != Choice::None {
+                    self.menu_mut().last_on = self.item_on;
+
+                    let argument = if kind == ItemKind::Slider { 1 } else { self.item_on };
+                    self.routine(choice, argument, context, settings);
+                }
+            },
+            Key::Escape => {
+                self.menu_mut().last_on = self.item_on;
+                self.active = false;
+            },
+            Key::Backspace => {
+                self.menu_mut().last_on = self.item_on;
+
+                if let Some(previous) = self.menu().previous {
+                    self.setup_next_menu(previous);
+                }
+            },
+            Key::Char(c) => {
+                // The next item down with the key as its hotkey, wrapping round.
+                let hotkey = (1..=item_count)
+                    .map(|step| (self.item_on + step) % item_count)
+                    .find(|&i| self.menu().items[i].hotkey == Some(c));
+
+                match hotkey {
+                    Some(i) => self.item_on = i,
+                    None => return false
+                }
+            },
+            _ => return false
+        }
+
+        true
+    }
+
+    // The keys that work with the menus down: the function keys, the screen size and Escape
+    // to bring up the main menu.
+    fn control_panel_responder(&mut self, key: Key, context: &MenuContext, settings: &mut Settings) -> bool {
+        match key {
+            Key::Char('-' | '=') if context.automap || context.chatting => return false,
+            Key::Char('-') => self.size_display(0, settings),
+            Key::Char('=') => self.size_display(1, settings),
+            Key::Function(1) => {
+                self.start_control_panel();
+                self.current = MenuId::ReadThis1;
+                self.item_on = 0;
+            },
+            Key::Function(2) => {
+                self.start_control_panel();
+                self.save_game(context);
+            },
+            Key::Function(3) => {
+                self.start_control_panel();
+                self.load_game(context);
+            },
+            Key::Function(4) => {
+                self.start_control_panel();
+                self.current = MenuId::Sound;
+                self.item_on = SOUND_SFX_VOLUME;
+            },
+            Key::Function(5) => self.change_detail(settings),
+            Key::Function(6) => self.quick_save(context),
+            Key::Function(7) => self.end_game(context),
+            Key::Function(8) => self.change_messages(settings),
+            Key::Function(9) => self.quick_load(context),
+            Key::Function(10) => self.quit_doom(context),
+            Key::Escape => self.start_control_panel(),
+            _ => return false
+        }
+
+        true
+    }
+
+    // The typing part of M_Responder while a save is being named.
+    fn save_string_responder(&mut self, key: Key, slot: usize) {
+        match key {
+            Key::Backspace => {
+                self.save_strings[slot].pop();
+            },
+            Key::Escape => {
+                if let Some(entry) = self.save_entry.take() {
+                    self.save_strings[slot] = entry.old;
+                }
+            },
+            Key::Enter => {
+                self.save_entry = None;
+
+                if !self.save_strings[slot].is_empty() {
+                    self.do_save(slot);
+                }
+            },
+            Key::Char(c) => {
+                let c = c.to_ascii_uppercase();
+                let text = &self.save_strings[slot];
+                let width = self.font.as_ref().map_or(0, |font| font.text_width(text));
+
+                if (' '..='_').contains(&c) && text.len() < SAVE_STRING_SIZE - 1 && width < (SAVE_STRING_SIZE as i32 - 2) * 8 {
+                    self.save_strings[slot].push(c);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    // The item routines. `argument` is the item picked, or for sliders 0 to go down and 1 to
+    // go up.
+    fn routine(&mut self, choice: Choice, argument: usize, context: &MenuContext, settings: &mut Settings) {
+        match choice {
+            Choice::None => {},
+            Choice::NewGame => {
+                if context.netgame {
+                    self.start_message(NEW_GAME, None, false);
+                } else if self.commercial {
+                    self.setup_next_menu(MenuId::NewGame);
+                } else {
+                    self.setup_next_menu(MenuId::Episode);
+                }
+            },
+            Choice::Options => self.setup_next_menu(MenuId::Options),
+            Choice::LoadGame => self.load_game(context),
+            Choice::SaveGame => self.save_game(context),
+            Choice::ReadThis => self.setup_next_menu(MenuId::ReadThis1),
+            Choice::ReadThis2 => self.setup_next_menu(MenuId::ReadThis2),
+            Choice::FinishReadThis => self.setup_next_menu(MenuId::Main),
+            Choice::QuitGame => self.quit_doom(context),
+            Choice::Episode => {
+                if !self.episodes[argument] {
+                    self.start_message(SHAREWARE, None, false);
+                    self.setup_next_menu(MenuId::ReadThis1);
+                    return;
+                }
+
+                self.episode = argument;
+                self.setup_next_menu(MenuId::NewGame);
+            },
+            Choice::Skill => {
+                if argument == NIGHTMARE {
+                    self.start_message(NIGHTMARE_PROMPT, Some(Confirm::Nightmare), true);
+                    return;
+                }
+
+                self.new_game(Skill::ALL[argument]);
+            },
+            Choice::EndGame => self.end_game(context),
+            Choice::Messages => self.change_messages(settings),
+            Choice::Detail => self.change_detail(settings),
+            Choice::ScreenSize => self.size_display(argument, settings),
+            Choice::MouseSensitivity => step(&mut settings.mouse_sensitivity, argument, 0, MAX_MOUSE_SENSITIVITY),
+            Choice::Sound => self.setup_next_menu(MenuId::Sound),
+            Choice::SfxVolume => step(&mut settings.sfx_volume, argument, 0, MAX_VOLUME),
+            Choice::MusicVolume => step(&mut settings.music_volume, argument, 0, MAX_VOLUME),
+            Choice::LoadSlot => self.load_select(argument),
+            Choice::SaveSlot => {
+                // M_SaveSelect: an empty slot starts out blank rather than "empty slot".
+                let old = self.save_strings[argument].clone();
+
+                if old == EMPTY_STRING {
+                    self.save_strings[argument].clear();
+                }
+
+                self.save_entry = Some(SaveEntry { slot: argument, old });
+            }
+        }
+    }
+
+    // A message's routine, once it has been answered yes.
+    fn confirm(&mut self, confirm: Confirm) {
+        match confirm {
+            Confirm::Quit => self.actions.push(MenuAction::Quit),
+            Confirm::Nightmare => self.new_game(Skill::Nightmare),
+            Confirm::EndGame => {
+                self.menu_mut().last_on = self.item_on;
+                self.active = false;
+                self.actions.push(MenuAction::EndGame);
+            },
+            Confirm::QuickSave => {
+                if let Some(slot) = self.quick_save_slot {
+                    self.do_save(slot);
+                }
+            },
+            Confirm::QuickLoad => {
+                if let Some(slot) = self.quick_save_slot {
+                    self.load_select(slot);
+                }
+            }
+        }
+    }
+
+    // M_ChooseSkill's G_DeferedInitNew
+    fn new_game(&mut self, skill: Skill) {
+        let episode = if self.commercial { 0 } else { self.episode };
+
+        self.actions.push(MenuAction::NewGame { episode, skill });
+        self.active = false;
+    }
+
+    // M_ReadSaveStrings: empty slots can't be loaded.
+    fn read_save_strings(&mut self, dir: &Path) {
+        let descriptions = read_save_descriptions(dir);
+
+        for (slot, description) in descriptions.into_iter().enumerate() {
+            let item = &mut self.menus[MenuId::Load as usize].items[slot];
+
+            item.kind = if description.is_some() { ItemKind::Select } else { ItemKind::Disabled };
+            self.save_strings[slot] = description.unwrap_or_else(|| EMPTY_STRING.to_string());
+        }
+    }
+
+    // M_LoadGame
+    fn load_game(&mut self, context: &MenuContext) {
+        if context.netgame {
+            self.start_message(LOAD_NET, None, false);
+            return;
+        }
+
+        self.setup_next_menu(MenuId::Load);
+        self.read_save_strings(context.save_dir);
+    }
+
+    // M_LoadSelect
+    fn load_select(&mut self, slot: usize) {
+        self.actions.push(MenuAction::LoadGame(slot));
+        self.active = false;
+    }
+
+    // M_SaveGame
+    fn save_game(&mut self, context: &MenuContext) {
+        if !context.playing {
+            self.start_message(SAVE_DEAD, None, false);
+            return;
+        }
+
+        self.setup_next_menu(MenuId::Save);
+        self.read_save_strings(context.save_dir);
+    }
+
+    // M_DoSave
+    fn do_save(&mut self, slot: usize) {
+        self.actions.push(MenuAction::SaveGame { slot, description: self.save_strings[slot].clone() });
+        self.active = false;
+
+        if self.picking_quick_save {
+            self.quick_save_slot = Some(slot);
+            self.picking_quick_save = false;
+        }
+    }
+
+    // M_QuickSave: the first one picks a slot through the save menu.
+    fn quick_save(&mut self, context: &MenuContext) {
+        if !context.playing {
+            return;
+        }
+
+        match self.quick_save_slot {
+            Some(slot) => {
+                let prompt = format!("quicksave over your game named\n\n'{}'?\n\n{}", self.save_strings[slot], PRESS_YN);
+                self.start_message(&prompt, Some(Confirm::QuickSave), true);
+            },
+            None => {
+                self.start_control_panel();
+                self.read_save_strings(context.save_dir);
+                self.setup_next_menu(MenuId::Save);
+                self.picking_quick_save = true;
+            }
+        }
+    }
+
+    // M_QuickLoad
+    fn quick_load(&mut self, context: &MenuContext) {
+        if context.netgame {
+            self.start_message(QUICK_LOAD_NET, None, false);
+            return;
+        }
+
+        match self.quick_save_slot {
+            Some(slot) => {
+                let prompt = format!("do you want to quickload the game named\n\n'{}'?\n\n{}", self.save_strings[slot], PRESS_YN);
+                self.start_message(&prompt, Some(Confirm::QuickLoad), true);
+            },
+            None => self.start_message(QUICK_SAVE_SPOT, None, false)
+        }
+    }
+
+    // M_EndGame
+    fn end_game(&mut self, context: &MenuContext) {
+        if !context.playing {
+            return;
+        }
+
+        if context.netgame {
+            self.start_message(NET_END, None, false);
+        } else {
+            self.start_message(END_GAME, Some(Confirm::EndGame), true);
+        }
+    }
+
+    // M_QuitDOOM
+    fn quit_doom(&mut self, context: &MenuContext) {
+        let message = QUIT_MESSAGES[((context.game_tic >> 2) % QUIT_MESSAGES.len() as u64) as usize];
+        self.start_message(&format!("{}\n\n{}", message, QUIT_TO_DOS), Some(Confirm::Quit), true);
+    }
+
+    // M_ChangeMessages
+    fn change_messages(&mut self, settings: &mut Settings) {
+        settings.show_messages = !settings.show_messages;
+
+        let message = if settings.show_messages { MESSAGES_ON } else { MESSAGES_OFF };
+        self.actions.push(MenuAction::ForcedMessage(message));
+    }
+
+    // M_ChangeDetail
+    fn change_detail(&mut self, settings: &mut Settings) {
+        settings.low_detail = !settings.low_detail;
+
+        let message = if settings.low_detail { DETAIL_LOW } else { DETAIL_HIGH };
+        self.actions.push(MenuAction::Message(message));
+    }
+
+    // M_SizeDisplay
+    fn size_display(&mut self, argument: usize, settings: &mut Settings) {
+        step(&mut settings.screen_blocks, argument, MIN_SCREEN_BLOCKS, MAX_SCREEN_BLOCKS);
+    }
+
+    fn patch(&self, name: &str) -> Option<&Patch> {
+        self.patches.get(name)
+    }
+
+    fn draw_patch(&self, target: &mut dyn DrawTarget, x: i32, y: i32, name: &str) {
+        if let Some(patch) = self.patch(name) {
+            target.draw_patch(x, y, patch);
+        }
+    }
+
+    // M_Drawer, in 320x200 coordinates. A message hides the menu behind it.
+    pub fn draw(&self, target: &mut dyn DrawTarget, settings: &Settings) {
+        if let Some(message) = &self.message {
+            self.draw_message(target, &message.text);
+            return;
+        }
+
+        if !self.active {
+            return;
+        }
+
+        let menu = self.menu();
+        let line = |row: usize| menu.y + LINE_HEIGHT * row as i32;
+
+        match self.current {
+            MenuId::Main => self.draw_patch(target, 94, 2, "M_DOOM"),
+            MenuId::Episode => self.draw_patch(target, 54, 38, "M_EPISOD"),
+            MenuId::NewGame => {
+                self.draw_patch(target, 96, 14, "M_NEWG");
+                self.draw_patch(target, 54, 38, "M_SKILL");
+            },
+            MenuId::Options => {
+                self.draw_patch(target, 108, 15, "M_OPTTTL");
+
+                let detail = if settings.low_detail { "M_GDLOW" } else { "M_GDHIGH" };
+                self.draw_patch(target, menu.x + 175, line(OPTIONS_DETAIL), detail);
+
+                let messages = if settings.show_messages { "M_MSGON" } else { "M_MSGOFF" };
+                self.draw_patch(target, menu.x + 120, line(OPTIONS_MESSAGES), messages);
+
+                let sensitivity_y = line(OPTIONS_MOUSE_SENSITIVITY + 1);
+                self.draw_thermo(target, menu.x, sensitivity_y, MOUSE_SENSITIVITY_NOTCHES, settings.mouse_sensitivity);
+
+                let screen_size = settings.screen_blocks - MIN_SCREEN_BLOCKS;
+                self.draw_thermo(target, menu.x, line(OPTIONS_SCREEN_SIZE + 1), SCREEN_SIZE_NOTCHES, screen_size);
+            },
+            MenuId::Sound => {
+                self.draw_patch(target, 60, 38, "M_SVOL");
+                self.draw_thermo(target, menu.x, line(SOUND_SFX_VOLUME + 1), VOLUME_NOTCHES, settings.sfx_volume);
+                self.draw_thermo(target, menu.x, line(SOUND_MUSIC_VOLUME + 1), VOLUME_NOTCHES, settings.music_volume);
+            },
+            MenuId::ReadThis1 => self.draw_patch(target, 0, 0, if self.commercial { "HELP" } else { "HELP1" }),
+            MenuId::ReadThis2 => self.draw_patch(target, 0, 0, if self.patch("HELP2").is_some() { "HELP2" } else { "CREDIT" }),
+            MenuId::Load | MenuId::Save => {
+                self.draw_patch(target, 72, 28, if self.current == MenuId::Load { "M_LOADG" } else { "M_SAVEG" });
+
+                for (slot, text) in self.save_strings.iter().enumerate() {
+                    self.draw_save_load_border(target, menu.x, line(slot));
+
+                    if let Some(font) = &self.font {
+                        let x = font.draw_text(target, menu.x, line(slot), text);
+
+                        if self.save_entry.as_ref().is_some_and(|entry| entry.slot == slot) {
+                            font.draw_text(target, x, line(slot), "_");
+                        }
+                    }
+                }
+            }
+        }
+
+        for (row, item) in menu.items.iter().enumerate() {
+            self.draw_patch(target, menu.x, line(row), item.patch);
+        }
+
+        self.draw_patch(target, menu.x + SKULL_X_OFFSET, line(self.item_on) - 5, SKULL_NAMES[self.which_skull]);
+    }
+
+    // M_DrawThermo
+    fn draw_thermo(&self, target: &mut dyn DrawTarget, x: i32, y: i32, notches: i32, dot: i32) {
+        self.draw_patch(target, x, y, "M_THERML");
+
+        for notch in 0..notches {
+            self.draw_patch(target, x + 8 + notch * 8, y, "M_THERMM");
+        }
+
+        self.draw_patch(target, x + 8 + notches * 8, y, "M_THERMR");
+        self.draw_patch(target, x + 8 + dot * 8, y, "M_THERMO");
+    }
+
+    // M_DrawSaveLoadBorder
+    fn draw_save_load_border(&self, target: &mut dyn DrawTarget, x: i32, y: i32) {
+        self.draw_patch(target, x - 8, y + 7, "M_LSLEFT");
+
+        for i in 0..SAVE_STRING_SIZE as i32 {
+            self.draw_patch(target, x + i * 8, y + 7, "M_LSCNTR");
+        }
+
+        self.draw_patch(target, x + SAVE_STRING_SIZE as i32 * 8, y + 7, "M_LSRGHT");
+    }
+
+    // Each line centered, the whole message in the middle of the screen.
+    fn draw_message(&self, target: &mut dyn DrawTarget, text: &str) {
+        let Some(font) = &self.font else {
+            return;
+        };
+
+        let lines: Vec<&str> = text.split('\n').collect();
+        let mut y = 100 - font.height() * lines.len() as i32 / 2;
+
+        for line in lines {
+            font.draw_text(target, 160 - font.text_width(line) / 2, y, line);
+            y += font.height();
+        }
+    }
+}
+
+// A slider's routine: one notch down for 0, up for 1, within its range.
+fn step(value: &mut i32, argument: usize, min: i32, max: i32) {
+    *value = if argument == 0 { (*value - 1).max(min) } else { (*value + 1).min(max) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(game_tic: u64) -> MenuContext<'static> {
+        MenuContext {
+            playing: true,
+            netgame: false,
+            automap: false,
+            chatting: false,
+            game_tic,
+            save_dir: Path::new("/nonexistent")
+        }
+    }
+
+    // Presses each key in turn and says whether the menu took them all.
+    fn press(menu: &mut Menu, keys: &[Key]) -> bool {
+        let mut settings = Settings::default();
+        keys.iter().all(|&key| menu.responder(Event::KeyDown(key), &context(0), &mut settings))
+    }
+
+    fn options_menu() -> Menu {
+        let mut menu = Menu::new(false, [true; 4]);
+        assert!(press(&mut menu, &[Key::Escape, Key::Char('o'), Key::Enter]));
+        assert_eq!(menu.current, MenuId::Options);
+        menu
+    }
+
+    #[test]
+    fn up_and_down_skip_gaps() {
+        let mut menu = options_menu();
+
+        menu.item_on = OPTIONS_SCREEN_SIZE;
+        press(&mut menu, &[Key::Down]);
+        assert_eq!(menu.item_on, OPTIONS_MOUSE_SENSITIVITY);
+
+        press(&mut menu, &[Key::Up]);
+        assert_eq!(menu.item_on, OPTIONS_SCREEN_SIZE);
+
+        // The sound menu ends in a gap, so going down from the music volume wraps round.
+        menu.setup_next_menu(MenuId::Sound);
+        menu.item_on = SOUND_MUSIC_VOLUME;
+        press(&mut menu, &[Key::Down]);
+        assert_eq!(menu.item_on, SOUND_SFX_VOLUME);
+
+        press(&mut menu, &[Key::Up]);
+        assert_eq!(menu.item_on, SOUND_MUSIC_VOLUME);
+    }
+
+    #[test]
+    fn hotkeys_search_down_and_wrap_round() {
+        let mut menu = options_menu();
+
+        menu.item_on = OPTIONS_MOUSE_SENSITIVITY;
+        press(&mut menu, &[Key::Char('m')]);
+        assert_eq!(menu.item_on, OPTIONS_MESSAGES);
+
+        press(&mut menu, &[Key::Char('m')]);
+        assert_eq!(menu.item_on, OPTIONS_MOUSE_SENSITIVITY);
+
+        press(&mut menu, &[Key::Char('s'), Key::Char('s')]);
+        assert_eq!(menu.item_on, OPTIONS_SCREEN_SIZE);
+
+        assert!(!press(&mut menu, &[Key::Char('z')]));
+        assert_eq!(menu.item_on, OPTIONS_SCREEN_SIZE);
+    }
+
+    #[test]
+    fn backspace_goes_to_the_previous_menu() {
+        let mut menu = options_menu();
+
+        menu.item_on = OPTIONS_DETAIL;
+        press(&mut menu, &[Key::Backspace]);
+        assert_eq!((menu.current, menu.item_on), (MenuId::Main, 1));
+
+        // The options menu comes back where it was left.
+        press(&mut menu, &[Key::Enter]);
+        assert_eq!((menu.current, menu.item_on), (MenuId::Options, OPTIONS_DETAIL));
+
+        // The main menu has nowhere to go back to.
+        menu.setup_next_menu(MenuId::Main);
+        press(&mut menu, &[Key::Backspace]);
+        assert_eq!(menu.current, MenuId::Main);
+        assert!(menu.is_active());
+    }
+
+    #[test]
+    fn yes_runs_the_message_routine() {
+        let mut menu = Menu::new(false, [true; 4]);
+
+        assert!(press(&mut menu, &[Key::Function(10)]));
+        assert!(menu.is_active());
+
+        // Only y, n, space and Escape answer it.
+        assert!(!press(&mut menu, &[Key::Char('x')]));
+        assert!(menu.message.is_some());
+
+        assert!(press(&mut menu, &[Key::Char('y')]));
+        assert_eq!(menu.take_actions(), [MenuAction::Quit]);
+        assert!(menu.message.is_none() && !menu.is_active());
+    }
+
+    #[test]
+    fn no_leaves_the_message_without_its_routine() {
+        let mut menu = options_menu();
+
+        menu.item_on = 0;
+        assert!(press(&mut menu, &[Key::Enter]));
+        assert_eq!(menu.message.as_ref().map(|message| message.confirm), Some(Some(Confirm::EndGame)));
+
+        assert!(press(&mut menu, &[Key::Char('n')]));
+        assert!(menu.take_actions().is_empty());
+        assert!(!menu.is_active());
+    }
+
+    #[test]
+    fn quit_messages_change_every_four_tics() {
+        for (game_tic, message) in [(0, 0), (3, 0), (4, 1), (27, 6), (28, 0)] {
+            let mut menu = Menu::new(false, [true; 4]);
+            menu.quit_doom(&context(game_tic));
+
+            assert!(menu.message.unwrap().text.starts_with(QUIT_MESSAGES[message]));
+        }
+    }
+}
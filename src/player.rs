@@ -1,17 +1,308 @@
+use crate::engine::TIC_RATE;
+use crate::math::Bam;
+use crate::things::AmmoType;
 use crate::wad::Point;
 
+// G_PlayerReborn's starting health and ammo.
+const START_HEALTH: i32 = 100;
+const START_BULLETS: i32 = 50;
+
+// maxammo, in AmmoType order. A backpack doubles it.
+const MAX_AMMO: [i32; 4] = [200, 50, 50, 300];
+
+// How long each power lasts, in tics.
+const INVULNERABILITY_TICS: i32 = 30 * TIC_RATE as i32;
+const INVISIBILITY_TICS: i32 = 60 * TIC_RATE as i32;
+const INFRARED_TICS: i32 = 120 * TIC_RATE as i32;
+const IRON_FEET_TICS: i32 = 60 * TIC_RATE as i32;
+
+// Added to the bonus count by every pickup.
+const BONUS_ADD: i32 = 6;
+const MAX_DAMAGE_COUNT: i32 = 100;
+
+// Sub-palettes of PLAYPAL: 1-8 shade red, 9-12 gold, 13 green.
+const START_RED_PALETTES: usize = 1;
+const NUM_RED_PALETTES: usize = 8;
+const START_BONUS_PALETTES: usize = 9;
+const NUM_BONUS_PALETTES: usize = 4;
+const RADIATION_PALETTE: usize = 13;
+
+// The colormaps powers switch the view to: full bright and the invulnerability inverse.
+const INFRARED_COLORMAP: usize = 1;
+const INVERSE_COLORMAP: usize = 32;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Power {
+    Invulnerability,
+    Strength,
+    Invisibility,
+    IronFeet,
+    AllMap,
+    Infrared
+}
+
+impl Power {
+    pub const COUNT: usize = 6;
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Weapon {
+    Fist,
+    #[default]
+    Pistol,
+    Shotgun,
+    Chaingun,
+    RocketLauncher,
+    PlasmaRifle,
+    Bfg,
+    Chainsaw,
+    SuperShotgun
+}
+
+impl Weapon {
+    pub const COUNT: usize = 9;
+
+    pub const ALL: [Weapon; Weapon::COUNT] = [
+        Weapon::Fist,
+        Weapon::Pistol,
+        Weapon::Shotgun,
+        Weapon::Chaingun,
+        Weapon::RocketLauncher,
+        Weapon::PlasmaRifle,
+        Weapon::Bfg,
+        Weapon::Chainsaw,
+        Weapon::SuperShotgun
+    ];
+
+    // weaponinfo's ammo: melee weapons use none.
+    pub fn ammo(self) -> Option<AmmoType> {
+        match self {
+            Weapon::Fist | Weapon::Chainsaw => None,
+            Weapon::Pistol | Weapon::Chaingun => Some(AmmoType::Bullets),
+            Weapon::Shotgun | Weapon::SuperShotgun => Some(AmmoType::Shells),
+            Weapon::RocketLauncher => Some(AmmoType::Rockets),
+            Weapon::PlasmaRifle | Weapon::Bfg => Some(AmmoType::Cells)
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Card {
+    BlueCard,
+    YellowCard,
+    RedCard,
+    BlueSkull,
+    YellowSkull,
+    RedSkull
+}
+
+impl Card {
+    pub const COUNT: usize = 6;
+}
+
 #[derive(Debug, Default, Copy, Clone)]
 pub struct Player {
     pub id: usize,
     pub position: Point,
-    pub angle: i16
+    pub angle: Bam,
+    pub extra_light: i32,
+    pub fixed_colormap: Option<usize>,
+    pub damage_count: i32,
+    pub bonus_count: i32,
+    pub powers: [i32; Power::COUNT],
+    pub health: i32,
+    pub armor_points: i32,
+    pub armor_type: i32,
+    pub ready_weapon: Weapon,
+    pub weapons: [bool; Weapon::COUNT],
+    pub ammo: [i32; 4],
+    pub max_ammo: [i32; 4],
+    pub cards: [bool; Card::COUNT],
+    pub god_mode: bool,
+    pub attack_down: bool,
+    // Where the last hit came from, if it wasn't self-inflicted.
+    pub attacker: Option<Point>
 }
 
 impl Player {
+    // G_PlayerReborn: a pistol, a fist and 50 bullets.
     pub fn new(id: usize) -> Self {
-        Self {
+        let mut player = Self {
             id,
+            health: START_HEALTH,
+            max_ammo: MAX_AMMO,
             ..Default::default()
+        };
+
+        player.weapons[Weapon::Fist as usize] = true;
+        player.weapons[Weapon::Pistol as usize] = true;
+        player.ammo[AmmoType::Bullets as usize] = START_BULLETS;
+        player
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.health <= 0
+    }
+
+    pub fn has_weapon(&self, weapon: Weapon) -> bool {
+        self.weapons[weapon as usize]
+    }
+
+    pub fn has_card(&self, card: Card) -> bool {
+        self.cards[card as usize]
+    }
+
+    pub fn ammo(&self, ammo: AmmoType) -> i32 {
+        self.ammo[ammo as usize]
+    }
+
+    pub fn max_ammo(&self, ammo: AmmoType) -> i32 {
+        self.max_ammo[ammo as usize]
+    }
+
+    pub fn power(&self, power: Power) -> i32 {
+        self.powers[power as usize]
+    }
+
+    // P_GivePower: timed powers count down from their duration, berserk counts up from 1.
+    pub fn give_power(&mut self, power: Power) {
+        self.powers[power as usize] = match power {
+            Power::Invulnerability => INVULNERABILITY_TICS,
+            Power::Invisibility => INVISIBILITY_TICS,
+            Power::Infrared => INFRARED_TICS,
+            Power::IronFeet => IRON_FEET_TICS,
+            Power::Strength | Power::AllMap => 1
+        };
+    }
+
+    // The player part of P_DamageMobj: armor soaks up a third (green) or half (blue) of the
+    // damage until it runs out, god mode and invulnerability all but telefrags.
+    pub fn take_damage(&mut self, mut damage: i32, attacker: Option<Point>) {
+        if damage < 1000 && (self.god_mode || self.power(Power::Invulnerability) != 0) {
+            return;
+        }
+
+        if self.armor_type != 0 {
+            let mut saved = if self.armor_type == 1 { damage / 3 } else { damage / 2 };
+
+            if self.armor_points <= saved {
+                saved = self.armor_points;
+                self.armor_type = 0;
+            }
+
+            self.armor_points -= saved;
+            damage -= saved;
+        }
+
+        self.health = (self.health - damage).max(0);
+        self.attacker = attacker;
+        self.damage_count = (self.damage_count + damage).min(MAX_DAMAGE_COUNT);
+    }
+
+    // The flash part of P_TouchSpecialThing.
+    pub fn pick_up(&mut self) {
+        self.bonus_count += BONUS_ADD;
+    }
+
+    // The counters and colormaps of P_PlayerThink, once per tic.
+    pub fn tick(&mut self) {
+        if self.powers[Power::Strength as usize] != 0 {
+            self.powers[Power::Strength as usize] += 1;
+        }
+
+        for power in [Power::Invulnerability, Power::Invisibility, Power::Infrared, Power::IronFeet] {
+            let count = &mut self.powers[power as usize];
+            *count = (*count - 1).max(0);
+        }
+
+        self.damage_count = (self.damage_count - 1).max(0);
+        self.bonus_count = (self.bonus_count - 1).max(0);
+
+        // Both blink for the last four seconds before running out.
+        let blinking = |count: i32| count > 4 * 32 || count & 8 != 0;
+
+        self.fixed_colormap = if self.power(Power::Invulnerability) != 0 {
+            blinking(self.power(Power::Invulnerability)).then_some(INVERSE_COLORMAP)
+        } else if self.power(Power::Infrared) != 0 {
+            blinking(self.power(Power::Infrared)).then_some(INFRARED_COLORMAP)
+        } else {
+            None
+        };
+    }
+
+    // ST_doPaletteStuff: the PLAYPAL sub-palette the screen is tinted with this frame.
+    pub fn palette(&self) -> usize {
+        let mut count = self.damage_count;
+
+        // Berserk starts fully red and slowly fades out.
+        if self.power(Power::Strength) != 0 {
+            count = count.max(12 - (self.power(Power::Strength) >> 6));
+        }
+
+        if count > 0 {
+            START_RED_PALETTES + (((count + 7) >> 3) as usize).min(NUM_RED_PALETTES - 1)
+        } else if self.bonus_count > 0 {
+            START_BONUS_PALETTES + (((self.bonus_count + 7) >> 3) as usize).min(NUM_BONUS_PALETTES - 1)
+        } else if self.power(Power::IronFeet) > 4 * 32 || self.power(Power::IronFeet) & 8 != 0 {
+            RADIATION_PALETTE
+        } else {
+            0
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn palette(damage_count: i32, bonus_count: i32, strength: i32, iron_feet: i32) -> usize {
+        let mut player = Player::new(1);
+
+        player.damage_count = damage_count;
+        player.bonus_count = bonus_count;
+        player.powers[Power::Strength as usize] = strength;
+        player.powers[Power::IronFeet as usize] = iron_feet;
+        player.palette()
+    }
+
+    #[test]
+    fn damage_turns_the_screen_red() {
+        for (damage_count, expected) in [(0, 0), (1, 2), (8, 2), (9, 3), (50, 8), (100, 8)] {
+            assert_eq!(palette(damage_count, 0, 0, 0), expected, "damage count {}", damage_count);
+        }
+    }
+
+    #[test]
+    fn berserk_fades_out_unless_hurt_more() {
+        // (damage count, strength, palette)
+        for (damage_count, strength, expected) in [
+            (0, 1, 3),
+            (4, 64, 3),
+            (0, 6 * 64, 2),
+            (0, 12 * 64, 0),
+            (40, 1, 6)
+        ] {
+            assert_eq!(palette(damage_count, 0, strength, 0), expected, "damage {} strength {}", damage_count, strength);
+        }
+    }
+
+    #[test]
+    fn bonuses_flash_gold() {
+        for (bonus_count, expected) in [(1, 10), (6, 10), (9, 11), (24, 12), (100, 12)] {
+            assert_eq!(palette(0, bonus_count, 0, 0), expected, "bonus count {}", bonus_count);
+        }
+
+        // Red wins over gold.
+        assert_eq!(palette(1, 100, 0, 0), 2);
+    }
+
+    #[test]
+    fn radiation_suits_flicker_as_they_run_out() {
+        // (iron feet, palette)
+        for (iron_feet, expected) in [(60 * 35, 13), (129, 13), (128, 0), (127, 13), (119, 0), (120, 13), (8, 13), (7, 0)] {
+            assert_eq!(palette(0, 0, 0, iron_feet), expected, "iron feet {}", iron_feet);
+        }
+
+        assert_eq!(palette(0, 6, 0, 60 * 35), 10);
+    }
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use crate::backend::DrawTarget;
+use crate::wad::{Flat, Loader, Patch};
+
+// The bevel round a shrunken view: top, bottom, left, right, then the corners.
+const EDGE_NAMES: [&str; 8] = ["BRDR_T", "BRDR_B", "BRDR_L", "BRDR_R", "BRDR_TL", "BRDR_TR", "BRDR_BL", "BRDR_BR"];
+const EDGE_SIZE: i32 = 8;
+
+// R_FillBackScreen's backdrop for a view smaller than the screen.
+pub struct ViewBorder {
+    edges: Vec<Patch>
+}
+
+impl ViewBorder {
+    // Without the bevel graphics the border is just the flat.
+    pub fn load(loader: &mut Loader) -> Self {
+        let edges = EDGE_NAMES.iter().map(|name| loader.load_patch(name)).collect::<Result<_>>().unwrap_or_default();
+        Self { edges }
+    }
+
+    // Tiles the flat over everything above `bottom` and bevels the edge of the view window,
+    // all in screen pixels.
+    pub fn draw(&self, target: &mut dyn DrawTarget, flat: &Flat, (x, y, width, height): (i32, i32, i32, i32), bottom: i32) {
+        target.tile_flat(0, 0, target.width() - 1, bottom - 1, flat);
+
+        let edge = EDGE_SIZE * target.base_scale();
+        let [top, bottom, left, right, top_left, top_right, bottom_left, bottom_right] = &self.edges[..] else {
+            return;
+        };
+
+        for dx in (0..width).step_by(edge as usize) {
+            target.draw_scaled_patch(x + dx, y - edge, top);
+            target.draw_scaled_patch(x + dx, y + height, bottom);
+        }
+
+        for dy in (0..height).step_by(edge as usize) {
+            target.draw_scaled_patch(x - edge, y + dy, left);
+            target.draw_scaled_patch(x + width, y + dy, right);
+        }
+
+        target.draw_scaled_patch(x - edge, y - edge, top_left);
+        target.draw_scaled_patch(x + width, y - edge, top_right);
+        target.draw_scaled_patch(x - edge, y + height, bottom_left);
+        target.draw_scaled_patch(x + width, y + height, bottom_right);
+    }
+}
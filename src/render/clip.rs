@@ -0,0 +1,111 @@
+#[derive(Debug, Copy, Clone)]
+struct ClipRange {
+    first: i32,
+    last: i32
+}
+
+// The solidsegs list of vanilla: sorted, non-overlapping column ranges that are already
+// fully occluded, bracketed by two sentinels just outside the screen.
+#[derive(Debug)]
+pub struct ClipList {
+    ranges: Vec<ClipRange>,
+    width: i32
+}
+
+impl ClipList {
+    pub fn new(width: i32) -> Self {
+        let mut clip_list = Self {
+            ranges: Vec::with_capacity(32),
+            width
+        };
+
+        clip_list.clear();
+        clip_list
+    }
+
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+        self.ranges.push(ClipRange { first: i32::MIN + 1, last: -1 });
+        self.ranges.push(ClipRange { first: self.width, last: i32::MAX });
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.ranges.len() == 2 && self.ranges[0].last + 1 >= self.ranges[1].first
+    }
+
+    pub fn is_range_occluded(&self, first: i32, last: i32) -> bool {
+        let start = self.ranges.iter().position(|range| range.last >= last).unwrap();
+
+        first >= self.ranges[start].first && last <= self.ranges[start].last
+    }
+
+    fn find_start(&self, first: i32) -> usize {
+        self.ranges.iter().position(|range| range.last >= first - 1).unwrap()
+    }
+
+    // R_ClipSolidWallSegment: reports the visible parts of first..=last and marks them occluded.
+    pub fn clip_solid(&mut self, first: i32, last: i32, visible: &mut Vec<(i32, i32)>) {
+        let start = self.find_start(first);
+
+        if first < self.ranges[start].first {
+            if last < self.ranges[start].first - 1 {
+                visible.push((first, last));
+                self.ranges.insert(start, ClipRange { first, last });
+                return;
+            }
+
+            visible.push((first, self.ranges[start].first - 1));
+            self.ranges[start].first = first;
+        }
+
+        if last <= self.ranges[start].last {
+            return;
+        }
+
+        let mut next = start;
+
+        while last >= self.ranges[next + 1].first - 1 {
+            visible.push((self.ranges[next].last + 1, self.ranges[next + 1].first - 1));
+            next += 1;
+
+            if last <= self.ranges[next].last {
+                self.ranges[start].last = self.ranges[next].last;
+                self.ranges.drain(start + 1..=next);
+                return;
+            }
+        }
+
+        visible.push((self.ranges[next].last + 1, last));
+        self.ranges[start].last = last;
+        self.ranges.drain(start + 1..=next);
+    }
+
+    // R_ClipPassWallSegment: like clip_solid, but see-through walls don't occlude anything.
+    pub fn clip_pass(&self, first: i32, last: i32, visible: &mut Vec<(i32, i32)>) {
+        let mut start = self.find_start(first);
+
+        if first < self.ranges[start].first {
+            if last < self.ranges[start].first - 1 {
+                visible.push((first, last));
+                return;
+            }
+
+            visible.push((first, self.ranges[start].first - 1));
+        }
+
+        if last <= self.ranges[start].last {
+            return;
+        }
+
+        while last >= self.ranges[start + 1].first - 1 {
+            visible.push((self.ranges[start].last + 1, self.ranges[start + 1].first - 1));
+            start += 1;
+
+            if last <= self.ranges[start].last {
+                return;
+            }
+        }
+
+        visible.push((self.ranges[start].last + 1, last));
+    }
+}
\ No newline at end of file
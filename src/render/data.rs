@@ -0,0 +1,360 @@
+use std::collections::HashMap;
+use anyhow::Result;
+use crate::math::Fixed;
+use crate::wad::{Colormap, Flat, Loader, Palette, Patch, TextureDef};
+
+// A composited wall texture, stored column by column.
+pub struct Texture {
+    pub name: String,
+    pub width: i32,
+    pub height: i32,
+    width_mask: i32,
+    pixels: Vec<u8>,
+    posts: Vec<Vec<(i32, i32)>>
+}
+
+impl Texture {
+    // R_GenerateComposite
+    fn composite(def: &TextureDef, patches: &[Option<Patch>]) -> Self {
+        let (width, height) = (def.width.max(1) as i32, def.height.max(1) as i32);
+        let mut pixels = vec![0u8; (width * height) as usize];
+        let mut opaque = vec![false; (width * height) as usize];
+
+        for texture_patch in &def.patches {
+            let Some(Some(patch)) = patches.get(texture_patch.patch_id as usize) else {
+                continue;
+            };
+
+            let x1 = texture_patch.origin_x as i32;
+
+            for x in x1.max(0)..(x1 + patch.width as i32).min(width) {
+                for post in &patch.columns[(x - x1) as usize] {
+                    let mut position = texture_patch.origin_y as i32 + post.top_delta as i32;
+                    let mut source = &post.pixels[..];
+
+                    if position < 0 {
+                        source = &source[(-position as usize).min(source.len())..];
+                        position = 0;
+                    }
+
+                    // A post that starts below the texture adds nothing, and on the last
+                    // column would index past the end.
+                    if position >= height {
+                        continue;
+                    }
+
+                    let count = (source.len() as i32).min(height - position).max(0) as usize;
+                    let start = (x * height + position) as usize;
+
+                    pixels[start..start + count].copy_from_slice(&source[..count]);
+                    opaque[start..start + count].fill(true);
+                }
+            }
+        }
+
+        let width_mask = (width as u32).next_power_of_two() as i32 - 1;
+
+        // Runs of covered rows, so masked mid textures keep their holes.
+        let posts = opaque.chunks(height as usize)
+            .map(|column| {
+                let mut runs: Vec<(i32, i32)> = Vec::new();
+
+                for (y, &is_opaque) in column.iter().enumerate() {
+                    match runs.last_mut() {
+                        Some((top, length)) if is_opaque && *top + *length == y as i32 => *length += 1,
+                        _ if is_opaque => runs.push((y as i32, 1)),
+                        _ => {}
+                    }
+                }
+
+                runs
+            })
+            .collect();
+
+        Self {
+            name: def.name.to_uppercase(),
+            width,
+            height,
+            width_mask,
+            pixels,
+            posts
+        }
+    }
+
+    pub fn height_frac(&self) -> Fixed {
+        Fixed::from_int(self.height)
+    }
+
+    // R_GetColumn + R_DrawColumn's source lookup. Rows wrap at 128 regardless of height, so
+    // shorter non-power-of-two textures read on into the next column just like vanilla.
+    pub fn sample(&self, column: i32, frac: Fixed) -> u8 {
+        let column = (column & self.width_mask) as usize;
+        let row = ((frac.0 >> 16) & 127) as usize;
+
+        self.pixels[(column * self.height as usize + row) % self.pixels.len()]
+    }
+
+    pub fn posts(&self, column: i32) -> impl Iterator<Item = (i32, &[u8])> {
+        let column = (column & self.width_mask) as usize % self.posts.len();
+        let start = column * self.height as usize;
+
+        self.posts[column].iter().map(move |&(top, length)| {
+            (top, &self.pixels[start + top as usize..start + (top + length) as usize])
+        })
+    }
+}
+
+// One frame of a sprite: either a single patch for every angle or eight rotations,
+// some of which are mirrored copies of another.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpriteFrame {
+    pub rotate: bool,
+    pub patches: [usize; 8],
+    pub flip: [bool; 8]
+}
+
+#[derive(Default)]
+struct SpriteFrameBuilder {
+    rotate: bool,
+    rotations: [Option<(usize, bool)>; 8]
+}
+
+impl SpriteFrameBuilder {
+    // R_InstallSpriteLump
+    fn install(&mut self, patch: usize, rotation: u8, flipped: bool) {
+        if rotation == 0 {
+            self.rotate = false;
+            self.rotations = [Some((patch, flipped)); 8];
+        } else if let Some(slot) = self.rotations.get_mut(rotation as usize - 1) {
+            self.rotate = true;
+            *slot = Some((patch, flipped));
+        }
+    }
+
+    fn build(&self) -> Option<SpriteFrame> {
+        let mut frame = SpriteFrame { rotate: self.rotate, ..Default::default() };
+
+        for (i, rotation) in self.rotations.iter().enumerate() {
+            let (patch, flip) = (*rotation)?;
+            frame.patches[i] = patch;
+            frame.flip[i] = flip;
+        }
+
+        Some(frame)
+    }
+}
+
+#[derive(Default)]
+pub struct RenderData {
+    pub palettes: Vec<Palette>,
+    pub colormaps: Vec<Colormap>,
+    pub flats: Vec<Flat>,
+    pub textures: Vec<Texture>,
+    pub sprite_patches: Vec<Patch>,
+    flat_ids: HashMap<String, usize>,
+    texture_ids: HashMap<String, usize>,
+    sprites: HashMap<String, Vec<Option<SpriteFrame>>>
+}
+
+impl RenderData {
+    pub fn load(loader: &mut Loader) -> Result<Self> {
+        let palettes = loader.load_palettes()?;
+        let colormaps = loader.load_colormaps()?;
+        let flats = loader.load_flats()?;
+
+        let patches: Vec<Option<Patch>> = loader.load_patch_names()?
+            .iter()
+            .map(|name| loader.load_patch(name).ok())
+            .collect();
+
+        let textures: Vec<Texture> = loader.load_texture_defs()?
+            .iter()
+            .map(|def| Texture::composite(def, &patches))
+            .collect();
+
+        let (sprite_patches, sprites) = Self::load_sprites(loader);
+
+        // The first definition wins, as R_TextureNumForName searches from the start.
+        let mut texture_ids: HashMap<String, usize> = HashMap::new();
+
+        for (i, texture) in textures.iter().enumerate() {
+            texture_ids.entry(texture.name.clone()).or_insert(i);
+        }
+
+        let flat_ids = flats.iter()
+            .enumerate()
+            .map(|(i, flat)| (flat.name.to_uppercase(), i))
+            .collect();
+
+        Ok(
+            Self {
+                palettes,
+                colormaps,
+                flats,
+                textures,
+                sprite_patches,
+                flat_ids,
+                texture_ids,
+                sprites
+            }
+        )
+    }
+
+    // R_InitSpriteDefs: lumps are named SPRTFR or SPRTFRFR, frame letter then rotation digit,
+    // where the second pair reuses the patch mirrored.
+    fn load_sprites(loader: &mut Loader) -> (Vec<Patch>, HashMap<String, Vec<Option<SpriteFrame>>>) {
+        let mut patches: Vec<Patch> = Vec::new();
+        let mut builders: HashMap<String, Vec<SpriteFrameBuilder>> = HashMap::new();
+
+        for name in loader.sprite_names() {
+            let bytes = name.as_bytes();
+
+            if bytes.len() < 6 {
+                continue;
+            }
+
+            let Ok(patch) = loader.load_patch(&name) else {
+                continue;
+            };
+
+            patches.push(patch);
+
+            let patch_id = patches.len() - 1;
+            let frames = builders.entry(name[..4].to_string()).or_default();
+
+            for pair in bytes[4..].chunks_exact(2).take(2).enumerate() {
+                let (i, &[frame, rotation]) = pair else {
+                    continue;
+                };
+
+                let (Some(frame), Some(rotation)) = (frame.checked_sub(b'A'), rotation.checked_sub(b'0')) else {
+                    continue;
+                };
+
+                if frames.len() <= frame as usize {
+                    frames.resize_with(frame as usize + 1, Default::default);
+                }
+
+                frames[frame as usize].install(patch_id, rotation, i == 1);
+            }
+        }
+
+        let sprites = builders.into_iter()
+            .map(|(name, frames)| (name, frames.iter().map(SpriteFrameBuilder::build).collect()))
+            .collect();
+
+        (patches, sprites)
+    }
+
+    pub fn flat_id(&self, name: &str) -> Option<usize> {
+        self.flat_ids.get(&name.to_uppercase()).copied()
+    }
+
+    pub fn sprite_frame(&self, sprite: &str, frame: char) -> Option<&SpriteFrame> {
+        let frame = (frame as usize).checked_sub('A' as usize)?;
+
+        self.sprites.get(sprite)?.get(frame)?.as_ref()
+    }
+
+    pub fn texture_id(&self, name: &str) -> Option<usize> {
+        self.texture_ids.get(&name.to_uppercase()).copied()
+    }
+
+    // Falls back to the normal palette when a PLAYPAL is short of tints.
+    pub fn palette(&self, id: usize) -> &Palette {
+        const BLACK: Palette = [[0; 3]; 256];
+
+        self.palettes.get(id).or(self.palettes.first()).unwrap_or(&BLACK)
+    }
+
+    pub fn colormap(&self, id: usize) -> &Colormap {
+        const IDENTITY: Colormap = {
+            let mut colormap = [0; 256];
+            let mut i = 0;
+
+            while i < 256 {
+                colormap[i] = i as u8;
+                i += 1;
+            }
+
+            colormap
+        };
+
+        self.colormaps.get(id).unwrap_or(&IDENTITY)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::wad::{Post, TexturePatch};
+
+    // Darkening colormaps, a patterned WALL texture, a checked FLAT and a SARG sprite.
+    pub(crate) fn test_data() -> RenderData {
+        let colormaps = (0..34)
+            .map(|level| std::array::from_fn(|i| (i * (32 - level.min(32)) / 32) as u8))
+            .collect();
+
+        let flats = vec![Flat { name: "FLAT".to_string(), pixels: (0..64 * 64).map(|i| if (i / 8 + i / 512) % 2 == 0 { 100 } else { 140 }).collect() }];
+
+        let wall = Patch {
+            columns: (0..64).map(|x| vec![Post { top_delta: 0, pixels: (0..128).map(|y| ((x * 7 + y * 3) % 200 + 32) as u8).collect() }]).collect(),
+            ..patch(64, 128, 0, 0)
+        };
+        let textures = vec![Texture::composite(&def(64, 128, vec![TexturePatch { origin_x: 0, origin_y: 0, patch_id: 0 }]), &[Some(wall)])];
+
+        let sprite = Patch { left_offset: 8, top_offset: 32, ..patch(16, 32, 0, 200) };
+        let frame = SpriteFrame { rotate: false, patches: [0; 8], flip: [false; 8] };
+
+        RenderData {
+            colormaps,
+            flat_ids: HashMap::from([("FLAT".to_string(), 0)]),
+            texture_ids: HashMap::from([("TEST".to_string(), 0)]),
+            sprites: HashMap::from([("SARG".to_string(), vec![Some(frame)])]),
+            flats,
+            textures,
+            sprite_patches: vec![sprite],
+            ..Default::default()
+        }
+    }
+
+    fn patch(width: i16, height: i16, top_delta: u8, color: u8) -> Patch {
+        let post = Post { top_delta, pixels: vec![color; height as usize] };
+
+        Patch {
+            name: "TEST".to_string(),
+            width,
+            height,
+            left_offset: 0,
+            top_offset: 0,
+            columns: vec![vec![post]; width as usize]
+        }
+    }
+
+    fn def(width: i16, height: i16, patches: Vec<TexturePatch>) -> TextureDef {
+        TextureDef { name: "test".to_string(), width, height, patches }
+    }
+
+    #[test]
+    fn composite_copies_patch_columns() {
+        let texture = Texture::composite(
+            &def(2, 4, vec![TexturePatch { origin_x: 0, origin_y: 1, patch_id: 0 }]),
+            &[Some(patch(2, 2, 0, 7))]
+        );
+
+        assert_eq!(texture.name, "TEST");
+        assert_eq!(texture.pixels, [0, 7, 7, 0, 0, 7, 7, 0]);
+        assert_eq!(texture.posts, [vec![(1, 2)], vec![(1, 2)]]);
+    }
+
+    #[test]
+    fn composite_skips_posts_below_the_texture() {
+        let texture = Texture::composite(
+            &def(2, 4, vec![TexturePatch { origin_x: 0, origin_y: 2, patch_id: 0 }]),
+            &[Some(patch(2, 2, 5, 7))]
+        );
+
+        assert_eq!(texture.pixels, [0; 8]);
+        assert!(texture.posts.iter().all(|runs| runs.is_empty()));
+    }
+}
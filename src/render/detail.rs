@@ -0,0 +1,46 @@
+use crate::backend::DrawTarget;
+
+// Low detail, as R_DrawColumnLow and R_DrawSpanLow do it: the view is drawn at half width
+// and every pixel lands on two columns of the frame.
+pub(super) struct LowDetailTarget<'a> {
+    frame: &'a mut dyn DrawTarget
+}
+
+impl<'a> LowDetailTarget<'a> {
+    pub fn new(frame: &'a mut dyn DrawTarget) -> Self {
+        Self { frame }
+    }
+}
+
+impl DrawTarget for LowDetailTarget<'_> {
+    fn width(&self) -> i32 {
+        self.frame.width() >> 1
+    }
+
+    fn height(&self) -> i32 {
+        self.frame.height()
+    }
+
+    fn pixel(&self, x: i32, y: i32) -> Option<u8> {
+        self.frame.pixel(x << 1, y)
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, color: u8) {
+        self.frame.set_pixel(x << 1, y, color);
+        self.frame.set_pixel((x << 1) + 1, y, color);
+    }
+
+    fn read_row(&self, x: i32, y: i32, row: &mut [u8]) {
+        let mut wide = vec![0; row.len() * 2];
+        self.frame.read_row(x << 1, y, &mut wide);
+
+        for (pixel, pair) in row.iter_mut().zip(wide.chunks_exact(2)) {
+            *pixel = pair[0];
+        }
+    }
+
+    fn write_row(&mut self, x: i32, y: i32, row: &[u8]) {
+        let wide: Vec<u8> = row.iter().flat_map(|&pixel| [pixel, pixel]).collect();
+        self.frame.write_row(x << 1, y, &wide);
+    }
+}
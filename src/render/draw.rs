@@ -0,0 +1,127 @@
+use crate::math::{Fixed, FRACBITS, FRACUNIT};
+use crate::backend::DrawTarget;
+use crate::render::{Renderer, Texture};
+
+// Rows above or below each fuzz pixel is copied from.
+const FUZZ_OFFSETS: [i32; 50] = [
+    1, -1, 1, -1, 1, 1, -1, 1, 1, -1, 1, 1, 1, -1, 1, 1, 1, -1, -1, -1, -1, 1, -1, -1, 1,
+    1, 1, 1, -1, 1, -1, 1, 1, -1, -1, 1, 1, -1, -1, -1, -1, 1, 1, 1, 1, -1, 1, 1, -1, 1
+];
+
+// The colormap fuzz darkens what is behind it with.
+const FUZZ_COLORMAP: usize = 6;
+
+impl Renderer {
+    fn in_strip(&self, x: i32) -> bool {
+        x >= self.strip.0 && x <= self.strip.1
+    }
+
+    fn draw_pixel(&self, frame: &mut dyn DrawTarget, x: i32, y: i32, colormap: usize, index: u8) {
+        frame.set_pixel(x, y, self.data.colormap(colormap)[index as usize]);
+    }
+
+    // R_DrawFuzzColumn: the spectre effect, a darkened copy of neighbouring screen pixels.
+    fn draw_fuzz_column(&self, frame: &mut dyn DrawTarget, x: i32, y_low: i32, y_high: i32) {
+        let y_low = y_low.max(1);
+        let y_high = y_high.min(self.height - 2);
+        let mut fuzz_pos = self.fuzz_pos.get();
+
+        // Another thread draws this column, but the fuzz sequence still moves on past it.
+        if !self.in_strip(x) {
+            let count = (y_high - y_low + 1).max(0) as usize;
+            self.fuzz_pos.set((fuzz_pos + count) % FUZZ_OFFSETS.len());
+            return;
+        }
+
+        for y in y_low..=y_high {
+            let source = frame.pixel(x, y + FUZZ_OFFSETS[fuzz_pos]).unwrap_or(0);
+            frame.set_pixel(x, y, self.data.colormap(FUZZ_COLORMAP)[source as usize]);
+
+            fuzz_pos = (fuzz_pos + 1) % FUZZ_OFFSETS.len();
+        }
+
+        self.fuzz_pos.set(fuzz_pos);
+    }
+
+    // R_DrawColumn
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn draw_column(
+        &self, frame: &mut dyn DrawTarget, texture: &Texture, column: i32, colormap: usize,
+        x: i32, y_low: i32, y_high: i32, texture_mid: Fixed, inv_scale: Fixed
+    ) {
+        if !self.in_strip(x) {
+            return;
+        }
+
+        let mut frac = texture_mid + Fixed((y_low - self.center_y).wrapping_mul(inv_scale.0));
+
+        for y in y_low..=y_high {
+            self.draw_pixel(frame, x, y, colormap, texture.sample(column, frac));
+            frac += inv_scale;
+        }
+    }
+
+    // R_DrawMaskedColumn: draws each post of a column, leaving the gaps between them untouched.
+    // Without a colormap the posts are drawn as fuzz.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn draw_masked_column<'a>(
+        &self, frame: &mut dyn DrawTarget, posts: impl Iterator<Item = (i32, &'a [u8])>, colormap: Option<usize>,
+        x: i32, texture_mid: Fixed, scale: Fixed, inv_scale: Fixed, ceiling_clip: i32, floor_clip: i32
+    ) {
+        if colormap.is_some() && !self.in_strip(x) {
+            return;
+        }
+
+        let sprite_top_screen = self.center_y_frac - texture_mid * scale;
+
+        for (top, pixels) in posts {
+            if pixels.is_empty() {
+                continue;
+            }
+
+            let top_screen = sprite_top_screen + Fixed(scale.0.wrapping_mul(top));
+            let bottom_screen = top_screen + Fixed(scale.0.wrapping_mul(pixels.len() as i32));
+
+            let y_low = ((top_screen.0 + FRACUNIT - 1) >> FRACBITS).max(ceiling_clip + 1);
+            let y_high = ((bottom_screen.0 - 1) >> FRACBITS).min(floor_clip - 1);
+
+            let Some(colormap) = colormap else {
+                self.draw_fuzz_column(frame, x, y_low, y_high);
+                continue;
+            };
+
+            let post_mid = texture_mid - Fixed::from_int(top);
+            let mut frac = post_mid + Fixed((y_low - self.center_y).wrapping_mul(inv_scale.0));
+
+            for y in y_low..=y_high {
+                let row = (((frac.0 >> FRACBITS) & 127) as usize).min(pixels.len() - 1);
+
+                self.draw_pixel(frame, x, y, colormap, pixels[row]);
+                frac += inv_scale;
+            }
+        }
+    }
+
+    // R_DrawSpan
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn draw_span(
+        &self, frame: &mut dyn DrawTarget, flat: usize, colormap: usize, y: i32, x1: i32, x2: i32,
+        mut x_frac: Fixed, mut y_frac: Fixed, x_step: Fixed, y_step: Fixed
+    ) {
+        let source = &self.data.flats[flat].pixels;
+
+        // Start where the whole span would have stepped to, so strips line up exactly.
+        let skip = (self.strip.0 - x1).max(0);
+        let (x1, x2) = (x1 + skip, x2.min(self.strip.1));
+        x_frac = Fixed(x_frac.0.wrapping_add(x_step.0.wrapping_mul(skip)));
+        y_frac = Fixed(y_frac.0.wrapping_add(y_step.0.wrapping_mul(skip)));
+
+        for x in x1..=x2 {
+            let spot = ((y_frac.0 >> (16 - 6)) & (63 * 64)) + ((x_frac.0 >> 16) & 63);
+            self.draw_pixel(frame, x, y, colormap, source[spot as usize]);
+
+            x_frac += x_step;
+            y_frac += y_step;
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use crate::map::GameMap;
+use crate::math::Fixed;
+use crate::render::Renderer;
+use crate::wad::Point;
+use crate::Player;
+
+// The world as it stood after the previous tic. Frames drawn between two tics place the view,
+// things and moving floors and ceilings part way from here to the current state.
+#[derive(Debug, Default, Clone)]
+pub struct TicSnapshot {
+    player: Player,
+    things: Vec<Point>,
+    sectors: Vec<(i16, i16)>
+}
+
+impl TicSnapshot {
+    pub fn capture(map: &GameMap) -> Self {
+        Self {
+            player: map.player,
+            things: map.things.iter().map(|thing| thing.position).collect(),
+            sectors: map.sectors.iter().map(|sector| (sector.floor_height, sector.ceiling_height)).collect()
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub(super) struct SectorPlanes {
+    pub floor: Fixed,
+    pub ceiling: Fixed
+}
+
+fn lerp_point(from: Point, to: Point, frac: Fixed) -> (Fixed, Fixed) {
+    (Fixed::from(from.x).lerp(Fixed::from(to.x), frac), Fixed::from(from.y).lerp(Fixed::from(to.y), frac))
+}
+
+impl Renderer {
+    // Only what is drawn is interpolated, the map itself stays at the current tic. Anything the
+    // snapshot doesn't know about, like a thing spawned this tic, is drawn where it is now.
+    pub(super) fn interpolate(&mut self, map: &GameMap, previous: &TicSnapshot, frac: Fixed) {
+        self.sector_planes = map.sectors.iter()
+            .enumerate()
+            .map(|(i, sector)| {
+                let (floor, ceiling) = previous.sectors.get(i).copied()
+                    .unwrap_or((sector.floor_height, sector.ceiling_height));
+
+                SectorPlanes {
+                    floor: Fixed::from(floor).lerp(Fixed::from(sector.floor_height), frac),
+                    ceiling: Fixed::from(ceiling).lerp(Fixed::from(sector.ceiling_height), frac)
+                }
+            })
+            .collect();
+
+        self.thing_positions = map.things.iter()
+            .enumerate()
+            .map(|(i, thing)| lerp_point(previous.things.get(i).copied().unwrap_or(thing.position), thing.position, frac))
+            .collect();
+
+        (self.view.x, self.view.y) = lerp_point(previous.player.position, map.player.position, frac);
+        self.view.angle = previous.player.angle.lerp(map.player.angle, frac);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::math::Fixed;
+use crate::render::Renderer;
+
+pub(super) const LIGHT_LEVELS: usize = 16;
+pub(super) const LIGHT_SEG_SHIFT: i32 = 4;
+pub(super) const MAX_LIGHT_SCALE: usize = 48;
+pub(super) const LIGHT_SCALE_SHIFT: i32 = 12;
+pub(super) const MAX_LIGHT_Z: usize = 128;
+pub(super) const LIGHT_Z_SHIFT: i32 = 20;
+
+const NUM_COLORMAPS: i32 = 32;
+const DISTMAP: i32 = 2;
+
+// The light tables were tuned for a 320 wide screen.
+const BASE_WIDTH: i32 = 320;
+
+fn start_map(light: usize) -> i32 {
+    ((LIGHT_LEVELS - 1 - light) as i32 * 2) * NUM_COLORMAPS / LIGHT_LEVELS as i32
+}
+
+impl Renderer {
+    // R_InitLightTables and the scalelight part of R_ExecuteSetViewSize.
+    pub(super) fn init_lights(&mut self) {
+        self.z_light = (0..LIGHT_LEVELS)
+            .map(|light| {
+                std::array::from_fn(|z| {
+                    let scale = Fixed::from_int(BASE_WIDTH / 2) / Fixed(((z + 1) as i32) << LIGHT_Z_SHIFT);
+                    let level = start_map(light) - (scale.0 >> LIGHT_SCALE_SHIFT) / DISTMAP;
+
+                    level.clamp(0, NUM_COLORMAPS - 1) as usize
+                })
+            })
+            .collect();
+
+        // Vanilla's j*SCREENWIDTH/viewwidth for views up to 320 wide. Wider views get a 320 wide
+        // table and have their scale brought down to it instead, see scale_colormap.
+        let width = self.base_width.clamp(1, BASE_WIDTH);
+
+        self.scale_light = (0..LIGHT_LEVELS)
+            .map(|light| {
+                std::array::from_fn(|scale| {
+                    let level = start_map(light) - scale as i32 * BASE_WIDTH / width / DISTMAP;
+
+                    level.clamp(0, NUM_COLORMAPS - 1) as usize
+                })
+            })
+            .collect();
+    }
+
+    // Sector light plus extralight, with the wall fake contrast passed in as `contrast`.
+    pub(super) fn light_row(&self, light_level: i16, contrast: i32) -> usize {
+        ((light_level as i32 >> LIGHT_SEG_SHIFT) + self.extra_light + contrast)
+            .clamp(0, LIGHT_LEVELS as i32 - 1) as usize
+    }
+
+    // The scale is clamped like vanilla's, then brought back to a 320 wide view if the view
+    // is wider, so it doesn't run out of brighter colormaps sooner.
+    pub(super) fn scale_colormap(&self, light_row: usize, scale: Fixed) -> usize {
+        self.fixed_colormap.unwrap_or_else(|| {
+            let mut index = (scale.0 >> LIGHT_SCALE_SHIFT).clamp(0, MAX_LIGHT_SCALE as i32 - 1);
+
+            if self.base_width > BASE_WIDTH {
+                index = index * BASE_WIDTH / self.base_width;
+            }
+
+            self.scale_light[light_row][index as usize]
+        })
+    }
+
+    pub(super) fn z_colormap(&self, light_row: usize, distance: Fixed) -> usize {
+        self.fixed_colormap.unwrap_or_else(|| {
+            self.z_light[light_row][((distance.0 >> LIGHT_Z_SHIFT).max(0) as usize).min(MAX_LIGHT_Z - 1)]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::data::tests::test_data;
+
+    // A light row that goes from dark far away to full bright close up.
+    const LIGHT_ROW: usize = 7;
+
+    // R_ExecuteSetViewSize's scalelight entry for LIGHT_ROW and scale index j.
+    fn vanilla_level(j: i32, view_width: i32) -> usize {
+        (start_map(LIGHT_ROW) - j * BASE_WIDTH / view_width / DISTMAP).clamp(0, NUM_COLORMAPS - 1) as usize
+    }
+
+    #[test]
+    fn reduced_views_use_vanilla_levels() {
+        // Screen size 8: a 256 wide view of the 320x200 screen.
+        let mut renderer = Renderer::new(320, 200, test_data());
+        renderer.set_view_size(256, 128, false);
+
+        for j in [0, 10, 20, 47] {
+            assert_eq!(renderer.scale_colormap(LIGHT_ROW, Fixed(j << LIGHT_SCALE_SHIFT)), vanilla_level(j, 256));
+        }
+
+        assert_eq!(renderer.scale_colormap(LIGHT_ROW, Fixed(200 << LIGHT_SCALE_SHIFT)), 3);
+    }
+
+    #[test]
+    fn wide_views_are_brought_back_to_320() {
+        let renderer = Renderer::new(640, 400, test_data());
+
+        for j in [0, 10, 20, 47, 200] {
+            assert_eq!(renderer.scale_colormap(LIGHT_ROW, Fixed(j << LIGHT_SCALE_SHIFT)), vanilla_level(j.min(47) / 2, 320));
+        }
+    }
+}
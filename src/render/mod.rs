@@ -0,0 +1,556 @@
+use std::cell::Cell;
+use std::sync::Arc;
+use crate::map::{GameMap, SUB_SECTOR_IDENTIFIER};
+use crate::math::{point_to_angle2, Bam, Fixed, ANG180, ANG90, ANGLETOFINESHIFT, FINEANGLES, FINETANGENT, FRACBITS, FRACUNIT};
+use crate::wad::{BoundingBox, Sidedef};
+use crate::backend::DrawTarget;
+
+mod border;
+pub use border::ViewBorder;
+
+mod clip;
+use clip::ClipList;
+
+mod data;
+pub use data::{RenderData, Texture};
+
+mod detail;
+use detail::LowDetailTarget;
+
+mod draw;
+
+mod interpolation;
+pub use interpolation::TicSnapshot;
+use interpolation::SectorPlanes;
+
+mod lights;
+use lights::{MAX_LIGHT_SCALE, MAX_LIGHT_Z};
+
+mod planes;
+use planes::Visplane;
+
+mod segs;
+use segs::{DrawSeg, WallSeg};
+
+mod sky;
+pub use sky::sky_texture_name;
+
+mod threads;
+
+mod sprites;
+use sprites::VisSprite;
+
+const FIELD_OF_VIEW: usize = 2048;
+const VIEW_HEIGHT: Fixed = Fixed::from_int(41);
+const SKY_FLAT: &str = "F_SKY1";
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RenderStats {
+    pub nodes_total: usize,
+    pub nodes_visited: usize,
+    pub bboxes_checked: usize,
+    pub bboxes_culled: usize,
+    pub sub_sectors_total: usize,
+    pub sub_sectors_visited: usize,
+    pub visplanes: usize,
+    pub draw_segs: usize,
+    pub vis_sprites: usize
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct View {
+    pub x: Fixed,
+    pub y: Fixed,
+    pub z: Fixed,
+    pub angle: Bam
+}
+
+pub struct Renderer {
+    screen: (i32, i32),
+    view_size: (i32, i32),
+    detail_shift: u32,
+    width: i32,
+    height: i32,
+    base_width: i32,
+    center_x: i32,
+    center_y: i32,
+    center_x_frac: Fixed,
+    center_y_frac: Fixed,
+    projection: Fixed,
+    view_angle_to_x: Vec<i32>,
+    x_to_view_angle: Vec<Bam>,
+    clip_angle: Bam,
+    view: View,
+    solid_segs: ClipList,
+    visible_ranges: Vec<(i32, i32)>,
+    ceiling_clip: Vec<i32>,
+    floor_clip: Vec<i32>,
+    planes: Vec<Visplane>,
+    floor_plane: Option<usize>,
+    ceiling_plane: Option<usize>,
+    span_start: Vec<i32>,
+    y_slope: Vec<Fixed>,
+    dist_scale: Vec<Fixed>,
+    base_x_scale: Fixed,
+    base_y_scale: Fixed,
+    scale_light: Vec<[usize; MAX_LIGHT_SCALE]>,
+    z_light: Vec<[usize; MAX_LIGHT_Z]>,
+    extra_light: i32,
+    fixed_colormap: Option<usize>,
+    draw_segs: Vec<DrawSeg>,
+    vis_sprites: Vec<VisSprite>,
+    sector_planes: Vec<SectorPlanes>,
+    thing_positions: Vec<(Fixed, Fixed)>,
+    sector_things: Vec<Vec<usize>>,
+    sector_visited: Vec<bool>,
+    fuzz_pos: Cell<usize>,
+    sky_flat: Option<usize>,
+    sky_texture: Option<usize>,
+    strip: (i32, i32),
+    workers: Vec<Renderer>,
+    data: Arc<RenderData>,
+    stats: RenderStats
+}
+
+impl Renderer {
+    // A renderer for a view filling the whole `width` by `height` screen.
+    pub fn new(width: u32, height: u32, data: RenderData) -> Self {
+        Self::with_data((width as i32, height as i32), width, height, 0, Arc::new(data))
+    }
+
+    fn with_data((screen_width, screen_height): (i32, i32), width: u32, height: u32, detail_shift: u32, data: Arc<RenderData>) -> Self {
+        let view_size = (width as i32, height as i32);
+
+        // The width a vanilla-shaped 8:5 screen of this height would have. The 90 degree field
+        // of view spans it, so wider screens see further to the sides instead of stretching.
+        // A view window narrower than the screen gets its share of it.
+        let base_width = (screen_height * 8 / 5).min(screen_width) * view_size.0 / screen_width.max(1);
+
+        // Low detail draws every column twice, so there are half as many of them.
+        let (width, height) = (view_size.0 >> detail_shift, view_size.1);
+
+        let mut renderer = Self {
+            screen: (screen_width, screen_height),
+            view_size,
+            detail_shift,
+            width,
+            height,
+            base_width,
+            center_x: width / 2,
+            center_y: height / 2,
+            center_x_frac: Fixed::from_int(width / 2),
+            center_y_frac: Fixed::from_int(height / 2),
+            projection: Fixed::from_int((base_width >> detail_shift) / 2),
+            view_angle_to_x: vec![0; FINEANGLES / 2],
+            x_to_view_angle: vec![Bam(0); width as usize + 1],
+            clip_angle: Bam(0),
+            view: View::default(),
+            solid_segs: ClipList::new(width),
+            visible_ranges: Vec::new(),
+            ceiling_clip: vec![-1; width as usize],
+            floor_clip: vec![height; width as usize],
+            planes: Vec::new(),
+            floor_plane: None,
+            ceiling_plane: None,
+            span_start: Vec::new(),
+            y_slope: Vec::new(),
+            dist_scale: Vec::new(),
+            base_x_scale: Fixed::ZERO,
+            base_y_scale: Fixed::ZERO,
+            scale_light: Vec::new(),
+            z_light: Vec::new(),
+            extra_light: 0,
+            fixed_colormap: None,
+            draw_segs: Vec::new(),
+            vis_sprites: Vec::new(),
+            sector_planes: Vec::new(),
+            thing_positions: Vec::new(),
+            sector_things: Vec::new(),
+            sector_visited: Vec::new(),
+            fuzz_pos: Cell::new(0),
+            sky_flat: data.flat_id(SKY_FLAT),
+            sky_texture: data.texture_id("SKY1"),
+            strip: (0, width - 1),
+            workers: Vec::new(),
+            data,
+            stats: RenderStats::default()
+        };
+
+        renderer.init_texture_mapping();
+        renderer.init_planes();
+        renderer.init_lights();
+        renderer
+    }
+
+    // R_ExecuteSetViewSize: the view becomes a `width` by `height` window of the screen,
+    // as it does when the status bar takes the bottom of it, drawn in double wide pixels for
+    // low detail.
+    pub fn set_view_size(&mut self, width: u32, height: u32, low_detail: bool) {
+        let detail_shift = low_detail as u32;
+
+        if ((width as i32, height as i32), detail_shift) == (self.view_size, self.detail_shift) {
+            return;
+        }
+
+        let threads = self.threads();
+        let sky_texture = self.sky_texture;
+
+        *self = Self::with_data(self.screen, width, height, detail_shift, Arc::clone(&self.data));
+        self.sky_texture = sky_texture;
+        self.set_threads(threads);
+    }
+
+    pub fn view_size(&self) -> (i32, i32) {
+        self.view_size
+    }
+
+    // R_InitTextureMapping
+    fn init_texture_mapping(&mut self) {
+        let focal_length = self.projection / Fixed(FINETANGENT[FINEANGLES / 4 + FIELD_OF_VIEW / 2]);
+
+        for (i, &tangent) in FINETANGENT[..FINEANGLES / 2].iter().enumerate() {
+            self.view_angle_to_x[i] = if tangent > FRACUNIT * 2 {
+                -1
+            } else if tangent < -FRACUNIT * 2 {
+                self.width + 1
+            } else {
+                let t = Fixed(tangent) * focal_length;
+                (((self.center_x_frac - t).0 + FRACUNIT - 1) >> FRACBITS).clamp(-1, self.width + 1)
+            };
+        }
+
+        for x in 0..=self.width {
+            let i = self.view_angle_to_x.iter().position(|&view_x| view_x <= x).unwrap_or(FINEANGLES / 2);
+            self.x_to_view_angle[x as usize] = Bam((i as u32) << ANGLETOFINESHIFT) - ANG90;
+        }
+
+        for view_x in self.view_angle_to_x.iter_mut() {
+            if *view_x == -1 {
+                *view_x = 0;
+            } else if *view_x == self.width + 1 {
+                *view_x = self.width;
+            }
+        }
+
+        self.clip_angle = self.x_to_view_angle[0];
+    }
+
+    fn setup_frame(&mut self, map: &GameMap, previous: &TicSnapshot, frac: Fixed) {
+        self.interpolate(map, previous, frac);
+
+        let floor = map.sub_sector_sector_id(map.point_in_sub_sector(self.view.x, self.view.y))
+            .map_or(Fixed::ZERO, |sector_id| self.sector_planes[sector_id].floor);
+
+        self.view.z = floor + VIEW_HEIGHT;
+        self.extra_light = map.player.extra_light;
+        self.fixed_colormap = map.player.fixed_colormap;
+        self.solid_segs.clear();
+        self.ceiling_clip.fill(-1);
+        self.floor_clip.fill(self.height);
+        self.clear_planes();
+        self.draw_segs.clear();
+        self.vis_sprites.clear();
+        self.link_things(map);
+
+        self.stats = RenderStats {
+            nodes_total: map.nodes.len(),
+            sub_sectors_total: map.ssectors.len(),
+            ..Default::default()
+        };
+    }
+
+    pub fn stats(&self) -> RenderStats {
+        self.stats
+    }
+
+    pub fn data(&self) -> &RenderData {
+        &self.data
+    }
+
+    // Draws the map's player view `frac` of a tic on from `previous`.
+    pub fn render_player_view(&mut self, map: &GameMap, previous: &TicSnapshot, frac: Fixed, frame: &mut dyn DrawTarget) {
+        if self.detail_shift > 0 {
+            let mut frame = LowDetailTarget::new(frame);
+            self.render_view(map, previous, frac, &mut frame);
+        } else {
+            self.render_view(map, previous, frac, frame);
+        }
+    }
+
+    fn render_view(&mut self, map: &GameMap, previous: &TicSnapshot, frac: Fixed, frame: &mut dyn DrawTarget) {
+        if self.workers.is_empty() {
+            self.render_strip(map, previous, frac, frame);
+        } else {
+            self.render_parallel(map, previous, frac, frame);
+        }
+    }
+
+    fn render_strip(&mut self, map: &GameMap, previous: &TicSnapshot, frac: Fixed, frame: &mut dyn DrawTarget) {
+        self.setup_frame(map, previous, frac);
+
+        if let Some(root_node_id) = map.nodes.len().checked_sub(1) {
+            self.render_bsp_node(map, frame, root_node_id as u16);
+        } else if !map.ssectors.is_empty() {
+            self.stats.sub_sectors_visited += 1;
+            self.render_sub_sector(map, frame, 0);
+        }
+
+        self.draw_planes(frame);
+
+        self.stats.draw_segs = self.draw_segs.len();
+        self.stats.vis_sprites = self.vis_sprites.len();
+        self.draw_masked(frame);
+    }
+
+    fn render_bsp_node(&mut self, map: &GameMap, frame: &mut dyn DrawTarget, node_id: u16) {
+        if self.solid_segs.is_full() {
+            return;
+        }
+
+        if node_id & SUB_SECTOR_IDENTIFIER != 0 {
+            self.stats.sub_sectors_visited += 1;
+            self.render_sub_sector(map, frame, (node_id & !SUB_SECTOR_IDENTIFIER) as usize);
+            return;
+        }
+
+        let Some(node) = map.nodes.get(node_id as usize) else {
+            return;
+        };
+
+        self.stats.nodes_visited += 1;
+
+        let side = map.point_on_side(self.view.x, self.view.y, node);
+        let children = [node.right_child_id, node.left_child_id];
+        let bboxes = [node.bbox_right, node.bbox_left];
+
+        self.render_bsp_node(map, frame, children[side]);
+
+        self.stats.bboxes_checked += 1;
+
+        if self.check_bbox(&bboxes[side ^ 1]) {
+            self.render_bsp_node(map, frame, children[side ^ 1]);
+        } else {
+            self.stats.bboxes_culled += 1;
+        }
+    }
+
+    // R_CheckBBox: whether any part of the box is inside the view and not yet occluded.
+    fn check_bbox(&self, bbox: &BoundingBox) -> bool {
+        let (top, bottom) = (Fixed::from(bbox.top), Fixed::from(bbox.bottom));
+        let (left, right) = (Fixed::from(bbox.left), Fixed::from(bbox.right));
+
+        let box_x = if self.view.x <= left { 0 } else if self.view.x < right { 1 } else { 2 };
+        let box_y = if self.view.y >= top { 0 } else if self.view.y > bottom { 1 } else { 2 };
+
+        // The corners spanning the widest view angle for each of the 3x3 areas around the box.
+        let (x1, y1, x2, y2) = match (box_y << 2) + box_x {
+            0 => (right, top, left, bottom),
+            1 => (right, top, left, top),
+            2 => (right, bottom, left, top),
+            4 => (left, top, left, bottom),
+            5 => return true,
+            6 => (right, bottom, right, top),
+            8 => (left, top, right, bottom),
+            9 => (left, bottom, right, bottom),
+            10 => (left, bottom, right, top),
+            _ => return true
+        };
+
+        let mut angle1 = point_to_angle2(self.view.x, self.view.y, x1, y1) - self.view.angle;
+        let mut angle2 = point_to_angle2(self.view.x, self.view.y, x2, y2) - self.view.angle;
+
+        let span = angle1 - angle2;
+
+        if span >= ANG180 {
+            return true;
+        }
+
+        let clip_angle = self.clip_angle;
+        let double_clip_angle = clip_angle + clip_angle;
+
+        let mut t_span = angle1 + clip_angle;
+
+        if t_span > double_clip_angle {
+            t_span -= double_clip_angle;
+
+            if t_span >= span {
+                return false;
+            }
+
+            angle1 = clip_angle;
+        }
+
+        t_span = clip_angle - angle2;
+
+        if t_span > double_clip_angle {
+            t_span -= double_clip_angle;
+
+            if t_span >= span {
+                return false;
+            }
+
+            angle2 = -clip_angle;
+        }
+
+        let sx1 = self.view_angle_to_x[((angle1 + ANG90).0 >> ANGLETOFINESHIFT) as usize];
+        let sx2 = self.view_angle_to_x[((angle2 + ANG90).0 >> ANGLETOFINESHIFT) as usize];
+
+        if sx1 == sx2 {
+            return false;
+        }
+
+        !self.solid_segs.is_range_occluded(sx1, sx2 - 1)
+    }
+
+    fn render_sub_sector(&mut self, map: &GameMap, frame: &mut dyn DrawTarget, sub_sector_id: usize) {
+        let Some(sub_sector) = map.ssectors.get(sub_sector_id) else {
+            return;
+        };
+
+        let Some(sector_id) = map.sub_sector_sector_id(sub_sector_id) else {
+            return;
+        };
+
+        let sector = &map.sectors[sector_id];
+        let planes = self.sector_planes[sector_id];
+
+        self.floor_plane = (planes.floor < self.view.z).then(|| {
+            self.find_plane(planes.floor, self.data.flat_id(&sector.floor_texture), sector.light_level)
+        });
+
+        self.ceiling_plane = (planes.ceiling > self.view.z || sector.ceiling_texture == SKY_FLAT).then(|| {
+            self.find_plane(planes.ceiling, self.data.flat_id(&sector.ceiling_texture), sector.light_level)
+        });
+
+        self.add_sprites(map, sector_id);
+
+        let first = sub_sector.first_seg_id as u16 as usize;
+        let count = sub_sector.seg_count as u16 as usize;
+
+        for seg_id in first..(first + count).min(map.segs.len()) {
+            self.add_line(map, frame, seg_id);
+        }
+    }
+
+    // R_AddLine: clips the seg to the view and hands it to the solid or pass-through clipper.
+    fn add_line(&mut self, map: &GameMap, frame: &mut dyn DrawTarget, seg_id: usize) {
+        let seg = &map.segs[seg_id];
+
+        let (Some(v1), Some(v2)) = (
+            map.vertexes.get(seg.start_vertex_id as u16 as usize),
+            map.vertexes.get(seg.end_vertex_id as u16 as usize)
+        ) else {
+            return;
+        };
+
+        let (v1_x, v1_y) = (Fixed::from(v1.x), Fixed::from(v1.y));
+        let (v2_x, v2_y) = (Fixed::from(v2.x), Fixed::from(v2.y));
+
+        let mut angle1 = point_to_angle2(self.view.x, self.view.y, v1_x, v1_y);
+        let mut angle2 = point_to_angle2(self.view.x, self.view.y, v2_x, v2_y);
+
+        let span = angle1 - angle2;
+
+        // Back side of the seg.
+        if span >= ANG180 {
+            return;
+        }
+
+        let rw_angle1 = angle1;
+        angle1 -= self.view.angle;
+        angle2 -= self.view.angle;
+
+        let clip_angle = self.clip_angle;
+        let double_clip_angle = clip_angle + clip_angle;
+
+        let mut t_span = angle1 + clip_angle;
+
+        if t_span > double_clip_angle {
+            t_span -= double_clip_angle;
+
+            if t_span >= span {
+                return;
+            }
+
+            angle1 = clip_angle;
+        }
+
+        t_span = clip_angle - angle2;
+
+        if t_span > double_clip_angle {
+            t_span -= double_clip_angle;
+
+            if t_span >= span {
+                return;
+            }
+
+            angle2 = -clip_angle;
+        }
+
+        let x1 = self.view_angle_to_x[((angle1 + ANG90).0 >> ANGLETOFINESHIFT) as usize];
+        let x2 = self.view_angle_to_x[((angle2 + ANG90).0 >> ANGLETOFINESHIFT) as usize];
+
+        if x1 == x2 {
+            return;
+        }
+
+        let (sidedef, front, back) = map.seg_sides(seg);
+
+        let (Some(linedef), Some(sidedef), Some(front)) = (map.linedefs.get(seg.linedef_id as u16 as usize), sidedef, front) else {
+            return;
+        };
+
+        let (front_id, back_id) = map.seg_sector_ids(seg);
+        let front_planes = front_id.map_or_else(SectorPlanes::default, |id| self.sector_planes[id]);
+        let back_planes = back.and(back_id).map(|id| self.sector_planes[id]);
+
+        let is_solid = match back_planes {
+            None => true,
+            Some(back) => back.ceiling <= front_planes.floor || back.floor >= front_planes.ceiling
+        };
+
+        // Lines between two identical sectors only exist to trigger specials.
+        if let (Some(back), Some(back_planes)) = (back, back_planes) {
+            if !is_solid
+                && back_planes.ceiling == front_planes.ceiling
+                && back_planes.floor == front_planes.floor
+                && back.ceiling_texture == front.ceiling_texture
+                && back.floor_texture == front.floor_texture
+                && back.light_level == front.light_level
+                && sidedef.middle_texture == Sidedef::NO_TEXTURE {
+                return;
+            }
+        }
+
+        let wall = WallSeg {
+            v1_x,
+            v1_y,
+            v2_x,
+            v2_y,
+            angle: Bam((seg.angle as u16 as u32) << 16),
+            rw_angle1,
+            offset: Fixed::from(seg.offset),
+            linedef,
+            sidedef,
+            front,
+            back,
+            front_planes,
+            back_planes
+        };
+
+        let mut visible = std::mem::take(&mut self.visible_ranges);
+        visible.clear();
+
+        if is_solid {
+            self.solid_segs.clip_solid(x1, x2 - 1, &mut visible);
+        } else {
+            self.solid_segs.clip_pass(x1, x2 - 1, &mut visible);
+        }
+
+        for &(start, stop) in &visible {
+            self.store_wall_range(&wall, start, stop, frame);
+        }
+
+        self.visible_ranges = visible;
+    }
+}
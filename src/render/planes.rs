@@ -0,0 +1,188 @@
+use crate::backend::DrawTarget;
+use crate::math::{Bam, Fixed, ANG90, FRACUNIT};
+use crate::render::Renderer;
+
+const UNSET: i32 = i32::MAX;
+
+pub(super) struct Visplane {
+    pub height: Fixed,
+    pub flat: Option<usize>,
+    pub light_level: i16,
+    pub min_x: i32,
+    pub max_x: i32,
+    pub top: Vec<i32>,
+    pub bottom: Vec<i32>
+}
+
+impl Visplane {
+    fn new(height: Fixed, flat: Option<usize>, light_level: i16, width: i32) -> Self {
+        Self {
+            height,
+            flat,
+            light_level,
+            min_x: width,
+            max_x: -1,
+            top: vec![UNSET; width as usize],
+            bottom: vec![0; width as usize]
+        }
+    }
+
+    fn column(&self, x: i32) -> (i32, i32) {
+        if x < self.min_x || x > self.max_x {
+            (UNSET, 0)
+        } else {
+            (self.top[x as usize], self.bottom[x as usize])
+        }
+    }
+}
+
+impl Renderer {
+    // R_InitPlanes / the plane part of R_ExecuteSetViewSize.
+    pub(super) fn init_planes(&mut self) {
+        self.y_slope = (0..self.height)
+            .map(|y| {
+                let dy = Fixed(((y - self.height / 2) << 16) + FRACUNIT / 2).abs();
+                (self.projection << self.detail_shift) / dy
+            })
+            .collect();
+
+        self.dist_scale = self.x_to_view_angle[..self.width as usize].iter()
+            .map(|&angle| Fixed::ONE / angle.cos().abs())
+            .collect();
+
+        self.span_start = vec![0; self.height as usize];
+    }
+
+    // R_ClearPlanes
+    pub(super) fn clear_planes(&mut self) {
+        self.planes.clear();
+
+        let angle = self.view.angle - ANG90;
+        self.base_x_scale = angle.cos() / self.projection;
+        self.base_y_scale = -(angle.sin() / self.projection);
+    }
+
+    // R_FindPlane: reuses a plane with the same height, flat and light level.
+    pub(super) fn find_plane(&mut self, mut height: Fixed, flat: Option<usize>, mut light_level: i16) -> usize {
+        // All sky is one plane, whatever the sector.
+        if flat.is_some() && flat == self.sky_flat {
+            height = Fixed::ZERO;
+            light_level = 0;
+        }
+
+        if let Some(id) = self.planes.iter().position(|plane| {
+            plane.height == height && plane.flat == flat && plane.light_level == light_level
+        }) {
+            return id;
+        }
+
+        self.planes.push(Visplane::new(height, flat, light_level, self.width));
+        self.planes.len() - 1
+    }
+
+    // R_CheckPlane: splits the plane if start..=stop overlaps columns it already covers.
+    pub(super) fn check_plane(&mut self, plane_id: usize, start: i32, stop: i32) -> usize {
+        let plane = &self.planes[plane_id];
+
+        let (intersect_low, union_low) = if start < plane.min_x { (plane.min_x, start) } else { (start, plane.min_x) };
+        let (intersect_high, union_high) = if stop > plane.max_x { (plane.max_x, stop) } else { (stop, plane.max_x) };
+
+        let overlaps = (intersect_low..=intersect_high).any(|x| plane.top[x as usize] != UNSET);
+
+        if !overlaps {
+            let plane = &mut self.planes[plane_id];
+            plane.min_x = union_low;
+            plane.max_x = union_high;
+            return plane_id;
+        }
+
+        let mut split = Visplane::new(plane.height, plane.flat, plane.light_level, self.width);
+        split.min_x = start;
+        split.max_x = stop;
+
+        self.planes.push(split);
+        self.planes.len() - 1
+    }
+
+    pub(super) fn mark_plane(&mut self, plane_id: usize, x: i32, top: i32, bottom: i32) {
+        let plane = &mut self.planes[plane_id];
+        plane.top[x as usize] = top;
+        plane.bottom[x as usize] = bottom;
+    }
+
+    // R_DrawPlanes
+    pub(super) fn draw_planes(&mut self, frame: &mut dyn DrawTarget) {
+        self.stats.visplanes = self.planes.len();
+
+        let planes = std::mem::take(&mut self.planes);
+
+        for plane in planes.iter().filter(|plane| plane.min_x <= plane.max_x) {
+            if plane.flat.is_none() {
+                continue;
+            }
+
+            if plane.flat == self.sky_flat {
+                self.draw_sky(frame, plane);
+                continue;
+            }
+
+            for x in plane.min_x..=plane.max_x + 1 {
+                let (t1, b1) = plane.column(x - 1);
+                let (t2, b2) = plane.column(x);
+
+                self.make_spans(frame, plane, x, t1, b1, t2, b2);
+            }
+        }
+
+        self.planes = planes;
+    }
+
+    // R_MakeSpans: closes spans that ended in the previous column and opens the new ones.
+    #[allow(clippy::too_many_arguments)]
+    fn make_spans(
+        &mut self, frame: &mut dyn DrawTarget, plane: &Visplane,
+        x: i32, mut t1: i32, mut b1: i32, mut t2: i32, mut b2: i32
+    ) {
+        while t1 < t2 && t1 <= b1 {
+            self.map_plane(frame, plane, t1, self.span_start[t1 as usize], x - 1);
+            t1 += 1;
+        }
+
+        while b1 > b2 && b1 >= t1 {
+            self.map_plane(frame, plane, b1, self.span_start[b1 as usize], x - 1);
+            b1 -= 1;
+        }
+
+        while t2 < t1 && t2 <= b2 {
+            self.span_start[t2 as usize] = x;
+            t2 += 1;
+        }
+
+        while b2 > b1 && b2 >= t2 {
+            self.span_start[b2 as usize] = x;
+            b2 -= 1;
+        }
+    }
+
+    // R_MapPlane followed by R_DrawSpan.
+    fn map_plane(&self, frame: &mut dyn DrawTarget, plane: &Visplane, y: i32, x1: i32, x2: i32) {
+        let Some(flat) = plane.flat else {
+            return;
+        };
+
+        let plane_height = (plane.height - self.view.z).abs();
+        let distance = plane_height * self.y_slope[y as usize];
+        let x_step = distance * self.base_x_scale;
+        let y_step = distance * self.base_y_scale;
+
+        let length = distance * self.dist_scale[x1 as usize];
+        let angle: Bam = self.view.angle + self.x_to_view_angle[x1 as usize];
+
+        let x_frac = self.view.x + angle.cos() * length;
+        let y_frac = -self.view.y - angle.sin() * length;
+
+        let colormap = self.z_colormap(self.light_row(plane.light_level, 0), distance);
+
+        self.draw_span(frame, flat, colormap, y, x1, x2, x_frac, y_frac, x_step, y_step);
+    }
+}
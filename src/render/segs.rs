@@ -0,0 +1,419 @@
+use crate::backend::DrawTarget;
+use crate::math::{point_to_dist, Bam, Fixed, ANG180, ANG90, FRACBITS};
+use crate::render::{Renderer, SectorPlanes, SKY_FLAT};
+use crate::wad::{Linedef, Sector, Sidedef};
+
+const HEIGHT_BITS: i32 = 12;
+const HEIGHT_UNIT: i32 = 1 << HEIGHT_BITS;
+
+const MIN_SCALE: Fixed = Fixed(256);
+const MAX_SCALE: Fixed = Fixed::from_int(64);
+
+pub(super) const SIL_BOTTOM: u8 = 1;
+pub(super) const SIL_TOP: u8 = 2;
+
+pub(super) const MASKED_COLUMN_DRAWN: i32 = i32::MAX;
+
+pub(super) struct WallSeg<'a> {
+    pub v1_x: Fixed,
+    pub v1_y: Fixed,
+    pub v2_x: Fixed,
+    pub v2_y: Fixed,
+    pub angle: Bam,
+    pub rw_angle1: Bam,
+    pub offset: Fixed,
+    pub linedef: &'a Linedef,
+    pub sidedef: &'a Sidedef,
+    pub front: &'a Sector,
+    pub back: Option<&'a Sector>,
+    pub front_planes: SectorPlanes,
+    pub back_planes: Option<SectorPlanes>
+}
+
+// The part of a two-sided line's middle texture that is drawn later, back to front with the sprites.
+pub(super) struct MaskedMid {
+    pub texture: usize,
+    pub texture_mid: Fixed,
+    pub light_row: usize,
+    pub columns: Vec<i32>
+}
+
+// A drawn wall range with what sprites need to be clipped against it. The clip arrays
+// are indexed from x1; None means the wall doesn't clip sprites on that side.
+pub(super) struct DrawSeg {
+    pub x1: i32,
+    pub x2: i32,
+    pub scale1: Fixed,
+    pub scale2: Fixed,
+    pub scale_step: Fixed,
+    pub v1_x: Fixed,
+    pub v1_y: Fixed,
+    pub v2_x: Fixed,
+    pub v2_y: Fixed,
+    pub silhouette: u8,
+    pub bottom_sil_height: Fixed,
+    pub top_sil_height: Fixed,
+    pub sprite_top_clip: Option<Vec<i32>>,
+    pub sprite_bottom_clip: Option<Vec<i32>>,
+    pub masked: Option<MaskedMid>
+}
+
+impl Renderer {
+    // R_ScaleFromGlobalAngle
+    fn scale_from_global_angle(&self, vis_angle: Bam, normal_angle: Bam, distance: Fixed) -> Fixed {
+        let angle_a = ANG90 + (vis_angle - self.view.angle);
+        let angle_b = ANG90 + (vis_angle - normal_angle);
+
+        let num = (self.projection * angle_b.sin()) << self.detail_shift;
+        let den = distance * angle_a.sin();
+
+        if den.0 > num.0 >> FRACBITS {
+            (num / den).clamp(MIN_SCALE, MAX_SCALE)
+        } else {
+            MAX_SCALE
+        }
+    }
+
+    // R_StoreWallRange followed by R_RenderSegLoop for the columns start..=stop.
+    pub(super) fn store_wall_range(&mut self, wall: &WallSeg, start: i32, stop: i32, frame: &mut dyn DrawTarget) {
+        let normal_angle = wall.angle + ANG90;
+        let offset_angle = Bam(((normal_angle - wall.rw_angle1).0 as i32).unsigned_abs()).min(ANG90);
+        let distance_angle = ANG90 - offset_angle;
+
+        let hypotenuse = point_to_dist(self.view.x, self.view.y, wall.v1_x, wall.v1_y);
+        let distance = hypotenuse * distance_angle.sin();
+
+        let scale = self.scale_from_global_angle(
+            self.view.angle + self.x_to_view_angle[start as usize], normal_angle, distance
+        );
+
+        let (scale2, scale_step) = if stop > start {
+            let scale2 = self.scale_from_global_angle(
+                self.view.angle + self.x_to_view_angle[stop as usize], normal_angle, distance
+            );
+
+            (scale2, Fixed((scale2 - scale).0 / (stop - start)))
+        } else {
+            (scale, Fixed::ZERO)
+        };
+
+        let mut draw_seg = DrawSeg {
+            x1: start,
+            x2: stop,
+            scale1: scale,
+            scale2,
+            scale_step,
+            v1_x: wall.v1_x,
+            v1_y: wall.v1_y,
+            v2_x: wall.v2_x,
+            v2_y: wall.v2_y,
+            silhouette: 0,
+            bottom_sil_height: Fixed::ZERO,
+            top_sil_height: Fixed::ZERO,
+            sprite_top_clip: None,
+            sprite_bottom_clip: None,
+            masked: None
+        };
+
+        let front = wall.front;
+        let sidedef = wall.sidedef;
+        let mut world_top = wall.front_planes.ceiling - self.view.z;
+        let world_bottom = wall.front_planes.floor - self.view.z;
+
+        let mut mid_texture = None;
+        let mut top_texture = None;
+        let mut bottom_texture = None;
+        let mut masked_texture = None;
+        let mut mid_texture_mid = Fixed::ZERO;
+        let mut top_texture_mid = Fixed::ZERO;
+        let mut bottom_texture_mid = Fixed::ZERO;
+        let mut mark_ceiling = true;
+        let mut mark_floor = true;
+        let mut world_high = Fixed::ZERO;
+        let mut world_low = Fixed::ZERO;
+
+        let texture_height = |texture_id: Option<usize>| {
+            texture_id.map_or(Fixed::ZERO, |id| self.data.textures[id].height_frac())
+        };
+
+        match wall.back.zip(wall.back_planes) {
+            None => {
+                draw_seg.silhouette = SIL_TOP | SIL_BOTTOM;
+                draw_seg.sprite_top_clip = Some(vec![self.height; (stop - start + 1) as usize]);
+                draw_seg.sprite_bottom_clip = Some(vec![-1; (stop - start + 1) as usize]);
+                draw_seg.bottom_sil_height = Fixed::MAX;
+                draw_seg.top_sil_height = Fixed::MIN;
+
+                mid_texture = self.data.texture_id(&sidedef.middle_texture);
+
+                mid_texture_mid = if wall.linedef.has_flag(Linedef::LOWER_UNPEGGED) {
+                    wall.front_planes.floor + texture_height(mid_texture) - self.view.z
+                } else {
+                    world_top
+                };
+
+                mid_texture_mid += Fixed::from(sidedef.y_offset);
+            },
+            Some((back, back_planes)) => {
+                let (front_floor, front_ceiling) = (wall.front_planes.floor, wall.front_planes.ceiling);
+                let (back_floor, back_ceiling) = (back_planes.floor, back_planes.ceiling);
+
+                if front_floor > back_floor {
+                    draw_seg.silhouette = SIL_BOTTOM;
+                    draw_seg.bottom_sil_height = front_floor;
+                } else if back_floor > self.view.z {
+                    draw_seg.silhouette = SIL_BOTTOM;
+                    draw_seg.bottom_sil_height = Fixed::MAX;
+                }
+
+                if front_ceiling < back_ceiling {
+                    draw_seg.silhouette |= SIL_TOP;
+                    draw_seg.top_sil_height = front_ceiling;
+                } else if back_ceiling < self.view.z {
+                    draw_seg.silhouette |= SIL_TOP;
+                    draw_seg.top_sil_height = Fixed::MIN;
+                }
+
+                // Closed doors hide everything behind them.
+                if back_ceiling <= front_floor {
+                    draw_seg.sprite_bottom_clip = Some(vec![-1; (stop - start + 1) as usize]);
+                    draw_seg.bottom_sil_height = Fixed::MAX;
+                    draw_seg.silhouette |= SIL_BOTTOM;
+                }
+
+                if back_floor >= front_ceiling {
+                    draw_seg.sprite_top_clip = Some(vec![self.height; (stop - start + 1) as usize]);
+                    draw_seg.top_sil_height = Fixed::MIN;
+                    draw_seg.silhouette |= SIL_TOP;
+                }
+
+                world_high = back_planes.ceiling - self.view.z;
+                world_low = back_planes.floor - self.view.z;
+
+                // Sky hack: no upper texture between two sky ceilings, so outdoor height changes show sky.
+                if front.ceiling_texture == SKY_FLAT && back.ceiling_texture == SKY_FLAT {
+                    world_top = world_high;
+                }
+
+                mark_floor = world_low != world_bottom
+                    || back.floor_texture != front.floor_texture
+                    || back.light_level != front.light_level;
+
+                mark_ceiling = world_high != world_top
+                    || back.ceiling_texture != front.ceiling_texture
+                    || back.light_level != front.light_level;
+
+                // Closed doors
+                if back_planes.ceiling <= wall.front_planes.floor || back_planes.floor >= wall.front_planes.ceiling {
+                    mark_ceiling = true;
+                    mark_floor = true;
+                }
+
+                if world_high < world_top && sidedef.upper_texture != Sidedef::NO_TEXTURE {
+                    top_texture = self.data.texture_id(&sidedef.upper_texture);
+
+                    top_texture_mid = if wall.linedef.has_flag(Linedef::UPPER_UNPEGGED) {
+                        world_top
+                    } else {
+                        back_planes.ceiling + texture_height(top_texture) - self.view.z
+                    };
+                }
+
+                if world_low > world_bottom && sidedef.lower_texture != Sidedef::NO_TEXTURE {
+                    bottom_texture = self.data.texture_id(&sidedef.lower_texture);
+
+                    bottom_texture_mid = if wall.linedef.has_flag(Linedef::LOWER_UNPEGGED) {
+                        world_top
+                    } else {
+                        world_low
+                    };
+                }
+
+                top_texture_mid += Fixed::from(sidedef.y_offset);
+                bottom_texture_mid += Fixed::from(sidedef.y_offset);
+
+                if sidedef.middle_texture != Sidedef::NO_TEXTURE {
+                    masked_texture = self.data.texture_id(&sidedef.middle_texture);
+
+                    mid_texture_mid = if wall.linedef.has_flag(Linedef::LOWER_UNPEGGED) {
+                        front_floor.max(back_floor) + texture_height(masked_texture) - self.view.z
+                    } else {
+                        front_ceiling.min(back_ceiling) - self.view.z
+                    };
+
+                    mid_texture_mid += Fixed::from(sidedef.y_offset);
+                }
+            }
+        }
+
+        let is_textured = mid_texture.is_some() || top_texture.is_some() || bottom_texture.is_some()
+            || masked_texture.is_some();
+
+        // rw_offset: how far along the wall the perpendicular from the view lands.
+        let mut texture_offset = hypotenuse * offset_angle.sin();
+
+        if (normal_angle - wall.rw_angle1) < ANG180 {
+            texture_offset = -texture_offset;
+        }
+
+        texture_offset += Fixed::from(sidedef.x_offset) + wall.offset;
+        let center_angle = self.view.angle - normal_angle;
+
+        // Fake contrast: horizontal walls are darker and vertical ones brighter.
+        let contrast = if wall.v1_y == wall.v2_y {
+            -1
+        } else if wall.v1_x == wall.v2_x {
+            1
+        } else {
+            0
+        };
+
+        let light_row = self.light_row(front.light_level, contrast);
+
+        if wall.front_planes.floor >= self.view.z {
+            mark_floor = false;
+        }
+
+        if wall.front_planes.ceiling <= self.view.z && front.ceiling_texture != SKY_FLAT {
+            mark_ceiling = false;
+        }
+
+        let ceiling_plane = self.ceiling_plane.filter(|_| mark_ceiling).map(|plane| self.check_plane(plane, start, stop));
+        let floor_plane = self.floor_plane.filter(|_| mark_floor).map(|plane| self.check_plane(plane, start, stop));
+
+        self.ceiling_plane = ceiling_plane.or(self.ceiling_plane);
+        self.floor_plane = floor_plane.or(self.floor_plane);
+
+        let (world_top, world_bottom) = (world_top >> 4, world_bottom >> 4);
+        let (world_high, world_low) = (world_high >> 4, world_low >> 4);
+
+        let mut top_frac = (self.center_y_frac >> 4) - world_top * scale;
+        let top_step = -(scale_step * world_top);
+        let mut bottom_frac = (self.center_y_frac >> 4) - world_bottom * scale;
+        let bottom_step = -(scale_step * world_bottom);
+
+        let mut pix_high = (self.center_y_frac >> 4) - world_high * scale;
+        let pix_high_step = -(scale_step * world_high);
+        let mut pix_low = (self.center_y_frac >> 4) - world_low * scale;
+        let pix_low_step = -(scale_step * world_low);
+
+        let mut scale = scale;
+        let mut masked_columns: Vec<i32> = Vec::new();
+
+        for x in start..=stop {
+            let column = x as usize;
+            let ceiling_clip = self.ceiling_clip[column];
+            let floor_clip = self.floor_clip[column];
+
+            let y_low = ((top_frac.0 + HEIGHT_UNIT - 1) >> HEIGHT_BITS).max(ceiling_clip + 1);
+            let y_high = (bottom_frac.0 >> HEIGHT_BITS).min(floor_clip - 1);
+
+            if let Some(plane) = ceiling_plane {
+                let (top, bottom) = (ceiling_clip + 1, (y_low - 1).min(floor_clip - 1));
+
+                if top <= bottom {
+                    self.mark_plane(plane, x, top, bottom);
+                }
+            }
+
+            if let Some(plane) = floor_plane {
+                let (top, bottom) = ((y_high + 1).max(ceiling_clip + 1), floor_clip - 1);
+
+                if top <= bottom {
+                    self.mark_plane(plane, x, top, bottom);
+                }
+            }
+
+            let mut texture_column = 0;
+            let mut inv_scale = Fixed::ZERO;
+            let mut colormap = 0;
+
+            if is_textured {
+                let angle = center_angle + self.x_to_view_angle[column];
+                texture_column = (texture_offset - angle.tan() * distance).0 >> FRACBITS;
+                inv_scale = Fixed((u32::MAX / scale.0.max(1) as u32) as i32);
+                colormap = self.scale_colormap(light_row, scale);
+            }
+
+            if masked_texture.is_some() {
+                masked_columns.push(texture_column);
+            }
+
+            if wall.back.is_none() {
+                if let Some(texture) = mid_texture.map(|id| &self.data.textures[id]) {
+                    if y_low <= y_high {
+                        self.draw_column(frame, texture, texture_column, colormap, x, y_low, y_high, mid_texture_mid, inv_scale);
+                    }
+                }
+
+                self.ceiling_clip[column] = self.height;
+                self.floor_clip[column] = -1;
+            } else {
+                if let Some(top_texture) = top_texture {
+                    let mid = (pix_high.0 >> HEIGHT_BITS).min(floor_clip - 1);
+
+                    if mid >= y_low {
+                        let texture = &self.data.textures[top_texture];
+                        self.draw_column(frame, texture, texture_column, colormap, x, y_low, mid, top_texture_mid, inv_scale);
+                        self.ceiling_clip[column] = mid;
+                    } else {
+                        self.ceiling_clip[column] = y_low - 1;
+                    }
+                } else if mark_ceiling {
+                    self.ceiling_clip[column] = y_low - 1;
+                }
+
+                if let Some(bottom_texture) = bottom_texture {
+                    let mid = ((pix_low.0 + HEIGHT_UNIT - 1) >> HEIGHT_BITS).max(self.ceiling_clip[column] + 1);
+
+                    if mid <= y_high {
+                        let texture = &self.data.textures[bottom_texture];
+                        self.draw_column(frame, texture, texture_column, colormap, x, mid, y_high, bottom_texture_mid, inv_scale);
+                        self.floor_clip[column] = mid;
+                    } else {
+                        self.floor_clip[column] = y_high + 1;
+                    }
+                } else if mark_floor {
+                    self.floor_clip[column] = y_high + 1;
+                }
+            }
+
+            scale += scale_step;
+            top_frac += top_step;
+            bottom_frac += bottom_step;
+            pix_high += pix_high_step;
+            pix_low += pix_low_step;
+        }
+
+        let columns = start as usize..=stop as usize;
+
+        if (draw_seg.silhouette & SIL_TOP != 0 || masked_texture.is_some()) && draw_seg.sprite_top_clip.is_none() {
+            draw_seg.sprite_top_clip = Some(self.ceiling_clip[columns.clone()].to_vec());
+        }
+
+        if (draw_seg.silhouette & SIL_BOTTOM != 0 || masked_texture.is_some()) && draw_seg.sprite_bottom_clip.is_none() {
+            draw_seg.sprite_bottom_clip = Some(self.floor_clip[columns].to_vec());
+        }
+
+        if let Some(texture) = masked_texture {
+            if draw_seg.silhouette & SIL_TOP == 0 {
+                draw_seg.silhouette |= SIL_TOP;
+                draw_seg.top_sil_height = Fixed::MIN;
+            }
+
+            if draw_seg.silhouette & SIL_BOTTOM == 0 {
+                draw_seg.silhouette |= SIL_BOTTOM;
+                draw_seg.bottom_sil_height = Fixed::MAX;
+            }
+
+            draw_seg.masked = Some(MaskedMid {
+                texture,
+                texture_mid: mid_texture_mid,
+                light_row,
+                columns: masked_columns
+            });
+        }
+
+        self.draw_segs.push(draw_seg);
+    }
+}
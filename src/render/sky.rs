@@ -0,0 +1,59 @@
+use crate::backend::DrawTarget;
+use crate::math::{Fixed, FRACUNIT};
+use crate::render::planes::Visplane;
+use crate::render::Renderer;
+
+const ANGLE_TO_SKY_SHIFT: u32 = 22;
+const SKY_TEXTURE_MID: Fixed = Fixed::from_int(100);
+
+// G_DoLoadLevel's choice of sky: by episode for ExMy maps, by map range for MAPxx.
+pub fn sky_texture_name(map_name: &str) -> String {
+    let name = map_name.to_uppercase();
+
+    if let Some(number) = name.strip_prefix("MAP").and_then(|number| number.parse::<u32>().ok()) {
+        return match number {
+            0..=11 => "SKY1",
+            12..=20 => "SKY2",
+            _ => "SKY3"
+        }.to_string();
+    }
+
+    match name.as_bytes() {
+        [b'E', episode @ b'1'..=b'4', b'M', ..] => format!("SKY{}", *episode as char),
+        _ => "SKY1".to_string()
+    }
+}
+
+impl Renderer {
+    pub fn set_sky_texture(&mut self, texture_name: &str) {
+        self.sky_texture = self.data.texture_id(texture_name);
+
+        for worker in &mut self.workers {
+            worker.sky_texture = self.sky_texture;
+        }
+    }
+
+    pub fn set_sky_for_map(&mut self, map_name: &str) {
+        self.set_sky_texture(&sky_texture_name(map_name));
+    }
+
+    // The sky branch of R_DrawPlanes: columns are picked by view angle, always at full brightness.
+    pub(super) fn draw_sky(&self, frame: &mut dyn DrawTarget, plane: &Visplane) {
+        let Some(texture) = self.sky_texture.map(|id| &self.data.textures[id]) else {
+            return;
+        };
+
+        let inv_scale = Fixed(FRACUNIT * 320 / self.base_width);
+
+        for x in plane.min_x..=plane.max_x {
+            let (top, bottom) = (plane.top[x as usize], plane.bottom[x as usize]);
+
+            if top <= bottom {
+                let angle = self.view.angle + self.x_to_view_angle[x as usize];
+                let column = (angle.0 >> ANGLE_TO_SKY_SHIFT) as i32;
+
+                self.draw_column(frame, texture, column, 0, x, top, bottom, SKY_TEXTURE_MID, inv_scale);
+            }
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use crate::backend::DrawTarget;
+use crate::map::{point_on_line_side, GameMap};
+use crate::math::{point_to_angle2, Bam, Fixed, ANG45, FRACBITS, FRACUNIT};
+use crate::render::segs::{DrawSeg, MASKED_COLUMN_DRAWN, SIL_BOTTOM, SIL_TOP};
+use crate::render::Renderer;
+use crate::things::{thing_sprite, ThingSprite};
+use crate::wad::Thing;
+
+const MIN_Z: Fixed = Fixed(FRACUNIT * 4);
+
+// The skill the renderer spawns things for, vanilla's default.
+const SPAWN_SKILL: i16 = Thing::SKILL_MEDIUM;
+
+// A thing projected to the screen, ready to be drawn in the masked pass.
+pub(super) struct VisSprite {
+    pub x1: i32,
+    pub x2: i32,
+    pub gx: Fixed,
+    pub gy: Fixed,
+    pub gz: Fixed,
+    pub gzt: Fixed,
+    pub start_frac: Fixed,
+    pub scale: Fixed,
+    pub x_iscale: Fixed,
+    pub texture_mid: Fixed,
+    pub patch: usize,
+    pub colormap: Option<usize>
+}
+
+impl Renderer {
+    // Sorts the map's things into the sectors they stand in, standing in for the sector thing lists.
+    pub(super) fn link_things(&mut self, map: &GameMap) {
+        self.sector_things.iter_mut().for_each(Vec::clear);
+        self.sector_things.resize_with(map.sectors.len(), Vec::new);
+        self.sector_visited.clear();
+        self.sector_visited.resize(map.sectors.len(), false);
+
+        for (thing_id, thing) in map.things.iter().enumerate() {
+            if !thing.has_flag(SPAWN_SKILL) || thing.has_flag(Thing::NOT_SINGLE_PLAYER) {
+                continue;
+            }
+
+            let (x, y) = self.thing_positions[thing_id];
+
+            if let Some(sector_id) = map.sub_sector_sector_id(map.point_in_sub_sector(x, y)) {
+                self.sector_things[sector_id].push(thing_id);
+            }
+        }
+    }
+
+    // R_AddSprites: every thing in the sector, once per frame.
+    pub(super) fn add_sprites(&mut self, map: &GameMap, sector_id: usize) {
+        if self.sector_visited.get(sector_id).copied().unwrap_or(true) {
+            return;
+        }
+
+        self.sector_visited[sector_id] = true;
+
+        let sector = &map.sectors[sector_id];
+        let planes = self.sector_planes[sector_id];
+        let light_row = self.light_row(sector.light_level, 0);
+        let thing_ids = std::mem::take(&mut self.sector_things[sector_id]);
+
+        for &thing_id in &thing_ids {
+            let thing = &map.things[thing_id];
+
+            let Some(sprite) = thing_sprite(thing.ed_type) else {
+                continue;
+            };
+
+            let z = match sprite.hanging_height {
+                Some(height) => planes.ceiling - Fixed::from(height),
+                None => planes.floor
+            };
+
+            let angle = Bam(ANG45.0.wrapping_mul((thing.angle / 45) as u32));
+            let (x, y) = self.thing_positions[thing_id];
+
+            if let Some(vis_sprite) = self.project_sprite(x, y, z, angle, sprite, light_row) {
+                self.vis_sprites.push(vis_sprite);
+            }
+        }
+
+        self.sector_things[sector_id] = thing_ids;
+    }
+
+    // R_ProjectSprite
+    fn project_sprite(
+        &self, x: Fixed, y: Fixed, z: Fixed, angle: Bam, sprite: &ThingSprite, light_row: usize
+    ) -> Option<VisSprite> {
+        let (view_cos, view_sin) = (self.view.angle.cos(), self.view.angle.sin());
+
+        let tr_x = x - self.view.x;
+        let tr_y = y - self.view.y;
+
+        let tz = tr_x * view_cos + tr_y * view_sin;
+
+        // Behind the view plane.
+        if tz < MIN_Z {
+            return None;
+        }
+
+        let x_scale = self.projection / tz;
+        let mut tx = tr_x * view_sin - tr_y * view_cos;
+
+        // Too far off the side.
+        if tx.abs() > tz << 2 {
+            return None;
+        }
+
+        let sprite_frame = self.data.sprite_frame(sprite.sprite, sprite.frame)?;
+
+        let (patch_id, flip) = if sprite_frame.rotate {
+            let view_angle = point_to_angle2(self.view.x, self.view.y, x, y);
+            let rotation = ((view_angle - angle + Bam((ANG45.0 / 2).wrapping_mul(9))).0 >> 29) as usize;
+
+            (sprite_frame.patches[rotation], sprite_frame.flip[rotation])
+        } else {
+            (sprite_frame.patches[0], sprite_frame.flip[0])
+        };
+
+        let patch = &self.data.sprite_patches[patch_id];
+        let width = Fixed::from_int(patch.width as i32);
+
+        tx -= Fixed::from_int(patch.left_offset as i32);
+        let x1 = (self.center_x_frac + tx * x_scale).0 >> FRACBITS;
+
+        if x1 > self.width {
+            return None;
+        }
+
+        tx += width;
+        let x2 = ((self.center_x_frac + tx * x_scale).0 >> FRACBITS) - 1;
+
+        if x2 < 0 {
+            return None;
+        }
+
+        let gzt = z + Fixed::from_int(patch.top_offset as i32);
+        let inv_scale = Fixed::ONE / x_scale;
+
+        let (mut start_frac, x_iscale) = if flip {
+            (width - Fixed(1), -inv_scale)
+        } else {
+            (Fixed::ZERO, inv_scale)
+        };
+
+        let visible_x1 = x1.max(0);
+
+        if visible_x1 > x1 {
+            start_frac += Fixed(x_iscale.0.wrapping_mul(visible_x1 - x1));
+        }
+
+        let colormap = match (sprite.shadow, sprite.full_bright, self.fixed_colormap) {
+            (true, _, _) => None,
+            (false, _, Some(colormap)) => Some(colormap),
+            (false, true, None) => Some(0),
+            (false, false, None) => Some(self.scale_colormap(light_row, x_scale << self.detail_shift))
+        };
+
+        Some(
+            VisSprite {
+                x1: visible_x1,
+                x2: x2.min(self.width - 1),
+                gx: x,
+                gy: y,
+                gz: z,
+                gzt,
+                start_frac,
+                scale: x_scale << self.detail_shift,
+                x_iscale,
+                texture_mid: gzt - self.view.z,
+                patch: patch_id,
+                colormap
+            }
+        )
+    }
+
+    // R_DrawMasked: sprites far to near, then whatever masked mid textures they didn't cover.
+    pub(super) fn draw_masked(&mut self, frame: &mut dyn DrawTarget) {
+        let mut vis_sprites = std::mem::take(&mut self.vis_sprites);
+        let mut draw_segs = std::mem::take(&mut self.draw_segs);
+
+        vis_sprites.sort_by_key(|sprite| sprite.scale);
+
+        for sprite in &vis_sprites {
+            self.draw_sprite(frame, sprite, &mut draw_segs);
+        }
+
+        for draw_seg in draw_segs.iter_mut().rev() {
+            let (x1, x2) = (draw_seg.x1, draw_seg.x2);
+            self.render_masked_seg_range(frame, draw_seg, x1, x2);
+        }
+
+        self.vis_sprites = vis_sprites;
+        self.draw_segs = draw_segs;
+    }
+
+    // R_DrawSprite: clips the sprite against every nearer wall silhouette.
+    fn draw_sprite(&self, frame: &mut dyn DrawTarget, sprite: &VisSprite, draw_segs: &mut [DrawSeg]) {
+        if sprite.x1 > sprite.x2 {
+            return;
+        }
+
+        const UNCLIPPED: i32 = -2;
+
+        let count = (sprite.x2 - sprite.x1 + 1) as usize;
+        let mut clip_bottom = vec![UNCLIPPED; count];
+        let mut clip_top = vec![UNCLIPPED; count];
+
+        for draw_seg in draw_segs.iter_mut().rev() {
+            if draw_seg.x1 > sprite.x2 || draw_seg.x2 < sprite.x1 || (draw_seg.silhouette == 0 && draw_seg.masked.is_none()) {
+                continue;
+            }
+
+            let (r1, r2) = (draw_seg.x1.max(sprite.x1), draw_seg.x2.min(sprite.x2));
+            let (low_scale, scale) = if draw_seg.scale1 > draw_seg.scale2 {
+                (draw_seg.scale2, draw_seg.scale1)
+            } else {
+                (draw_seg.scale1, draw_seg.scale2)
+            };
+
+            let is_behind = scale < sprite.scale || (low_scale < sprite.scale && point_on_line_side(
+                sprite.gx, sprite.gy,
+                draw_seg.v1_x, draw_seg.v1_y,
+                draw_seg.v2_x - draw_seg.v1_x, draw_seg.v2_y - draw_seg.v1_y
+            ) == 0);
+
+            if is_behind {
+                self.render_masked_seg_range(frame, draw_seg, r1, r2);
+                continue;
+            }
+
+            let mut silhouette = draw_seg.silhouette;
+
+            if sprite.gz >= draw_seg.bottom_sil_height {
+                silhouette &= !SIL_BOTTOM;
+            }
+
+            if sprite.gzt <= draw_seg.top_sil_height {
+                silhouette &= !SIL_TOP;
+            }
+
+            for x in r1..=r2 {
+                let (i, j) = ((x - sprite.x1) as usize, (x - draw_seg.x1) as usize);
+
+                if silhouette & SIL_BOTTOM != 0 && clip_bottom[i] == UNCLIPPED {
+                    if let Some(bottom) = &draw_seg.sprite_bottom_clip {
+                        clip_bottom[i] = bottom[j];
+                    }
+                }
+
+                if silhouette & SIL_TOP != 0 && clip_top[i] == UNCLIPPED {
+                    if let Some(top) = &draw_seg.sprite_top_clip {
+                        clip_top[i] = top[j];
+                    }
+                }
+            }
+        }
+
+        let patch = &self.data.sprite_patches[sprite.patch];
+        let inv_scale = sprite.x_iscale.abs() >> self.detail_shift;
+        let mut frac = sprite.start_frac;
+
+        for x in sprite.x1..=sprite.x2 {
+            let i = (x - sprite.x1) as usize;
+            let floor_clip = if clip_bottom[i] == UNCLIPPED { self.height } else { clip_bottom[i] };
+            let ceiling_clip = if clip_top[i] == UNCLIPPED { -1 } else { clip_top[i] };
+
+            if let Some(posts) = usize::try_from(frac.0 >> FRACBITS).ok().and_then(|column| patch.columns.get(column)) {
+                let posts = posts.iter().map(|post| (post.top_delta as i32, &post.pixels[..]));
+
+                self.draw_masked_column(
+                    frame, posts, sprite.colormap, x, sprite.texture_mid, sprite.scale, inv_scale, ceiling_clip, floor_clip
+                );
+            }
+
+            frac += sprite.x_iscale;
+        }
+    }
+
+    // R_RenderMaskedSegRange
+    fn render_masked_seg_range(&self, frame: &mut dyn DrawTarget, draw_seg: &mut DrawSeg, x1: i32, x2: i32) {
+        let (Some(masked), Some(top_clip), Some(bottom_clip)) = (
+            draw_seg.masked.as_mut(), &draw_seg.sprite_top_clip, &draw_seg.sprite_bottom_clip
+        ) else {
+            return;
+        };
+
+        let texture = &self.data.textures[masked.texture];
+        let mut scale = draw_seg.scale1 + Fixed((x1 - draw_seg.x1).wrapping_mul(draw_seg.scale_step.0));
+
+        for x in x1..=x2 {
+            let i = (x - draw_seg.x1) as usize;
+
+            if masked.columns[i] != MASKED_COLUMN_DRAWN {
+                let colormap = self.scale_colormap(masked.light_row, scale);
+                let inv_scale = Fixed((u32::MAX / scale.0.max(1) as u32) as i32);
+
+                self.draw_masked_column(
+                    frame, texture.posts(masked.columns[i]), Some(colormap), x,
+                    masked.texture_mid, scale, inv_scale, top_clip[i], bottom_clip[i]
+                );
+
+                masked.columns[i] = MASKED_COLUMN_DRAWN;
+            }
+
+            scale += draw_seg.scale_step;
+        }
+    }
+}
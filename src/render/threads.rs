@@ -0,0 +1,203 @@
+use std::sync::Arc;
+use crate::backend::{DrawTarget, FrameBuffer};
+use crate::map::GameMap;
+use crate::math::Fixed;
+use crate::render::{Renderer, TicSnapshot};
+
+// A worker's share of the screen, drawn into its own buffer and copied over once every
+// thread is done.
+struct StripTarget {
+    x: i32,
+    frame: FrameBuffer
+}
+
+impl StripTarget {
+    fn copy_from(target: &dyn DrawTarget, (first, last): (i32, i32)) -> Self {
+        let mut strip = Self {
+            x: first,
+            frame: FrameBuffer::new((last - first + 1) as u32, target.height() as u32)
+        };
+
+        let width = strip.frame.width() as usize;
+
+        for (y, row) in strip.frame.pixels_mut().chunks_exact_mut(width).enumerate() {
+            target.read_row(first, y as i32, row);
+        }
+
+        strip
+    }
+
+    fn copy_to(&self, target: &mut dyn DrawTarget) {
+        for (y, row) in self.frame.pixels().chunks_exact(self.frame.width() as usize).enumerate() {
+            target.write_row(self.x, y as i32, row);
+        }
+    }
+}
+
+impl DrawTarget for StripTarget {
+    fn width(&self) -> i32 {
+        self.frame.width()
+    }
+
+    fn height(&self) -> i32 {
+        self.frame.height()
+    }
+
+    fn pixel(&self, x: i32, y: i32) -> Option<u8> {
+        self.frame.pixel(x - self.x, y)
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, color: u8) {
+        self.frame.set_pixel(x - self.x, y, color);
+    }
+}
+
+impl Renderer {
+    // Splits the view into strips of whole columns, one per thread. Every thread still walks
+    // the whole BSP and sets up every wall and sprite, so everything that is stepped from
+    // column to column comes out exactly as on one thread, but only draws its own strip.
+    pub fn set_threads(&mut self, threads: usize) {
+        let threads = threads.clamp(1, self.width as usize) as i32;
+        let strip = |i: i32| (self.width * i / threads, self.width * (i + 1) / threads - 1);
+
+        self.strip = strip(0);
+        self.workers = (1..threads)
+            .map(|i| {
+                let mut worker = Renderer::with_data(self.screen, self.view_size.0 as u32, self.view_size.1 as u32, self.detail_shift, Arc::clone(&self.data));
+                worker.strip = strip(i);
+                worker.sky_texture = self.sky_texture;
+                worker.fuzz_pos.set(self.fuzz_pos.get());
+                worker
+            })
+            .collect();
+    }
+
+    pub fn threads(&self) -> usize {
+        self.workers.len() + 1
+    }
+
+    pub(super) fn render_parallel(&mut self, map: &GameMap, previous: &TicSnapshot, frac: Fixed, frame: &mut dyn DrawTarget) {
+        let mut workers = std::mem::take(&mut self.workers);
+        let mut strips: Vec<StripTarget> = workers.iter()
+            .map(|worker| StripTarget::copy_from(frame, worker.strip))
+            .collect();
+
+        std::thread::scope(|scope| {
+            for (worker, strip) in workers.iter_mut().zip(&mut strips) {
+                scope.spawn(move || worker.render_strip(map, previous, frac, strip));
+            }
+
+            // This thread takes the first strip and draws it in place.
+            self.render_strip(map, previous, frac, frame);
+        });
+
+        for strip in &strips {
+            strip.copy_to(frame);
+        }
+
+        self.workers = workers;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Bam;
+    use crate::render::data::tests::test_data;
+    use crate::wad::{Linedef, Point, Sector, Seg, Sidedef, SubSector, Thing, Vertex};
+
+    // A square room, a single subsector without nodes, with two spectres in front of the
+    // player so fuzz is drawn across the strips.
+    fn room() -> GameMap {
+        let mut map = GameMap::with_name("TEST");
+        let corners = [(0, 0), (0, 256), (256, 256), (256, 0)];
+
+        map.vertexes = corners.iter().map(|&(x, y)| Vertex { x, y }).collect();
+        map.linedefs = (0..4)
+            .map(|i| Linedef {
+                start_vertex_id: i,
+                end_vertex_id: (i + 1) % 4,
+                flags: Linedef::BLOCKING,
+                line_type: 0,
+                sector_tag: 0,
+                front_sidedef_id: i,
+                back_sidedef_id: Linedef::NO_SIDEDEF
+            })
+            .collect();
+        map.sidedefs = (0..4)
+            .map(|_| Sidedef {
+                x_offset: 0,
+                y_offset: 0,
+                upper_texture: Sidedef::NO_TEXTURE.to_string(),
+                lower_texture: Sidedef::NO_TEXTURE.to_string(),
+                middle_texture: "TEST".to_string(),
+                sector_id: 0
+            })
+            .collect();
+        map.sectors = vec![Sector {
+            floor_height: 0,
+            ceiling_height: 128,
+            floor_texture: "FLAT".to_string(),
+            ceiling_texture: "FLAT".to_string(),
+            light_level: 160,
+            special: 0,
+            tag: 0
+        }];
+        map.segs = map.linedefs.iter()
+            .map(|linedef| {
+                let (start, end) = (corners[linedef.start_vertex_id as usize], corners[linedef.end_vertex_id as usize]);
+                let angle = Bam::from_degrees(((end.1 - start.1) as f64).atan2((end.0 - start.0) as f64).to_degrees());
+
+                Seg {
+                    start_vertex_id: linedef.start_vertex_id as i16,
+                    end_vertex_id: linedef.end_vertex_id as i16,
+                    angle: (angle.0 >> 16) as i16,
+                    linedef_id: linedef.front_sidedef_id as i16,
+                    direction: 0,
+                    offset: 0
+                }
+            })
+            .collect();
+        map.ssectors = vec![SubSector { seg_count: 4, first_seg_id: 0 }];
+        map.things = [(100, 150), (150, 170)]
+            .map(|(x, y)| Thing { position: Point { x, y }, angle: 0, ed_type: 58, flags: Thing::SKILL_MEDIUM })
+            .to_vec();
+        map.calc_map_bounds();
+
+        map.player.position = Point { x: 128, y: 40 };
+        map.player.angle = Bam::from_degrees(100.0);
+        map
+    }
+
+    fn render(renderer: &mut Renderer, map: &GameMap) -> FrameBuffer {
+        let mut frame = FrameBuffer::new(320, 200);
+        renderer.render_player_view(map, &TicSnapshot::capture(map), Fixed::ONE, &mut frame);
+        frame
+    }
+
+    #[test]
+    fn threads_draw_the_same_pixels_as_one() {
+        let map = room();
+        let mut single = Renderer::new(320, 200, test_data());
+        let mut threaded = Renderer::new(320, 200, test_data());
+
+        threaded.set_threads(4);
+
+        let frame = render(&mut single, &map);
+        assert_eq!(frame.pixels(), render(&mut threaded, &map).pixels());
+        assert!(frame.pixels().iter().any(|&pixel| pixel != 0));
+    }
+
+    #[test]
+    fn new_workers_carry_on_the_fuzz() {
+        let map = room();
+        let mut single = Renderer::new(320, 200, test_data());
+        let mut threaded = Renderer::new(320, 200, test_data());
+
+        render(&mut single, &map);
+        render(&mut threaded, &map);
+        threaded.set_threads(4);
+
+        assert_eq!(render(&mut single, &map).pixels(), render(&mut threaded, &map).pixels());
+    }
+}
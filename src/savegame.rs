@@ -0,0 +1,250 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, bail, Result};
+use crate::math::Bam;
+use crate::player::{Card, Player, Power, Weapon};
+use crate::stats::Skill;
+use crate::wad::Point;
+
+pub const SAVE_SLOTS: usize = 6;
+pub const SAVE_STRING_SIZE: usize = 24;
+
+// Like vanilla's, a save starts with its description and a version string, but what follows
+// is this engine's own and not "version 109".
+const VERSION_SIZE: usize = 16;
+const VERSION: &str = "doom-rs save 1";
+const MAP_NAME_SIZE: usize = 8;
+
+pub fn save_path(dir: &Path, slot: usize) -> PathBuf {
+    dir.join(format!("doomsav{}.dsg", slot))
+}
+
+// M_ReadSaveStrings: the description of every slot that has a save in it.
+pub fn read_save_descriptions(dir: &Path) -> [Option<String>; SAVE_SLOTS] {
+    std::array::from_fn(|slot| {
+        let bytes = fs::read(save_path(dir, slot)).ok()?;
+        SaveReader { bytes: &bytes, offset: 0 }.string(SAVE_STRING_SIZE).ok()
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct SaveGame {
+    pub description: String,
+    pub map_name: String,
+    pub skill: Skill,
+    pub game_tic: u64,
+    pub player: Player
+}
+
+impl SaveGame {
+    // G_DoSaveGame
+    pub fn save(&self, dir: &Path, slot: usize) -> Result<()> {
+        fs::write(save_path(dir, slot), self.encode())?;
+        Ok(())
+    }
+
+    // G_DoLoadGame
+    pub fn load(dir: &Path, slot: usize) -> Result<Self> {
+        Self::decode(&fs::read(save_path(dir, slot))?)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        let player = &self.player;
+
+        put_string(&mut bytes, &self.description, SAVE_STRING_SIZE);
+        put_string(&mut bytes, VERSION, VERSION_SIZE);
+        put_string(&mut bytes, &self.map_name, MAP_NAME_SIZE);
+        bytes.push(self.skill as u8);
+        bytes.extend_from_slice(&self.game_tic.to_le_bytes());
+
+        // P_ArchivePlayers
+        bytes.extend_from_slice(&player.position.x.to_le_bytes());
+        bytes.extend_from_slice(&player.position.y.to_le_bytes());
+        bytes.extend_from_slice(&player.angle.0.to_le_bytes());
+
+        let counts = [
+            player.health,
+            player.armor_points,
+            player.armor_type,
+            player.extra_light,
+            player.fixed_colormap.map_or(-1, |colormap| colormap as i32),
+            player.damage_count,
+            player.bonus_count
+        ];
+
+        for count in counts.iter().chain(&player.powers).chain(&player.ammo).chain(&player.max_ammo) {
+            bytes.extend_from_slice(&count.to_le_bytes());
+        }
+
+        bytes.push(player.ready_weapon as u8);
+        bytes.extend(player.weapons.iter().chain(&player.cards).map(|&owned| owned as u8));
+        bytes.push(player.god_mode as u8);
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut reader = SaveReader { bytes, offset: 0 };
+        let description = reader.string(SAVE_STRING_SIZE)?;
+
+        if reader.string(VERSION_SIZE)? != VERSION {
+            bail!("Savegame is from a different version");
+        }
+
+        let map_name = reader.string(MAP_NAME_SIZE)?;
+        let skill = *Skill::ALL.get(reader.u8()? as usize).ok_or_else(|| anyhow!("Savegame has a bad skill"))?;
+        let game_tic = u64::from_le_bytes(reader.take()?);
+
+        let mut player = Player::new(1);
+        player.position = Point { x: i16::from_le_bytes(reader.take()?), y: i16::from_le_bytes(reader.take()?) };
+        player.angle = Bam(u32::from_le_bytes(reader.take()?));
+        player.health = reader.i32()?;
+        player.armor_points = reader.i32()?;
+        player.armor_type = reader.i32()?;
+        player.extra_light = reader.i32()?;
+        player.fixed_colormap = usize::try_from(reader.i32()?).ok();
+        player.damage_count = reader.i32()?;
+        player.bonus_count = reader.i32()?;
+
+        for i in 0..Power::COUNT {
+            player.powers[i] = reader.i32()?;
+        }
+
+        for i in 0..player.ammo.len() {
+            player.ammo[i] = reader.i32()?;
+        }
+
+        for i in 0..player.max_ammo.len() {
+            player.max_ammo[i] = reader.i32()?;
+        }
+
+        player.ready_weapon = *Weapon::ALL.get(reader.u8()? as usize).ok_or_else(|| anyhow!("Savegame has a bad weapon"))?;
+
+        for i in 0..Weapon::COUNT {
+            player.weapons[i] = reader.u8()? != 0;
+        }
+
+        for i in 0..Card::COUNT {
+            player.cards[i] = reader.u8()? != 0;
+        }
+
+        player.god_mode = reader.u8()? != 0;
+
+        Ok(Self { description, map_name, skill, game_tic, player })
+    }
+}
+
+// Zero padded, and cut short if it doesn't fit.
+fn put_string(bytes: &mut Vec<u8>, text: &str, size: usize) {
+    let mut field = vec![0u8; size];
+    let length = text.len().min(size);
+
+    field[..length].copy_from_slice(&text.as_bytes()[..length]);
+    bytes.extend_from_slice(&field);
+}
+
+struct SaveReader<'a> {
+    bytes: &'a [u8],
+    offset: usize
+}
+
+impl SaveReader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let field = self.bytes.get(self.offset..self.offset + N).ok_or_else(|| anyhow!("Savegame is truncated"))?;
+        self.offset += N;
+
+        Ok(field.try_into()?)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn i32(&mut self) -> Result<i32> {
+        Ok(i32::from_le_bytes(self.take()?))
+    }
+
+    fn string(&mut self, size: usize) -> Result<String> {
+        let field = self.bytes.get(self.offset..self.offset + size).ok_or_else(|| anyhow!("Savegame is truncated"))?;
+        self.offset += size;
+
+        let length = field.iter().position(|&byte| byte == 0).unwrap_or(size);
+        Ok(String::from_utf8_lossy(&field[..length]).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn save() -> SaveGame {
+        let mut player = Player::new(1);
+
+        player.position = Point { x: -1024, y: 3072 };
+        player.angle = Bam(0x4000_0000);
+        player.health = 37;
+        player.armor_points = 200;
+        player.armor_type = 2;
+        player.extra_light = 1;
+        player.fixed_colormap = Some(32);
+        player.damage_count = 12;
+        player.bonus_count = 6;
+        player.powers[Power::IronFeet as usize] = 700;
+        player.ammo = [10, 20, 30, 40];
+        player.max_ammo = [400, 100, 600, 100];
+        player.ready_weapon = Weapon::SuperShotgun;
+        player.weapons[Weapon::SuperShotgun as usize] = true;
+        player.cards[Card::RedSkull as usize] = true;
+        player.god_mode = true;
+
+        SaveGame { description: "BEFORE THE BOSS".to_string(), map_name: "MAP07".to_string(), skill: Skill::Hard, game_tic: 35 * 60, player }
+    }
+
+    #[test]
+    fn saves_come_back_as_they_went() {
+        let (before, after) = (save(), SaveGame::decode(&save().encode()).unwrap());
+        let (a, b) = (&before.player, &after.player);
+
+        assert_eq!((&after.description, &after.map_name, after.skill, after.game_tic), (&before.description, &before.map_name, before.skill, before.game_tic));
+        assert_eq!((a.position.x, a.position.y, a.angle.0), (b.position.x, b.position.y, b.angle.0));
+        assert_eq!((a.health, a.armor_points, a.armor_type, a.extra_light), (b.health, b.armor_points, b.armor_type, b.extra_light));
+        assert_eq!((a.fixed_colormap, a.damage_count, a.bonus_count, a.powers), (b.fixed_colormap, b.damage_count, b.bonus_count, b.powers));
+        assert_eq!((a.ammo, a.max_ammo, a.ready_weapon), (b.ammo, b.max_ammo, b.ready_weapon));
+        assert_eq!((a.weapons, a.cards, a.god_mode), (b.weapons, b.cards, b.god_mode));
+    }
+
+    #[test]
+    fn no_fixed_colormap_survives_a_save() {
+        let mut game = save();
+        game.player.fixed_colormap = None;
+
+        assert_eq!(SaveGame::decode(&game.encode()).unwrap().player.fixed_colormap, None);
+    }
+
+    #[test]
+    fn long_descriptions_are_cut_short() {
+        let mut game = save();
+        game.description = "X".repeat(40);
+
+        assert_eq!(SaveGame::decode(&game.encode()).unwrap().description, "X".repeat(SAVE_STRING_SIZE));
+    }
+
+    #[test]
+    fn truncated_saves_are_refused() {
+        let bytes = save().encode();
+
+        for length in [0, SAVE_STRING_SIZE, SAVE_STRING_SIZE + VERSION_SIZE + 4, bytes.len() - 1] {
+            let error = SaveGame::decode(&bytes[..length]).unwrap_err();
+            assert_eq!(error.to_string(), "Savegame is truncated");
+        }
+    }
+
+    #[test]
+    fn other_versions_are_refused() {
+        let mut bytes = save().encode();
+        bytes[SAVE_STRING_SIZE..SAVE_STRING_SIZE + 9].copy_from_slice(b"version 1");
+
+        let error = SaveGame::decode(&bytes).unwrap_err();
+        assert_eq!(error.to_string(), "Savegame is from a different version");
+    }
+}
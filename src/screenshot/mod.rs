@@ -0,0 +1,83 @@
+use std::path::{Path, PathBuf};
+use anyhow::{bail, Result};
+use crate::backend::DrawTarget;
+use crate::wad::Palette;
+
+mod png;
+pub use png::encode_png;
+
+mod pcx;
+pub use pcx::encode_pcx;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum ScreenshotFormat {
+    #[default]
+    Png,
+    Pcx
+}
+
+impl ScreenshotFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "png" => Some(Self::Png),
+            "pcx" => Some(Self::Pcx),
+            _ => None
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Pcx => "pcx"
+        }
+    }
+}
+
+// The target's palette indices, shown through `palette`.
+pub fn encode_screenshot(target: &dyn DrawTarget, palette: &Palette, format: ScreenshotFormat) -> Result<Vec<u8>> {
+    let (width, height) = (target.width().max(0) as u32, target.height().max(0) as u32);
+    let mut pixels = vec![0; (width * height) as usize];
+
+    for (y, row) in pixels.chunks_exact_mut(width.max(1) as usize).enumerate() {
+        target.read_row(0, y as i32, row);
+    }
+
+    match format {
+        ScreenshotFormat::Png => encode_png(width, height, &pixels, palette),
+        ScreenshotFormat::Pcx => encode_pcx(width, height, &pixels, palette)
+    }
+}
+
+// M_ScreenShot's naming: the first of DOOM00 to DOOM99 that isn't taken yet.
+pub fn next_screenshot_path(dir: &Path, format: ScreenshotFormat) -> Result<PathBuf> {
+    for i in 0..100 {
+        let path = dir.join(format!("DOOM{:02}.{}", i, format.extension()));
+
+        if !path.exists() {
+            return Ok(path);
+        }
+    }
+
+    bail!("Couldn't create a screenshot, DOOM00 to DOOM99 are all taken in {}", dir.display())
+}
+
+pub fn save_screenshot(target: &dyn DrawTarget, palette: &Palette, dir: &Path, format: ScreenshotFormat) -> Result<PathBuf> {
+    let path = next_screenshot_path(dir, format)?;
+    std::fs::write(&path, encode_screenshot(target, palette, format)?)?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_frames_are_refused() {
+        let palette: Palette = [[0; 3]; 256];
+
+        assert!(encode_png(0, 10, &[], &palette).is_err());
+        assert!(encode_pcx(10, 0, &[], &palette).is_err());
+        assert!(encode_pcx(2, 1, &[1, 1], &palette).is_ok());
+    }
+}
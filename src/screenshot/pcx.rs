@@ -0,0 +1,99 @@
+use anyhow::{bail, Result};
+use crate::wad::Palette;
+
+// WritePCXfile: a version 5, 8-bit, single plane PCX, run length coded row by row, with
+// the 256 color palette appended after a 0x0c marker.
+pub fn encode_pcx(width: u32, height: u32, pixels: &[u8], palette: &Palette) -> Result<Vec<u8>> {
+    if width == 0 || height == 0 {
+        bail!("Can't save an empty {}x{} screenshot", width, height);
+    }
+
+    let mut pcx = Vec::with_capacity(128 + pixels.len() + 769);
+
+    pcx.extend_from_slice(&[0x0a, 5, 1, 8]);
+
+    for value in [0, 0, width - 1, height - 1, width, height] {
+        pcx.extend_from_slice(&(value as u16).to_le_bytes());
+    }
+
+    pcx.extend_from_slice(&[0; 48]);
+    pcx.extend_from_slice(&[0, 1]);
+    pcx.extend_from_slice(&(width as u16).to_le_bytes());
+    pcx.extend_from_slice(&2u16.to_le_bytes());
+    pcx.resize(128, 0);
+
+    for row in pixels.chunks_exact(width as usize) {
+        let mut i = 0;
+
+        while i < row.len() {
+            let color = row[i];
+            let run = row[i..].iter().take(63).take_while(|&&pixel| pixel == color).count();
+
+            // A byte with its top two bits set would read as a run count, so it gets one.
+            if run > 1 || color & 0xc0 == 0xc0 {
+                pcx.push(0xc0 | run as u8);
+            }
+
+            pcx.push(color);
+            i += run;
+        }
+    }
+
+    pcx.push(0x0c);
+    pcx.extend_from_slice(palette.as_flattened());
+    Ok(pcx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The pixels back from PCX's run length coding.
+    fn decode(mut data: &[u8]) -> Vec<u8> {
+        let mut pixels = Vec::new();
+
+        while let [byte, rest @ ..] = data {
+            if byte & 0xc0 == 0xc0 {
+                pixels.extend(std::iter::repeat_n(rest[0], (byte & 0x3f) as usize));
+                data = &rest[1..];
+            } else {
+                pixels.push(*byte);
+                data = rest;
+            }
+        }
+
+        pixels
+    }
+
+    #[test]
+    fn long_runs_and_high_bytes_are_coded() {
+        let mut pixels = vec![5; 100];
+        pixels.extend([0xc0, 0xff, 0x3f, 0x3f]);
+        pixels.extend(vec![0xc7; 70]);
+
+        let palette: Palette = std::array::from_fn(|i| [i as u8, 0, 0]);
+        let pcx = encode_pcx(pixels.len() as u32, 1, &pixels, &palette).unwrap();
+        let body = &pcx[128..pcx.len() - 769];
+
+        assert_eq!(body, [
+            0xff, 5, 0xe5, 5,
+            0xc1, 0xc0, 0xc1, 0xff, 0xc2, 0x3f,
+            0xff, 0xc7, 0xc7, 0xc7
+        ]);
+        assert_eq!(decode(body), pixels);
+
+        assert_eq!(&pcx[..4], [0x0a, 5, 1, 8]);
+        assert_eq!(u16::from_le_bytes([pcx[8], pcx[9]]), 173);
+        assert_eq!(u16::from_le_bytes([pcx[66], pcx[67]]), 174);
+        assert_eq!(pcx[pcx.len() - 769], 0x0c);
+        assert_eq!(pcx[pcx.len() - 3], 255);
+    }
+
+    #[test]
+    fn runs_stop_at_the_end_of_a_row() {
+        let pixels = [3, 3, 3, 3, 3, 3];
+        let pcx = encode_pcx(3, 2, &pixels, &[[0; 3]; 256]).unwrap();
+
+        assert_eq!(&pcx[128..pcx.len() - 769], [0xc3, 3, 0xc3, 3]);
+    }
+}
@@ -0,0 +1,352 @@
+use anyhow::{bail, Result};
+use crate::wad::Palette;
+
+// Paletted PNG with its own deflate: LZ77 over a 32K window, coded with the fixed Huffman
+// tables. Not as small as a real zlib, but a Doom frame has plenty of repeats to find.
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258
+];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073,
+    4097, 6145, 8193, 12289, 16385, 24577
+];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+const WINDOW: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const HASH_BITS: u32 = 15;
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u32
+}
+
+impl BitWriter {
+    // Plain values go in least significant bit first.
+    fn bits(&mut self, value: u32, count: u32) {
+        self.buffer |= value << self.count;
+        self.count += count;
+
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    // Huffman codes go in most significant bit first.
+    fn code(&mut self, code: u32, length: u32) {
+        self.bits(code.reverse_bits() >> (32 - length), length);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+
+        self.bytes
+    }
+
+    fn literal(&mut self, symbol: u32) {
+        match symbol {
+            0..=143 => self.code(0x30 + symbol, 8),
+            144..=255 => self.code(0x190 + symbol - 144, 9),
+            256..=279 => self.code(symbol - 256, 7),
+            _ => self.code(0xc0 + symbol - 280, 8)
+        }
+    }
+
+    fn copy(&mut self, length: usize, distance: usize) {
+        let i = LENGTH_BASE.iter().rposition(|&base| base as usize <= length).unwrap_or(0);
+        self.literal(257 + i as u32);
+        self.bits((length - LENGTH_BASE[i] as usize) as u32, LENGTH_EXTRA[i] as u32);
+
+        let i = DISTANCE_BASE.iter().rposition(|&base| base as usize <= distance).unwrap_or(0);
+        self.code(i as u32, 5);
+        self.bits((distance - DISTANCE_BASE[i] as usize) as u32, DISTANCE_EXTRA[i] as u32);
+    }
+}
+
+fn hash(data: &[u8]) -> usize {
+    let value = (data[0] as u32) << 16 | (data[1] as u32) << 8 | data[2] as u32;
+
+    (value.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+}
+
+// One final block of fixed Huffman codes, greedy matching against the last position each
+// three byte prefix was seen at.
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+    let mut last_seen = vec![usize::MAX; 1 << HASH_BITS];
+    let mut i = 0;
+
+    writer.bits(1, 1);
+    writer.bits(1, 2);
+
+    while i < data.len() {
+        let mut length = 0;
+        let mut distance = 0;
+
+        if i + MIN_MATCH <= data.len() {
+            let key = hash(&data[i..]);
+            let candidate = last_seen[key];
+            last_seen[key] = i;
+
+            if candidate != usize::MAX && i - candidate <= WINDOW {
+                let limit = (data.len() - i).min(MAX_MATCH);
+                length = (0..limit).take_while(|&n| data[candidate + n] == data[i + n]).count();
+                distance = i - candidate;
+            }
+        }
+
+        if length >= MIN_MATCH {
+            writer.copy(length, distance);
+
+            for j in i + 1..(i + length).min(data.len().saturating_sub(MIN_MATCH - 1)) {
+                last_seen[hash(&data[j..])] = j;
+            }
+
+            i += length;
+        } else {
+            writer.literal(data[i] as u32);
+            i += 1;
+        }
+    }
+
+    writer.literal(256);
+    writer.finish()
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+
+        a %= 65521;
+        b %= 65521;
+    }
+
+    b << 16 | a
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+
+    for &byte in data {
+        crc ^= byte as u32;
+
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb88320 } else { crc >> 1 };
+        }
+    }
+
+    !crc
+}
+
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+// An 8-bit indexed PNG of `pixels`, row by row, with the palette as its PLTE.
+// PNG has no empty images.
+pub fn encode_png(width: u32, height: u32, pixels: &[u8], palette: &Palette) -> Result<Vec<u8>> {
+    if width == 0 || height == 0 {
+        bail!("Can't save an empty {}x{} screenshot", width, height);
+    }
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&[8, 3, 0, 0, 0]);
+    chunk(&mut png, b"IHDR", &header);
+
+    chunk(&mut png, b"PLTE", palette.as_flattened());
+
+    // Every row gets filter type 0, no filtering.
+    let mut rows = Vec::with_capacity(pixels.len() + height as usize);
+
+    for row in pixels.chunks_exact(width as usize) {
+        rows.push(0);
+        rows.extend_from_slice(row);
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    zlib.extend(deflate(&rows));
+    zlib.extend_from_slice(&adler32(&rows).to_be_bytes());
+    chunk(&mut png, b"IDAT", &zlib);
+
+    chunk(&mut png, b"IEND", &[]);
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Reads deflate's bits, least significant first.
+    struct BitReader<'a> {
+        bytes: &'a [u8],
+        position: usize
+    }
+
+    impl BitReader<'_> {
+        fn bits(&mut self, count: u32) -> u32 {
+            (0..count).fold(0, |value, i| {
+                let bit = (self.bytes[self.position / 8] >> (self.position % 8)) & 1;
+                self.position += 1;
+                value | (bit as u32) << i
+            })
+        }
+
+        // Huffman codes come most significant bit first.
+        fn code(&mut self, length: u32) -> u32 {
+            (0..length).fold(0, |code, _| code << 1 | self.bits(1))
+        }
+
+        fn literal(&mut self) -> u32 {
+            let code = self.code(7);
+
+            if code <= 0x17 {
+                return 256 + code;
+            }
+
+            let code = code << 1 | self.bits(1);
+
+            match code {
+                0x30..=0xbf => code - 0x30,
+                0xc0..=0xc7 => 280 + code - 0xc0,
+                _ => 144 + (code << 1 | self.bits(1)) - 0x190
+            }
+        }
+    }
+
+    // Inflates a single block of fixed Huffman codes, noting the longest copy and the
+    // farthest distance it saw.
+    fn inflate(bytes: &[u8]) -> (Vec<u8>, usize, usize) {
+        let mut reader = BitReader { bytes, position: 0 };
+        let mut data: Vec<u8> = Vec::new();
+        let (mut longest, mut farthest) = (0, 0);
+
+        assert_eq!((reader.bits(1), reader.bits(2)), (1, 1), "one final fixed Huffman block");
+
+        loop {
+            let symbol = reader.literal();
+
+            match symbol {
+                0..=255 => data.push(symbol as u8),
+                256 => break,
+                _ => {
+                    let i = symbol as usize - 257;
+                    let length = LENGTH_BASE[i] as usize + reader.bits(LENGTH_EXTRA[i] as u32) as usize;
+                    let i = reader.code(5) as usize;
+                    let distance = DISTANCE_BASE[i] as usize + reader.bits(DISTANCE_EXTRA[i] as u32) as usize;
+
+                    for _ in 0..length {
+                        data.push(data[data.len() - distance]);
+                    }
+
+                    longest = longest.max(length);
+                    farthest = farthest.max(distance);
+                }
+            }
+        }
+
+        (data, longest, farthest)
+    }
+
+    // The chunks of a PNG, checking each one's CRC.
+    fn chunks(png: &[u8]) -> Vec<([u8; 4], &[u8])> {
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+
+        let mut chunks = Vec::new();
+        let mut offset = 8;
+
+        while offset < png.len() {
+            let length = u32::from_be_bytes(png[offset..offset + 4].try_into().unwrap()) as usize;
+            let body = &png[offset + 4..offset + 8 + length];
+            let crc = u32::from_be_bytes(png[offset + 8 + length..offset + 12 + length].try_into().unwrap());
+
+            assert_eq!(crc32(body), crc);
+            chunks.push((body[..4].try_into().unwrap(), &body[4..]));
+            offset += length + 12;
+        }
+
+        chunks
+    }
+
+    // Noise from a small LCG, which LZ77 can't find anything in.
+    fn noise(length: usize) -> Vec<u8> {
+        let mut seed = 12345u32;
+
+        (0..length)
+            .map(|_| {
+                seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                (seed >> 16) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn deflate_round_trips() {
+        for data in [Vec::new(), b"a".to_vec(), b"abcabcabcabcabc".to_vec(), noise(1000), vec![7; 1000]] {
+            assert_eq!(inflate(&deflate(&data)).0, data);
+        }
+    }
+
+    #[test]
+    fn known_checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae426082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+    }
+
+    #[test]
+    fn pngs_decode_to_their_pixels() {
+        // A row of noise, the same row again 5001 bytes later, and a flat row.
+        let width = 5000;
+        let row = noise(width);
+        let pixels = [row.clone(), row, vec![0xc4; width]].concat();
+
+        let mut palette: Palette = [[0; 3]; 256];
+        palette[1] = [255, 0, 0];
+
+        let png = encode_png(width as u32, 3, &pixels, &palette).unwrap();
+        let chunks = chunks(&png);
+        let kinds: Vec<&[u8; 4]> = chunks.iter().map(|(kind, _)| kind).collect();
+
+        assert_eq!(kinds, [b"IHDR", b"PLTE", b"IDAT", b"IEND"]);
+        assert_eq!(chunks[0].1, [0, 0, 0x13, 0x88, 0, 0, 0, 3, 8, 3, 0, 0, 0]);
+        assert_eq!(&chunks[1].1[3..6], [255, 0, 0]);
+
+        let zlib = chunks[2].1;
+        assert_eq!(u16::from_be_bytes([zlib[0], zlib[1]]) % 31, 0);
+
+        let (rows, longest, farthest) = inflate(&zlib[2..zlib.len() - 4]);
+        assert_eq!(u32::from_be_bytes(zlib[zlib.len() - 4..].try_into().unwrap()), adler32(&rows));
+        assert_eq!((longest, farthest), (MAX_MATCH, width + 1));
+
+        let filters: Vec<u8> = rows.iter().step_by(width + 1).copied().collect();
+        assert_eq!(filters, [0, 0, 0]);
+
+        let decoded: Vec<u8> = rows.chunks_exact(width + 1).flat_map(|row| row[1..].to_vec()).collect();
+        assert_eq!(decoded, pixels);
+    }
+}
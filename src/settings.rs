@@ -0,0 +1,30 @@
+pub const MAX_MOUSE_SENSITIVITY: i32 = 9;
+pub const MAX_VOLUME: i32 = 15;
+pub const MIN_SCREEN_BLOCKS: i32 = 3;
+pub const MAX_SCREEN_BLOCKS: i32 = 11;
+
+// What the options menus change, with the defaults from m_misc.c.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Settings {
+    pub mouse_sensitivity: i32,
+    pub sfx_volume: i32,
+    pub music_volume: i32,
+    pub show_messages: bool,
+    // screenblocks: 11 is the whole screen, 10 leaves room for the status bar and fewer
+    // shrink the view into a bordered window.
+    pub screen_blocks: i32,
+    pub low_detail: bool
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            mouse_sensitivity: 5,
+            sfx_volume: 8,
+            music_volume: 8,
+            show_messages: true,
+            screen_blocks: 9,
+            low_detail: false
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use std::fmt;
+use serde::Serialize;
+use crate::map::GameMap;
+use crate::things::{thing_info, AmmoType, ThingKind};
+use crate::wad::Thing;
+
+const SECRET_SECTOR_SPECIAL: i16 = 9;
+
+// P_GiveWeapon: a weapon pickup holds two clips, five in deathmatch and one if a monster
+// dropped it.
+const WEAPON_CLIPS: u32 = 2;
+const WEAPON_CLIPS_DEATHMATCH: u32 = 5;
+const WEAPON_CLIPS_DROPPED: u32 = 1;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Skill {
+    Baby,
+    Easy,
+    Medium,
+    Hard,
+    Nightmare
+}
+
+impl Skill {
+    pub const ALL: [Skill; 5] = [Skill::Baby, Skill::Easy, Skill::Medium, Skill::Hard, Skill::Nightmare];
+
+    fn thing_flag(&self) -> i16 {
+        match self {
+            Skill::Baby | Skill::Easy => Thing::SKILL_EASY,
+            Skill::Medium => Thing::SKILL_MEDIUM,
+            Skill::Hard | Skill::Nightmare => Thing::SKILL_HARD
+        }
+    }
+
+    fn ammo_multiplier(&self) -> u32 {
+        match self {
+            Skill::Baby | Skill::Nightmare => 2,
+            _ => 1
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameMode {
+    SinglePlayer,
+    Cooperative,
+    Deathmatch
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 3] = [GameMode::SinglePlayer, GameMode::Cooperative, GameMode::Deathmatch];
+}
+
+#[derive(Debug, Default, Copy, Clone, Serialize)]
+pub struct AmmoTotals {
+    pub bullets: u32,
+    pub shells: u32,
+    pub rockets: u32,
+    pub cells: u32
+}
+
+impl AmmoTotals {
+    fn add(&mut self, ammo: AmmoType, amount: u32) {
+        match ammo {
+            AmmoType::Bullets => self.bullets += amount,
+            AmmoType::Shells => self.shells += amount,
+            AmmoType::Rockets => self.rockets += amount,
+            AmmoType::Cells => self.cells += amount
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, Serialize)]
+pub struct ThingStats {
+    pub monsters: u32,
+    pub monster_hp: u32,
+    pub items: u32,
+    pub weapons: u32,
+    pub ammo: AmmoTotals
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SkillStats {
+    pub skill: Skill,
+    pub mode: GameMode,
+    #[serde(flatten)]
+    pub stats: ThingStats
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MapStats {
+    pub map: String,
+    pub secrets: u32,
+    pub entries: Vec<SkillStats>
+}
+
+pub fn map_stats(map: &GameMap) -> MapStats {
+    let secrets = map.sectors.iter()
+        .filter(|sector| sector.special == SECRET_SECTOR_SPECIAL)
+        .count() as u32;
+
+    let entries = GameMode::ALL.iter()
+        .flat_map(|&mode| Skill::ALL.iter().map(move |&skill| SkillStats {
+            skill,
+            mode,
+            stats: thing_stats(&map.things, skill, mode)
+        }))
+        .collect();
+
+    MapStats {
+        map: map.map_name.clone(),
+        secrets,
+        entries
+    }
+}
+
+pub fn thing_stats(things: &[Thing], skill: Skill, mode: GameMode) -> ThingStats {
+    let mut stats = ThingStats::default();
+    let multiplier = skill.ammo_multiplier();
+
+    let spawned = things.iter()
+        .filter(|thing| thing.has_flag(skill.thing_flag()))
+        .filter(|thing| mode != GameMode::SinglePlayer || !thing.has_flag(Thing::NOT_SINGLE_PLAYER))
+        .filter_map(|thing| thing_info(thing.ed_type))
+        .filter(|info| mode != GameMode::Deathmatch || info.spawns_in_deathmatch());
+
+    for info in spawned {
+        if info.is_item() {
+            stats.items += 1;
+        }
+
+        match info.kind {
+            ThingKind::Monster { health } => {
+                if info.counts_kill() {
+                    stats.monsters += 1;
+                }
+
+                stats.monster_hp += health;
+            },
+            ThingKind::Weapon { ammo } => {
+                stats.weapons += 1;
+
+                // Things placed on the map are never dropped ones.
+                if let Some(ammo) = ammo {
+                    stats.ammo.add(ammo, weapon_ammo(ammo, skill, mode, false));
+                }
+            },
+            ThingKind::Ammo { ammo, amount } => stats.ammo.add(ammo, amount * multiplier),
+            ThingKind::Backpack => {
+                for ammo in AmmoType::ALL {
+                    stats.ammo.add(ammo, ammo.clip_amount() * multiplier);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    stats
+}
+
+// P_GiveWeapon's ammo, doubled by P_GiveAmmo on the easiest and hardest skills.
+fn weapon_ammo(ammo: AmmoType, skill: Skill, mode: GameMode, dropped: bool) -> u32 {
+    let clips = match mode {
+        _ if dropped => WEAPON_CLIPS_DROPPED,
+        GameMode::Deathmatch => WEAPON_CLIPS_DEATHMATCH,
+        _ => WEAPON_CLIPS
+    };
+
+    ammo.clip_amount() * clips * skill.ammo_multiplier()
+}
+
+impl fmt::Display for Skill {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Skill::Baby => write!(f, "ITYTD"),
+            Skill::Easy => write!(f, "HNTR"),
+            Skill::Medium => write!(f, "HMP"),
+            Skill::Hard => write!(f, "UV"),
+            Skill::Nightmare => write!(f, "NM")
+        }
+    }
+}
+
+impl fmt::Display for GameMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameMode::SinglePlayer => write!(f, "single"),
+            GameMode::Cooperative => write!(f, "coop"),
+            GameMode::Deathmatch => write!(f, "dm")
+        }
+    }
+}
+
+impl fmt::Display for MapStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} ({} secrets)", self.map, self.secrets)?;
+        writeln!(
+            f, "  {:<6} {:<5} {:>8} {:>10} {:>6} {:>7} {:>7} {:>6} {:>7} {:>5}",
+            "mode", "skill", "monsters", "monster hp", "items", "weapons", "bullets", "shells", "rockets", "cells"
+        )?;
+
+        for entry in &self.entries {
+            let stats = &entry.stats;
+
+            writeln!(
+                f, "  {:<6} {:<5} {:>8} {:>10} {:>6} {:>7} {:>7} {:>6} {:>7} {:>5}",
+                entry.mode.to_string(), entry.skill.to_string(), stats.monsters, stats.monster_hp, stats.items,
+                stats.weapons, stats.ammo.bullets, stats.ammo.shells, stats.ammo.rockets, stats.ammo.cells
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad::{Point, Sector};
+
+    const ALL_SKILLS: i16 = Thing::SKILL_EASY | Thing::SKILL_MEDIUM | Thing::SKILL_HARD;
+
+    fn thing(ed_type: i16, flags: i16) -> Thing {
+        Thing { position: Point { x: 0, y: 0 }, angle: 0, ed_type, flags }
+    }
+
+    #[test]
+    fn things_spawn_by_skill_and_mode() {
+        let things = [
+            thing(3004, Thing::SKILL_EASY),
+            thing(3004, Thing::SKILL_MEDIUM),
+            thing(3004, Thing::SKILL_HARD),
+            thing(3004, Thing::SKILL_HARD | Thing::NOT_SINGLE_PLAYER),
+            thing(3004, ALL_SKILLS)
+        ];
+
+        let monsters = |skill, mode| thing_stats(&things, skill, mode).monsters;
+
+        assert_eq!(monsters(Skill::Baby, GameMode::SinglePlayer), 2);
+        assert_eq!(monsters(Skill::Easy, GameMode::SinglePlayer), 2);
+        assert_eq!(monsters(Skill::Medium, GameMode::SinglePlayer), 2);
+        assert_eq!(monsters(Skill::Hard, GameMode::SinglePlayer), 2);
+        assert_eq!(monsters(Skill::Nightmare, GameMode::SinglePlayer), 2);
+        assert_eq!(monsters(Skill::Hard, GameMode::Cooperative), 3);
+        assert_eq!(monsters(Skill::Hard, GameMode::Deathmatch), 3);
+        assert_eq!(monsters(Skill::Medium, GameMode::Cooperative), 2);
+    }
+
+    #[test]
+    fn kills_and_items_follow_their_flags() {
+        let things = [
+            thing(3004, ALL_SKILLS),
+            thing(3006, ALL_SKILLS),
+            thing(2014, ALL_SKILLS),
+            thing(2011, ALL_SKILLS),
+            thing(5, ALL_SKILLS)
+        ];
+
+        let stats = thing_stats(&things, Skill::Medium, GameMode::SinglePlayer);
+
+        // Lost souls aren't counted as kills but still have their health counted.
+        assert_eq!((stats.monsters, stats.monster_hp), (1, 120));
+        // Health bonuses count as items, stimpacks don't.
+        assert_eq!(stats.items, 1);
+    }
+
+    #[test]
+    fn weapons_hold_clips_like_p_giveweapon() {
+        // (skill, mode, dropped, shells)
+        for (skill, mode, dropped, shells) in [
+            (Skill::Medium, GameMode::SinglePlayer, false, 8),
+            (Skill::Medium, GameMode::SinglePlayer, true, 4),
+            (Skill::Medium, GameMode::Cooperative, false, 8),
+            (Skill::Medium, GameMode::Deathmatch, false, 20),
+            (Skill::Medium, GameMode::Deathmatch, true, 4),
+            (Skill::Baby, GameMode::SinglePlayer, false, 16),
+            (Skill::Baby, GameMode::SinglePlayer, true, 8),
+            (Skill::Nightmare, GameMode::Deathmatch, false, 40),
+            (Skill::Hard, GameMode::SinglePlayer, false, 8)
+        ] {
+            assert_eq!(weapon_ammo(AmmoType::Shells, skill, mode, dropped), shells, "{} {} dropped: {}", skill, mode, dropped);
+        }
+    }
+
+    #[test]
+    fn map_stats_add_up_ammo_and_secrets() {
+        let mut map = GameMap::with_name("E1M1");
+
+        map.things = vec![thing(2001, ALL_SKILLS), thing(2008, ALL_SKILLS), thing(8, ALL_SKILLS)];
+        map.sectors = [0, 9, 9]
+            .map(|special| Sector {
+                floor_height: 0,
+                ceiling_height: 128,
+                floor_texture: "FLAT".to_string(),
+                ceiling_texture: "FLAT".to_string(),
+                light_level: 160,
+                special,
+                tag: 0
+            })
+            .to_vec();
+
+        let stats = map_stats(&map);
+        assert_eq!(stats.secrets, 2);
+        assert_eq!(stats.entries.len(), GameMode::ALL.len() * Skill::ALL.len());
+
+        let ammo = |skill, mode| {
+            let entry = stats.entries.iter().find(|entry| entry.skill == skill && entry.mode == mode).unwrap();
+            (entry.stats.weapons, entry.stats.ammo.shells, entry.stats.ammo.bullets)
+        };
+
+        // A shotgun's two clips, a box of 4 shells and a backpack's clip of everything.
+        assert_eq!(ammo(Skill::Medium, GameMode::SinglePlayer), (1, 8 + 4 + 4, 10));
+        assert_eq!(ammo(Skill::Nightmare, GameMode::SinglePlayer), (1, 32, 20));
+        assert_eq!(ammo(Skill::Medium, GameMode::Deathmatch), (1, 20 + 4 + 4, 10));
+    }
+}
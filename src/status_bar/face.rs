@@ -0,0 +1,311 @@
+use crate::engine::TIC_RATE;
+use crate::math::{point_to_angle2, Fixed, ANG180, ANG45};
+use crate::player::{Player, Power, Weapon};
+
+// Each of the five pain levels has 3 straight faces, turned right and left, ouch, evil grin
+// and rampage, in that order. God mode and dead come after the last level.
+pub(super) const PAIN_FACES: usize = 5;
+pub(super) const STRAIGHT_FACES: usize = 3;
+const FACE_STRIDE: usize = 8;
+pub(super) const FACE_COUNT: usize = PAIN_FACES * FACE_STRIDE + 2;
+
+const TURN_OFFSET: usize = STRAIGHT_FACES;
+const OUCH_OFFSET: usize = TURN_OFFSET + 2;
+const EVIL_GRIN_OFFSET: usize = OUCH_OFFSET + 1;
+const RAMPAGE_OFFSET: usize = EVIL_GRIN_OFFSET + 1;
+const GOD_FACE: usize = PAIN_FACES * FACE_STRIDE;
+const DEAD_FACE: usize = GOD_FACE + 1;
+
+const STRAIGHT_FACE_TICS: i32 = TIC_RATE as i32 / 2;
+const TURN_TICS: i32 = TIC_RATE as i32;
+const EVIL_GRIN_TICS: i32 = 2 * TIC_RATE as i32;
+const RAMPAGE_DELAY: i32 = 2 * TIC_RATE as i32;
+const MUCH_PAIN: i32 = 20;
+
+// The marine's face: whichever is the most important thing happening to the player holds
+// the face until it times out or something more important comes along.
+pub(super) struct Face {
+    pub index: usize,
+    count: i32,
+    priority: i32,
+    last_attack_down: i32,
+    old_health: i32,
+    old_weapons: [bool; Weapon::COUNT],
+    pain_health: i32,
+    pain_offset: usize
+}
+
+impl Face {
+    // ST_initData
+    pub fn new(player: &Player) -> Self {
+        Self {
+            index: 0,
+            count: 0,
+            priority: 0,
+            last_attack_down: -1,
+            old_health: -1,
+            old_weapons: player.weapons,
+            pain_health: -1,
+            pain_offset: 0
+        }
+    }
+
+    // ST_calcPainOffset: the row of faces for the current health, bloodier as it drops.
+    fn pain_offset(&mut self, player: &Player) -> usize {
+        let health = player.health.min(100);
+
+        if health != self.pain_health {
+            self.pain_offset = FACE_STRIDE * (((100 - health) * PAIN_FACES as i32) / 101) as usize;
+            self.pain_health = health;
+        }
+
+        self.pain_offset
+    }
+
+    // ST_updateFaceWidget, then ST_Ticker's note of the health for next time.
+    pub fn tick(&mut self, player: &Player, random: i32) {
+        if self.priority < 10 && player.is_dead() {
+            self.priority = 9;
+            self.index = DEAD_FACE;
+            self.count = 1;
+        }
+
+        // A new weapon was picked up.
+        if self.priority < 9 && player.bonus_count != 0 && self.old_weapons != player.weapons {
+            self.old_weapons = player.weapons;
+            self.priority = 8;
+            self.count = EVIL_GRIN_TICS;
+            self.index = self.pain_offset(player) + EVIL_GRIN_OFFSET;
+        }
+
+        // Being attacked: look towards whoever did it.
+        if self.priority < 8 && player.damage_count != 0 {
+            if let Some(attacker) = player.attacker {
+                self.priority = 7;
+                self.count = TURN_TICS;
+
+                // Vanilla's test is backwards, so the ouch face only shows when health went up.
+                if player.health - self.old_health > MUCH_PAIN {
+                    self.index = self.pain_offset(player) + OUCH_OFFSET;
+                } else {
+                    let bad_guy_angle = point_to_angle2(
+                        Fixed::from(player.position.x),
+                        Fixed::from(player.position.y),
+                        Fixed::from(attacker.x),
+                        Fixed::from(attacker.y)
+                    );
+
+                    let (difference, turn_right) = if bad_guy_angle > player.angle {
+                        (bad_guy_angle - player.angle, bad_guy_angle - player.angle > ANG180)
+                    } else {
+                        (player.angle - bad_guy_angle, player.angle - bad_guy_angle <= ANG180)
+                    };
+
+                    self.index = self.pain_offset(player) + if difference < ANG45 {
+                        RAMPAGE_OFFSET
+                    } else if turn_right {
+                        TURN_OFFSET
+                    } else {
+                        TURN_OFFSET + 1
+                    };
+                }
+            }
+        }
+
+        // Hurt by the level or their own rocket.
+        if self.priority < 7 && player.damage_count != 0 {
+            self.count = TURN_TICS;
+
+            if player.health - self.old_health > MUCH_PAIN {
+                self.priority = 7;
+                self.index = self.pain_offset(player) + OUCH_OFFSET;
+            } else {
+                self.priority = 6;
+                self.index = self.pain_offset(player) + RAMPAGE_OFFSET;
+            }
+        }
+
+        // Holding down fire for a while.
+        if self.priority < 6 {
+            if player.attack_down {
+                if self.last_attack_down == -1 {
+                    self.last_attack_down = RAMPAGE_DELAY;
+                } else {
+                    self.last_attack_down -= 1;
+
+                    if self.last_attack_down == 0 {
+                        self.priority = 5;
+                        self.index = self.pain_offset(player) + RAMPAGE_OFFSET;
+                        self.count = 1;
+                        self.last_attack_down = 1;
+                    }
+                }
+            } else {
+                self.last_attack_down = -1;
+            }
+        }
+
+        if self.priority < 5 && (player.god_mode || player.power(Power::Invulnerability) != 0) {
+            self.priority = 4;
+            self.index = GOD_FACE;
+            self.count = 1;
+        }
+
+        // Nothing going on: look around.
+        if self.count == 0 {
+            self.index = self.pain_offset(player) + (random % 3) as usize;
+            self.count = STRAIGHT_FACE_TICS;
+            self.priority = 0;
+        }
+
+        self.count -= 1;
+        self.old_health = player.health;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad::Point;
+
+    // What every test feeds as M_Random, which picks the second straight face.
+    const RANDOM: i32 = 4;
+
+    // A face that has seen one quiet tic of `player`, so it knows the old health.
+    fn settled(player: &Player) -> Face {
+        let mut face = Face::new(player);
+        face.tick(player, RANDOM);
+        face
+    }
+
+    fn attacked_from(x: i16, y: i16) -> usize {
+        let mut player = Player::new(1);
+        let mut face = settled(&player);
+
+        player.damage_count = 10;
+        player.attacker = Some(Point { x, y });
+        face.tick(&player, RANDOM);
+        face.index
+    }
+
+    #[test]
+    fn quiet_players_look_around() {
+        let mut player = Player::new(1);
+        let face = settled(&player);
+        assert_eq!(face.index, 1);
+
+        // Bloodier faces as health drops.
+        player.health = 30;
+        assert_eq!(settled(&player).index, 3 * FACE_STRIDE + 1);
+    }
+
+    #[test]
+    fn dead_players_look_dead() {
+        let mut player = Player::new(1);
+        player.health = 0;
+        player.god_mode = true;
+
+        assert_eq!(settled(&player).index, DEAD_FACE);
+    }
+
+    #[test]
+    fn new_weapons_get_an_evil_grin() {
+        let mut player = Player::new(1);
+        let mut face = settled(&player);
+
+        player.weapons[Weapon::Shotgun as usize] = true;
+        player.bonus_count = 6;
+        face.tick(&player, RANDOM);
+        assert_eq!(face.index, EVIL_GRIN_OFFSET);
+
+        // It holds until it times out, then goes back to a straight face.
+        for _ in 1..EVIL_GRIN_TICS {
+            face.tick(&player, RANDOM);
+            assert_eq!(face.index, EVIL_GRIN_OFFSET);
+        }
+
+        face.tick(&player, RANDOM);
+        assert_eq!(face.index, 1);
+    }
+
+    #[test]
+    fn ouch_only_shows_when_health_went_up() {
+        let mut player = Player::new(1);
+        player.health = 30;
+
+        let mut face = settled(&player);
+
+        player.health = 60;
+        player.damage_count = 10;
+        player.attacker = Some(Point { x: 0, y: 100 });
+        face.tick(&player, RANDOM);
+        assert_eq!(face.index, FACE_STRIDE + OUCH_OFFSET);
+
+        // Losing a lot of health doesn't.
+        let mut face = settled(&player);
+
+        player.health = 10;
+        face.tick(&player, RANDOM);
+        assert_ne!(face.index % FACE_STRIDE, OUCH_OFFSET);
+    }
+
+    #[test]
+    fn faces_turn_towards_the_attacker() {
+        // The player is at the origin facing east.
+        assert_eq!(attacked_from(0, 100), TURN_OFFSET + 1);
+        assert_eq!(attacked_from(0, -100), TURN_OFFSET);
+        assert_eq!(attacked_from(100, 10), RAMPAGE_OFFSET);
+        // Straight behind is exactly ANG180 away, which vanilla counts as the left.
+        assert_eq!(attacked_from(-100, 0), TURN_OFFSET + 1);
+        assert_eq!(attacked_from(-100, -10), TURN_OFFSET);
+    }
+
+    #[test]
+    fn hurting_yourself_is_a_rampage() {
+        let mut player = Player::new(1);
+        let mut face = settled(&player);
+
+        player.damage_count = 10;
+        face.tick(&player, RANDOM);
+        assert_eq!(face.index, RAMPAGE_OFFSET);
+    }
+
+    #[test]
+    fn holding_fire_starts_a_rampage() {
+        let mut player = Player::new(1);
+        let mut face = settled(&player);
+
+        player.attack_down = true;
+
+        for _ in 0..RAMPAGE_DELAY {
+            face.tick(&player, RANDOM);
+            assert_ne!(face.index, RAMPAGE_OFFSET);
+        }
+
+        face.tick(&player, RANDOM);
+        assert_eq!(face.index, RAMPAGE_OFFSET);
+
+        // Letting go ends it the next time the face changes.
+        player.attack_down = false;
+        face.tick(&player, RANDOM);
+        face.tick(&player, RANDOM);
+        assert_eq!(face.index, 1);
+    }
+
+    #[test]
+    fn invulnerable_players_get_the_god_face() {
+        let mut player = Player::new(1);
+        player.god_mode = true;
+        assert_eq!(settled(&player).index, GOD_FACE);
+
+        player.god_mode = false;
+        player.powers[Power::Invulnerability as usize] = 30 * TIC_RATE as i32;
+        assert_eq!(settled(&player).index, GOD_FACE);
+
+        // Anything more important than that wins.
+        let mut face = settled(&player);
+        player.damage_count = 10;
+        face.tick(&player, RANDOM);
+        assert_eq!(face.index, RAMPAGE_OFFSET);
+    }
+}
@@ -0,0 +1,255 @@
+use anyhow::Result;
+use crate::backend::{DrawTarget, BASE_HEIGHT, BASE_WIDTH};
+use crate::math::Random;
+use crate::player::{Card, Player};
+use crate::things::AmmoType;
+use crate::wad::{Flat, Loader, Patch};
+
+mod face;
+use face::{Face, FACE_COUNT, PAIN_FACES, STRAIGHT_FACES};
+
+// ST_HEIGHT: the bar covers the bottom 32 rows of the 320x200 screen.
+pub const STATUS_BAR_HEIGHT: i32 = 32;
+const STATUS_BAR_Y: i32 = BASE_HEIGHT as i32 - STATUS_BAR_HEIGHT;
+
+// Widget positions from st_stuff.c. Numbers are placed by their right edge.
+const AMMO_X: i32 = 44;
+const HEALTH_X: i32 = 90;
+const ARMOR_X: i32 = 221;
+const BIG_NUMBER_Y: i32 = 171;
+const ARMS_BACKGROUND_X: i32 = 104;
+const ARMS_BACKGROUND_Y: i32 = 168;
+const ARMS_X: i32 = 111;
+const ARMS_Y: i32 = 172;
+const ARMS_X_SPACE: i32 = 12;
+const ARMS_Y_SPACE: i32 = 10;
+const FACE_X: i32 = 143;
+const FACE_Y: i32 = 168;
+const KEYS_X: i32 = 239;
+const KEYS_Y: [i32; 3] = [171, 181, 191];
+const AMMO_COUNT_X: i32 = 288;
+const MAX_AMMO_X: i32 = 314;
+const AMMO_COUNT_Y: [i32; 4] = [173, 179, 185, 191];
+
+// The flat the screen border is tiled with.
+pub const BORDER_FLAT: &str = "FLOOR7_2";
+
+// The graphics of a row of digits.
+struct Font {
+    digits: Vec<Patch>,
+    minus: Option<Patch>
+}
+
+impl Font {
+    fn load(loader: &mut Loader, prefix: &str, minus: Option<&str>) -> Result<Self> {
+        Ok(
+            Self {
+                digits: (0..10).map(|digit| loader.load_patch(&format!("{}{}", prefix, digit))).collect::<Result<_>>()?,
+                minus: minus.map(|name| loader.load_patch(name)).transpose()?
+            }
+        )
+    }
+
+    // STlib_drawNum: right aligned at `x`, keeping the last `digits` digits.
+    fn draw(&self, target: &mut dyn DrawTarget, x: i32, y: i32, digits: usize, number: i32) {
+        let width = self.digits[0].width as i32;
+        let negative = number < 0;
+        let mut number = match digits {
+            2 if negative => number.max(-9).abs(),
+            3 if negative => number.max(-99).abs(),
+            _ => number.abs()
+        };
+
+        let mut x = x;
+
+        if number == 0 {
+            target.draw_patch(x - width, y, &self.digits[0]);
+        }
+
+        for _ in 0..digits {
+            if number == 0 {
+                break;
+            }
+
+            x -= width;
+            target.draw_patch(x, y, &self.digits[(number % 10) as usize]);
+            number /= 10;
+        }
+
+        if let (true, Some(minus)) = (negative, &self.minus) {
+            target.draw_patch(x - 8, y, minus);
+        }
+    }
+}
+
+// st_stuff.c: the bar along the bottom of the screen with the player's numbers and face.
+pub struct StatusBar {
+    background: Patch,
+    arms_background: Patch,
+    tall_numbers: Font,
+    percent: Patch,
+    yellow_numbers: Font,
+    gray_numbers: Font,
+    keys: Vec<Patch>,
+    faces: Vec<Patch>,
+    face: Face
+}
+
+impl StatusBar {
+    // ST_loadGraphics
+    pub fn load(loader: &mut Loader, player: &Player) -> Result<Self> {
+        let mut face_names: Vec<String> = Vec::with_capacity(FACE_COUNT);
+
+        for pain in 0..PAIN_FACES {
+            face_names.extend((0..STRAIGHT_FACES).map(|straight| format!("STFST{}{}", pain, straight)));
+            face_names.push(format!("STFTR{}0", pain));
+            face_names.push(format!("STFTL{}0", pain));
+            face_names.push(format!("STFOUCH{}", pain));
+            face_names.push(format!("STFEVL{}", pain));
+            face_names.push(format!("STFKILL{}", pain));
+        }
+
+        face_names.push("STFGOD0".to_string());
+        face_names.push("STFDEAD0".to_string());
+
+        Ok(
+            Self {
+                background: loader.load_patch("STBAR")?,
+                arms_background: loader.load_patch("STARMS")?,
+                tall_numbers: Font::load(loader, "STTNUM", Some("STTMINUS"))?,
+                percent: loader.load_patch("STTPRCNT")?,
+                yellow_numbers: Font::load(loader, "STYSNUM", None)?,
+                gray_numbers: Font::load(loader, "STGNUM", None)?,
+                keys: (0..Card::COUNT).map(|i| loader.load_patch(&format!("STKEYS{}", i))).collect::<Result<_>>()?,
+                faces: face_names.iter().map(|name| loader.load_patch(name)).collect::<Result<_>>()?,
+                face: Face::new(player)
+            }
+        )
+    }
+
+    // ST_Start: a new level starts with a fresh face.
+    pub fn start(&mut self, player: &Player) {
+        self.face = Face::new(player);
+    }
+
+    // ST_Ticker
+    pub fn tick(&mut self, player: &Player, random: &mut Random) {
+        let random = random.random();
+        self.face.tick(player, random);
+    }
+
+    // ST_Drawer, with the sides tiled with `border` when the screen is wider than 320.
+    pub fn draw(&self, player: &Player, border: Option<&Flat>, target: &mut dyn DrawTarget) {
+        let scale = target.base_scale();
+        let (origin_x, origin_y) = target.base_origin();
+
+        if let (true, Some(border)) = (origin_x > 0, border) {
+            let (top, bottom) = (origin_y + STATUS_BAR_Y * scale, origin_y + BASE_HEIGHT as i32 * scale - 1);
+            let width = target.width();
+
+            target.tile_flat(0, top, origin_x - 1, bottom, border);
+            target.tile_flat(origin_x + BASE_WIDTH as i32 * scale, top, width - 1, bottom, border);
+        }
+
+        target.draw_patch(0, STATUS_BAR_Y, &self.background);
+        target.draw_patch(ARMS_BACKGROUND_X, ARMS_BACKGROUND_Y, &self.arms_background);
+
+        if let Some(ammo) = player.ready_weapon.ammo() {
+            self.tall_numbers.draw(target, AMMO_X, BIG_NUMBER_Y, 3, player.ammo(ammo));
+        }
+
+        self.tall_numbers.draw(target, HEALTH_X, BIG_NUMBER_Y, 3, player.health);
+        target.draw_patch(HEALTH_X, BIG_NUMBER_Y, &self.percent);
+
+        self.tall_numbers.draw(target, ARMOR_X, BIG_NUMBER_Y, 3, player.armor_points);
+        target.draw_patch(ARMOR_X, BIG_NUMBER_Y, &self.percent);
+
+        // Weapon slots 2 to 7, lit up once owned.
+        for i in 0..6 {
+            let font = if player.weapons[i + 1] { &self.yellow_numbers } else { &self.gray_numbers };
+            let (x, y) = (ARMS_X + (i as i32 % 3) * ARMS_X_SPACE, ARMS_Y + (i as i32 / 3) * ARMS_Y_SPACE);
+
+            target.draw_patch(x, y, &font.digits[i + 2]);
+        }
+
+        target.draw_patch(FACE_X, FACE_Y, &self.faces[self.face.index]);
+
+        // A skull key shows instead of the card of the same colour.
+        for (i, &y) in KEYS_Y.iter().enumerate() {
+            let key = if player.cards[i + 3] {
+                Some(i + 3)
+            } else {
+                player.cards[i].then_some(i)
+            };
+
+            if let Some(key) = key {
+                target.draw_patch(KEYS_X, y, &self.keys[key]);
+            }
+        }
+
+        for (ammo, &y) in AmmoType::ALL.iter().zip(&AMMO_COUNT_Y) {
+            self.yellow_numbers.draw(target, AMMO_COUNT_X, y, 3, player.ammo(*ammo));
+            self.yellow_numbers.draw(target, MAX_AMMO_X, y, 3, player.max_ammo(*ammo));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::FrameBuffer;
+    use crate::wad::Post;
+
+    const DIGIT_WIDTH: i32 = 4;
+    const MINUS_COLOR: u8 = 11;
+
+    // A one row patch of a single color.
+    fn patch(width: i32, color: u8) -> Patch {
+        Patch {
+            name: String::new(),
+            width: width as i16,
+            height: 1,
+            left_offset: 0,
+            top_offset: 0,
+            columns: (0..width).map(|_| vec![Post { top_delta: 0, pixels: vec![color] }]).collect()
+        }
+    }
+
+    // Digit n is drawn in color n + 1, so the row reads back as the number.
+    fn font() -> Font {
+        Font { digits: (0..10).map(|digit| patch(DIGIT_WIDTH, digit + 1)).collect(), minus: Some(patch(1, MINUS_COLOR)) }
+    }
+
+    // What is on the row, one character a patch, from where a minus would go up to `x`.
+    fn drawn(digits: usize, number: i32) -> String {
+        let mut frame = FrameBuffer::new(BASE_WIDTH, BASE_HEIGHT);
+        let x = 100;
+
+        font().draw(&mut frame, x, 10, digits, number);
+
+        let mut row = vec![0; 24];
+        frame.read_row(x - row.len() as i32, 10, &mut row);
+
+        row.iter()
+            .enumerate()
+            .filter(|&(i, &color)| color != 0 && (color == MINUS_COLOR || i % DIGIT_WIDTH as usize == 0))
+            .map(|(_, &color)| if color == MINUS_COLOR { '-' } else { (b'0' + color - 1) as char })
+            .collect()
+    }
+
+    #[test]
+    fn numbers_keep_their_last_digits() {
+        assert_eq!(drawn(3, 0), "0");
+        assert_eq!(drawn(3, 42), "42");
+        assert_eq!(drawn(3, 1234), "234");
+    }
+
+    #[test]
+    fn negative_numbers_are_clamped() {
+        assert_eq!(drawn(2, -5), "-5");
+        assert_eq!(drawn(2, -123), "-9");
+        assert_eq!(drawn(3, -123), "-99");
+        // Only two and three digit widgets clamp, like STlib_drawNum.
+        assert_eq!(drawn(4, -1234), "-1234");
+    }
+}
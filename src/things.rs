@@ -0,0 +1,314 @@
+use serde::Serialize;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AmmoType {
+    Bullets,
+    Shells,
+    Rockets,
+    Cells
+}
+
+impl AmmoType {
+    pub const ALL: [AmmoType; 4] = [AmmoType::Bullets, AmmoType::Shells, AmmoType::Rockets, AmmoType::Cells];
+
+    pub fn clip_amount(&self) -> u32 {
+        match self {
+            AmmoType::Bullets => 10,
+            AmmoType::Shells => 4,
+            AmmoType::Rockets => 1,
+            AmmoType::Cells => 20
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ThingKind {
+    Monster { health: u32 },
+    Weapon { ammo: Option<AmmoType> },
+    Ammo { ammo: AmmoType, amount: u32 },
+    Backpack,
+    Health,
+    Armor,
+    Powerup,
+    Key
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct ThingInfo {
+    pub ed_type: i16,
+    pub name: &'static str,
+    pub kind: ThingKind,
+    // The mobjinfo flags the intermission tallies go by.
+    pub flags: u32
+}
+
+impl ThingInfo {
+    pub const COUNT_KILL: u32 = 0x400000;
+    pub const COUNT_ITEM: u32 = 0x800000;
+
+    // MF_COUNTKILL: counted towards the kill percentage.
+    pub fn counts_kill(&self) -> bool {
+        self.flags & Self::COUNT_KILL != 0
+    }
+
+    // MF_COUNTITEM: counted towards the item percentage.
+    pub fn is_item(&self) -> bool {
+        self.flags & Self::COUNT_ITEM != 0
+    }
+
+    pub fn spawns_in_deathmatch(&self) -> bool {
+        self.kind != ThingKind::Key
+    }
+}
+
+const fn monster(ed_type: i16, name: &'static str, health: u32) -> ThingInfo {
+    ThingInfo { ed_type, name, kind: ThingKind::Monster { health }, flags: ThingInfo::COUNT_KILL }
+}
+
+// Lost souls and the boss brain, which the kill count leaves out.
+const fn uncounted_monster(ed_type: i16, name: &'static str, health: u32) -> ThingInfo {
+    ThingInfo { ed_type, name, kind: ThingKind::Monster { health }, flags: 0 }
+}
+
+const fn weapon(ed_type: i16, name: &'static str, ammo: Option<AmmoType>) -> ThingInfo {
+    ThingInfo { ed_type, name, kind: ThingKind::Weapon { ammo }, flags: 0 }
+}
+
+const fn ammo(ed_type: i16, name: &'static str, ammo: AmmoType, amount: u32) -> ThingInfo {
+    ThingInfo { ed_type, name, kind: ThingKind::Ammo { ammo, amount }, flags: 0 }
+}
+
+const fn item(ed_type: i16, name: &'static str, kind: ThingKind) -> ThingInfo {
+    ThingInfo { ed_type, name, kind, flags: 0 }
+}
+
+// The bonuses and powerups that count towards the item percentage.
+const fn counted_item(ed_type: i16, name: &'static str, kind: ThingKind) -> ThingInfo {
+    ThingInfo { ed_type, name, kind, flags: ThingInfo::COUNT_ITEM }
+}
+
+pub const THING_INFO: [ThingInfo; 56] = [
+    monster(3004, "Zombieman", 20),
+    monster(9, "Shotgun guy", 30),
+    monster(65, "Heavy weapon dude", 70),
+    monster(84, "Wolfenstein SS", 50),
+    monster(3001, "Imp", 60),
+    monster(3002, "Demon", 150),
+    monster(58, "Spectre", 150),
+    uncounted_monster(3006, "Lost soul", 100),
+    monster(3005, "Cacodemon", 400),
+    monster(71, "Pain elemental", 400),
+    monster(66, "Revenant", 300),
+    monster(69, "Hell knight", 500),
+    monster(3003, "Baron of Hell", 1000),
+    monster(68, "Arachnotron", 500),
+    monster(67, "Mancubus", 600),
+    monster(64, "Arch-vile", 700),
+    monster(7, "Spider Mastermind", 3000),
+    monster(16, "Cyberdemon", 4000),
+    monster(72, "Commander Keen", 100),
+    uncounted_monster(88, "Boss brain", 250),
+
+    weapon(2005, "Chainsaw", None),
+    weapon(2001, "Shotgun", Some(AmmoType::Shells)),
+    weapon(82, "Super shotgun", Some(AmmoType::Shells)),
+    weapon(2002, "Chaingun", Some(AmmoType::Bullets)),
+    weapon(2003, "Rocket launcher", Some(AmmoType::Rockets)),
+    weapon(2004, "Plasma gun", Some(AmmoType::Cells)),
+    weapon(2006, "BFG 9000", Some(AmmoType::Cells)),
+
+    ammo(2007, "Clip", AmmoType::Bullets, 10),
+    ammo(2048, "Box of bullets", AmmoType::Bullets, 50),
+    ammo(2008, "Shells", AmmoType::Shells, 4),
+    ammo(2049, "Box of shells", AmmoType::Shells, 20),
+    ammo(2010, "Rocket", AmmoType::Rockets, 1),
+    ammo(2046, "Box of rockets", AmmoType::Rockets, 5),
+    ammo(2047, "Energy cell", AmmoType::Cells, 20),
+    ammo(17, "Energy cell pack", AmmoType::Cells, 100),
+    item(8, "Backpack", ThingKind::Backpack),
+
+    item(2011, "Stimpack", ThingKind::Health),
+    item(2012, "Medikit", ThingKind::Health),
+    counted_item(2014, "Health bonus", ThingKind::Health),
+    counted_item(2013, "Soulsphere", ThingKind::Health),
+    counted_item(83, "Megasphere", ThingKind::Health),
+    counted_item(2015, "Armor bonus", ThingKind::Armor),
+    item(2018, "Green armor", ThingKind::Armor),
+    item(2019, "Blue armor", ThingKind::Armor),
+
+    counted_item(2022, "Invulnerability", ThingKind::Powerup),
+    counted_item(2023, "Berserk", ThingKind::Powerup),
+    counted_item(2024, "Partial invisibility", ThingKind::Powerup),
+    item(2025, "Radiation suit", ThingKind::Powerup),
+    counted_item(2026, "Computer area map", ThingKind::Powerup),
+    counted_item(2045, "Light amplification visor", ThingKind::Powerup),
+
+    item(5, "Blue keycard", ThingKind::Key),
+    item(40, "Blue skull key", ThingKind::Key),
+    item(13, "Red keycard", ThingKind::Key),
+    item(38, "Red skull key", ThingKind::Key),
+    item(6, "Yellow keycard", ThingKind::Key),
+    item(39, "Yellow skull key", ThingKind::Key)
+];
+
+pub fn thing_info(ed_type: i16) -> Option<&'static ThingInfo> {
+    THING_INFO.iter().find(|info| info.ed_type == ed_type)
+}
+
+// The spawn frame of each thing as the renderer draws it.
+#[derive(Debug, Copy, Clone)]
+pub struct ThingSprite {
+    pub ed_type: i16,
+    pub sprite: &'static str,
+    pub frame: char,
+    pub full_bright: bool,
+    pub shadow: bool,
+    pub hanging_height: Option<i16>
+}
+
+const fn sprite(ed_type: i16, sprite: &'static str, frame: char) -> ThingSprite {
+    ThingSprite { ed_type, sprite, frame, full_bright: false, shadow: false, hanging_height: None }
+}
+
+const fn bright(ed_type: i16, sprite: &'static str, frame: char) -> ThingSprite {
+    ThingSprite { ed_type, sprite, frame, full_bright: true, shadow: false, hanging_height: None }
+}
+
+const fn shadow(ed_type: i16, sprite: &'static str, frame: char) -> ThingSprite {
+    ThingSprite { ed_type, sprite, frame, full_bright: false, shadow: true, hanging_height: None }
+}
+
+const fn hanging(ed_type: i16, sprite: &'static str, frame: char, height: i16) -> ThingSprite {
+    ThingSprite { ed_type, sprite, frame, full_bright: false, shadow: false, hanging_height: Some(height) }
+}
+
+pub const THING_SPRITES: [ThingSprite; 115] = [
+    sprite(3004, "POSS", 'A'),
+    sprite(9, "SPOS", 'A'),
+    sprite(65, "CPOS", 'A'),
+    sprite(84, "SSWV", 'A'),
+    sprite(3001, "TROO", 'A'),
+    sprite(3002, "SARG", 'A'),
+    shadow(58, "SARG", 'A'),
+    bright(3006, "SKUL", 'A'),
+    sprite(3005, "HEAD", 'A'),
+    sprite(71, "PAIN", 'A'),
+    sprite(66, "SKEL", 'A'),
+    sprite(69, "BOS2", 'A'),
+    sprite(3003, "BOSS", 'A'),
+    sprite(68, "BSPI", 'A'),
+    sprite(67, "FATT", 'A'),
+    sprite(64, "VILE", 'A'),
+    sprite(7, "SPID", 'A'),
+    sprite(16, "CYBR", 'A'),
+    hanging(72, "KEEN", 'A', 72),
+    sprite(88, "BBRN", 'A'),
+
+    sprite(2005, "CSAW", 'A'),
+    sprite(2001, "SHOT", 'A'),
+    sprite(82, "SGN2", 'A'),
+    sprite(2002, "MGUN", 'A'),
+    sprite(2003, "LAUN", 'A'),
+    sprite(2004, "PLAS", 'A'),
+    sprite(2006, "BFUG", 'A'),
+
+    sprite(2007, "CLIP", 'A'),
+    sprite(2048, "AMMO", 'A'),
+    sprite(2008, "SHEL", 'A'),
+    sprite(2049, "SBOX", 'A'),
+    sprite(2010, "ROCK", 'A'),
+    sprite(2046, "BROK", 'A'),
+    sprite(2047, "CELL", 'A'),
+    sprite(17, "CELP", 'A'),
+    sprite(8, "BPAK", 'A'),
+
+    sprite(2011, "STIM", 'A'),
+    sprite(2012, "MEDI", 'A'),
+    sprite(2014, "BON1", 'A'),
+    bright(2013, "SOUL", 'A'),
+    bright(83, "MEGA", 'A'),
+    sprite(2015, "BON2", 'A'),
+    sprite(2018, "ARM1", 'A'),
+    sprite(2019, "ARM2", 'A'),
+
+    bright(2022, "PINV", 'A'),
+    bright(2023, "PSTR", 'A'),
+    bright(2024, "PINS", 'A'),
+    bright(2025, "SUIT", 'A'),
+    bright(2026, "PMAP", 'A'),
+    bright(2045, "PVIS", 'A'),
+
+    sprite(5, "BKEY", 'A'),
+    sprite(40, "BSKU", 'A'),
+    sprite(13, "RKEY", 'A'),
+    sprite(38, "RSKU", 'A'),
+    sprite(6, "YKEY", 'A'),
+    sprite(39, "YSKU", 'A'),
+
+    sprite(2035, "BAR1", 'A'),
+    bright(34, "CAND", 'A'),
+    bright(35, "CBRA", 'A'),
+    bright(44, "TBLU", 'A'),
+    bright(45, "TGRN", 'A'),
+    bright(46, "TRED", 'A'),
+    bright(55, "SMBT", 'A'),
+    bright(56, "SMGT", 'A'),
+    bright(57, "SMRT", 'A'),
+    bright(2028, "COLU", 'A'),
+    bright(85, "TLMP", 'A'),
+    bright(86, "TLP2", 'A'),
+    bright(70, "FCAN", 'A'),
+    sprite(48, "ELEC", 'A'),
+    sprite(30, "COL1", 'A'),
+    sprite(31, "COL2", 'A'),
+    sprite(32, "COL3", 'A'),
+    sprite(33, "COL4", 'A'),
+    sprite(36, "COL5", 'A'),
+    sprite(37, "COL6", 'A'),
+    bright(41, "CEYE", 'A'),
+    bright(42, "FSKU", 'A'),
+    sprite(43, "TRE1", 'A'),
+    sprite(47, "SMIT", 'A'),
+    sprite(54, "TRE2", 'A'),
+
+    sprite(10, "PLAY", 'W'),
+    sprite(12, "PLAY", 'W'),
+    sprite(15, "PLAY", 'N'),
+    sprite(18, "POSS", 'L'),
+    sprite(19, "SPOS", 'L'),
+    sprite(20, "TROO", 'M'),
+    sprite(21, "SARG", 'N'),
+    sprite(22, "HEAD", 'L'),
+    sprite(23, "SKUL", 'K'),
+    sprite(24, "POL5", 'A'),
+    sprite(79, "POB1", 'A'),
+    sprite(80, "POB2", 'A'),
+    sprite(81, "BRS1", 'A'),
+    sprite(25, "POL1", 'A'),
+    sprite(26, "POL6", 'A'),
+    sprite(27, "POL4", 'A'),
+    sprite(28, "POL2", 'A'),
+    bright(29, "POL3", 'A'),
+
+    hanging(49, "GOR1", 'A', 68),
+    hanging(50, "GOR2", 'A', 84),
+    hanging(51, "GOR3", 'A', 84),
+    hanging(52, "GOR4", 'A', 68),
+    hanging(53, "GOR5", 'A', 52),
+    hanging(59, "GOR2", 'A', 84),
+    hanging(60, "GOR4", 'A', 68),
+    hanging(61, "GOR3", 'A', 52),
+    hanging(62, "GOR5", 'A', 52),
+    hanging(63, "GOR1", 'A', 68),
+    hanging(73, "HDB1", 'A', 88),
+    hanging(74, "HDB2", 'A', 88),
+    hanging(75, "HDB3", 'A', 64),
+    hanging(76, "HDB4", 'A', 64),
+    hanging(77, "HDB5", 'A', 64),
+    hanging(78, "HDB6", 'A', 64)
+];
+
+pub fn thing_sprite(ed_type: i16) -> Option<&'static ThingSprite> {
+    THING_SPRITES.iter().find(|sprite| sprite.ed_type == ed_type)
+}
@@ -43,6 +43,53 @@ pub struct Linedef {
     pub back_sidedef_id: u16
 }
 
+impl Linedef {
+    pub const BLOCKING: u16 = 0x0001;
+    pub const BLOCK_MONSTERS: u16 = 0x0002;
+    pub const TWO_SIDED: u16 = 0x0004;
+    pub const UPPER_UNPEGGED: u16 = 0x0008;
+    pub const LOWER_UNPEGGED: u16 = 0x0010;
+    pub const SECRET: u16 = 0x0020;
+    pub const BLOCK_SOUND: u16 = 0x0040;
+    pub const NOT_ON_MAP: u16 = 0x0080;
+    pub const ALREADY_ON_MAP: u16 = 0x0100;
+
+    pub const NO_SIDEDEF: u16 = 0xFFFF;
+
+    pub fn has_flag(&self, flag: u16) -> bool {
+        self.flags & flag != 0
+    }
+
+    pub fn has_back_sidedef(&self) -> bool {
+        self.back_sidedef_id != Self::NO_SIDEDEF
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Sidedef {
+    pub x_offset: i16,
+    pub y_offset: i16,
+    pub upper_texture: String,
+    pub lower_texture: String,
+    pub middle_texture: String,
+    pub sector_id: u16
+}
+
+impl Sidedef {
+    pub const NO_TEXTURE: &'static str = "-";
+}
+
+#[derive(Debug, Clone)]
+pub struct Sector {
+    pub floor_height: i16,
+    pub ceiling_height: i16,
+    pub floor_texture: String,
+    pub ceiling_texture: String,
+    pub light_level: i16,
+    pub special: i16,
+    pub tag: u16
+}
+
 #[derive(Debug, Default, Copy, Clone)]
 pub struct Point {
     pub x: i16,
@@ -57,6 +104,18 @@ pub struct Thing {
     pub flags: i16
 }
 
+impl Thing {
+    pub const SKILL_EASY: i16 = 0x0001;
+    pub const SKILL_MEDIUM: i16 = 0x0002;
+    pub const SKILL_HARD: i16 = 0x0004;
+    pub const AMBUSH: i16 = 0x0008;
+    pub const NOT_SINGLE_PLAYER: i16 = 0x0010;
+
+    pub fn has_flag(&self, flag: i16) -> bool {
+        self.flags & flag != 0
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct Seg {
     pub start_vertex_id: i16,
@@ -91,4 +150,45 @@ pub struct BoundingBox {
     pub bottom: i16,
     pub left: i16,
     pub right: i16
+}
+
+#[derive(Debug, Clone)]
+pub struct TextureDef {
+    pub name: String,
+    pub width: i16,
+    pub height: i16,
+    pub patches: Vec<TexturePatch>
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct TexturePatch {
+    pub origin_x: i16,
+    pub origin_y: i16,
+    pub patch_id: i16
+}
+
+#[derive(Debug, Clone)]
+pub struct Post {
+    pub top_delta: u8,
+    pub pixels: Vec<u8>
+}
+
+#[derive(Debug, Clone)]
+pub struct Patch {
+    pub name: String,
+    pub width: i16,
+    pub height: i16,
+    pub left_offset: i16,
+    pub top_offset: i16,
+    pub columns: Vec<Vec<Post>>
+}
+
+pub type Palette = [[u8; 3]; 256];
+
+pub type Colormap = [u8; 256];
+
+#[derive(Debug, Clone)]
+pub struct Flat {
+    pub name: String,
+    pub pixels: Vec<u8>
 }
\ No newline at end of file
@@ -1,7 +1,8 @@
 use std::path::PathBuf;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use crate::map::GameMap;
-use crate::wad::{Linedef, LumpIndices, Node, Seg, SubSector, Thing, Vertex};
+use crate::math::Bam;
+use crate::wad::{Colormap, Flat, Linedef, LumpIndices, Node, Palette, Patch, Point, Post, Sector, Seg, Sidedef, SubSector, TextureDef, Thing, Vertex};
 use crate::wad::reader::{Reader, ReadFromBytes, ReadLumpData};
 
 pub struct Loader {
@@ -19,8 +20,188 @@ impl Loader {
         )
     }
 
+    pub fn map_names(&self) -> Vec<String> {
+        let directory = &self.reader.directory;
+
+        directory.iter()
+            .enumerate()
+            .filter(|(i, _)| {
+                directory.get(i + LumpIndices::THINGS as usize)
+                    .is_some_and(|lump| lump.name == "THINGS")
+            })
+            .map(|(_, lump)| lump.name.clone())
+            .collect()
+    }
+
+    pub fn load_texture_names(&mut self) -> Result<Vec<String>> {
+        let textures = self.load_texture_defs()?;
+
+        Ok(textures.into_iter().map(|texture| texture.name).collect())
+    }
+
+    pub fn load_texture_defs(&mut self) -> Result<Vec<TextureDef>> {
+        let mut textures: Vec<TextureDef> = Vec::new();
+
+        for lump_name in ["TEXTURE1", "TEXTURE2"] {
+            let Some(lump_index) = self.reader.get_lump_index(lump_name) else {
+                continue;
+            };
+
+            let lump_offset = self.reader.directory[lump_index].offset;
+            let num_textures: usize = self.reader.read(lump_offset, 4)?;
+
+            for i in 0..num_textures {
+                let texture_offset: usize = self.reader.read(lump_offset + 4 + i * 4, 4)?;
+                textures.push(self.reader.read(lump_offset + texture_offset, 22)?);
+            }
+        }
+
+        Ok(textures)
+    }
+
+    pub fn load_patch_names(&mut self) -> Result<Vec<String>> {
+        let lump_index = self.reader.get_lump_index("PNAMES")
+            .ok_or_else(|| anyhow!("Lump PNAMES not found in WAD"))?;
+
+        let lump_offset = self.reader.directory[lump_index].offset;
+        let num_patches: usize = self.reader.read(lump_offset, 4)?;
+        let mut names: Vec<String> = Vec::new();
+
+        for i in 0..num_patches {
+            let name: String = self.reader.read(lump_offset + 4 + i * 8, 8)?;
+            names.push(name.to_uppercase());
+        }
+
+        Ok(names)
+    }
+
+    pub fn load_patch(&mut self, patch_name: &str) -> Result<Patch> {
+        let bytes = self.read_lump_bytes(&patch_name.to_uppercase())?;
+        let malformed = || anyhow!("Patch {} is malformed", patch_name);
+
+        let u8_at = |offset: usize| bytes.get(offset).copied().ok_or_else(malformed);
+        let i16_at = |offset: usize| Ok::<_, anyhow::Error>(i16::from_le_bytes([u8_at(offset)?, u8_at(offset + 1)?]));
+        let u32_at = |offset: usize| Ok::<_, anyhow::Error>(u32::from_le_bytes([
+            u8_at(offset)?, u8_at(offset + 1)?, u8_at(offset + 2)?, u8_at(offset + 3)?
+        ]));
+
+        let width = i16_at(0)?;
+        let mut columns: Vec<Vec<Post>> = Vec::new();
+
+        for x in 0..width.max(0) as usize {
+            let mut offset = u32_at(8 + x * 4)? as usize;
+            let mut posts: Vec<Post> = Vec::new();
+
+            loop {
+                let top_delta = u8_at(offset)?;
+
+                if top_delta == 0xff {
+                    break;
+                }
+
+                let length = u8_at(offset + 1)? as usize;
+                let pixels = bytes.get(offset + 3..offset + 3 + length).ok_or_else(malformed)?;
+
+                posts.push(Post { top_delta, pixels: pixels.to_vec() });
+                offset += length + 4;
+            }
+
+            columns.push(posts);
+        }
+
+        Ok(
+            Patch {
+                name: patch_name.to_uppercase(),
+                width,
+                height: i16_at(2)?,
+                left_offset: i16_at(4)?,
+                top_offset: i16_at(6)?,
+                columns
+            }
+        )
+    }
+
+    pub fn read_lump_bytes(&mut self, lump_name: &str) -> Result<Vec<u8>> {
+        let lump_index = self.reader.get_lump_index(lump_name)
+            .ok_or_else(|| anyhow!("Lump {} not found in WAD", lump_name))?;
+        let lump = self.reader.directory[lump_index].clone();
+
+        self.reader.read(lump.offset, lump.size)
+    }
+
+    pub fn load_palettes(&mut self) -> Result<Vec<Palette>> {
+        let bytes = self.read_lump_bytes("PLAYPAL")?;
+
+        let palettes = bytes.chunks_exact(256 * 3)
+            .map(|chunk| {
+                let mut palette: Palette = [[0; 3]; 256];
+
+                for (color, rgb) in palette.iter_mut().zip(chunk.chunks_exact(3)) {
+                    color.copy_from_slice(rgb);
+                }
+
+                palette
+            })
+            .collect();
+
+        Ok(palettes)
+    }
+
+    pub fn load_colormaps(&mut self) -> Result<Vec<Colormap>> {
+        let bytes = self.read_lump_bytes("COLORMAP")?;
+
+        let colormaps = bytes.chunks_exact(256)
+            .map(|chunk| {
+                let mut colormap: Colormap = [0; 256];
+                colormap.copy_from_slice(chunk);
+                colormap
+            })
+            .collect();
+
+        Ok(colormaps)
+    }
+
+    pub fn load_flats(&mut self) -> Result<Vec<Flat>> {
+        let directory = self.reader.directory.clone();
+        let mut flats: Vec<Flat> = Vec::new();
+        let mut in_flats = false;
+
+        for lump in directory {
+            match lump.name.as_str() {
+                "F_START" | "FF_START" => in_flats = true,
+                "F_END" | "FF_END" => in_flats = false,
+                _ if in_flats && lump.size == 64 * 64 => {
+                    flats.push(Flat {
+                        name: lump.name.clone(),
+                        pixels: self.reader.read(lump.offset, lump.size)?
+                    });
+                },
+                _ => {}
+            }
+        }
+
+        Ok(flats)
+    }
+
+    pub fn sprite_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        let mut in_sprites = false;
+
+        for lump in &self.reader.directory {
+            match lump.name.as_str() {
+                "S_START" | "SS_START" => in_sprites = true,
+                "S_END" | "SS_END" => in_sprites = false,
+                _ if in_sprites && lump.size > 0 => names.push(lump.name.clone()),
+                _ => {}
+            }
+        }
+
+        names
+    }
+
     pub fn load_map_data(&mut self, map: &mut GameMap) -> Result<()> {
-        let map_index = self.reader.get_lump_index(&map.map_name).unwrap();
+        let map_index = self.reader.get_lump_index(&map.map_name)
+            .ok_or_else(|| anyhow!("Map {} not found in WAD", map.map_name))?;
 
         map.vertexes = self.reader.read_lump(
             map_index + LumpIndices::VERTEXES as usize,
@@ -36,6 +217,18 @@ impl Loader {
             None
         )?;
 
+        map.sidedefs = self.reader.read_lump(
+            map_index + LumpIndices::SIDEDEFS as usize,
+            30,
+            None
+        )?;
+
+        map.sectors = self.reader.read_lump(
+            map_index + LumpIndices::SECTORS as usize,
+            26,
+            None
+        )?;
+
         map.nodes = self.reader.read_lump(
             map_index + LumpIndices::NODES as usize,
             28,
@@ -60,10 +253,19 @@ impl Loader {
             None
         )?;
 
+        // Only the blockmap header's origin is used, so maps without one grid from their corner.
+        map.blockmap_origin = Point { x: map.bounds.min_x, y: map.bounds.min_y };
+
+        if let Some(lump) = self.reader.directory.get(map_index + LumpIndices::BLOCKMAP as usize).cloned() {
+            if lump.name == "BLOCKMAP" && lump.size >= 4 {
+                map.blockmap_origin = Point { x: self.reader.read(lump.offset, 2)?, y: self.reader.read(lump.offset + 2, 2)? };
+            }
+        }
+
         if map.player.id > 0 {
             if let Some(thing) = map.things.get(map.player.id - 1) {
                 map.player.position = thing.position;
-                map.player.angle = thing.angle;
+                map.player.angle = Bam::from_map_angle(thing.angle);
             }
         }
 
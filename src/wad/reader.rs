@@ -3,7 +3,7 @@ use std::io::{Read, Seek, SeekFrom};
 use std::path::PathBuf;
 use anyhow::Result;
 use sdl2::sys::u_int16_t;
-use crate::wad::{BoundingBox, Header, Linedef, LumpInfo, Node, Point, Seg, SubSector, Thing, Vertex};
+use crate::wad::{BoundingBox, Header, Linedef, LumpInfo, Node, Point, Sector, Seg, Sidedef, SubSector, TextureDef, TexturePatch, Thing, Vertex};
 
 pub struct Reader {
     file: File,
@@ -102,6 +102,12 @@ impl ReadFromBytes<usize> for Reader {
     }
 }
 
+impl ReadFromBytes<Vec<u8>> for Reader {
+    fn read(&mut self, offset: usize, num_bytes: usize) -> Result<Vec<u8>> {
+        self.read_bytes(offset, num_bytes)
+    }
+}
+
 impl ReadFromBytes<String> for Reader {
     fn read(&mut self, offset: usize, num_bytes: usize) -> Result<String> {
         let bytes = self.read_bytes(offset, num_bytes)?;
@@ -136,6 +142,33 @@ impl ReadFromBytes<Linedef> for Reader {
     }
 }
 
+impl ReadFromBytes<Sidedef> for Reader {
+    fn read(&mut self, offset: usize, num_bytes: usize) -> Result<Sidedef> {
+        Ok(Sidedef {
+            x_offset: self.read(offset, 2)?,
+            y_offset: self.read(offset + 2, 2)?,
+            upper_texture: self.read(offset + 4, 8)?,
+            lower_texture: self.read(offset + 12, 8)?,
+            middle_texture: self.read(offset + 20, 8)?,
+            sector_id: self.read(offset + 28, 2)?
+        })
+    }
+}
+
+impl ReadFromBytes<Sector> for Reader {
+    fn read(&mut self, offset: usize, num_bytes: usize) -> Result<Sector> {
+        Ok(Sector {
+            floor_height: self.read(offset, 2)?,
+            ceiling_height: self.read(offset + 2, 2)?,
+            floor_texture: self.read(offset + 4, 8)?,
+            ceiling_texture: self.read(offset + 12, 8)?,
+            light_level: self.read(offset + 20, 2)?,
+            special: self.read(offset + 22, 2)?,
+            tag: self.read(offset + 24, 2)?
+        })
+    }
+}
+
 impl ReadFromBytes<Node> for Reader {
     fn read(&mut self, offset: usize, num_bytes: usize) -> Result<Node> {
         Ok(Node {
@@ -197,6 +230,34 @@ impl ReadFromBytes<Thing> for Reader {
     }
 }
 
+impl ReadFromBytes<TextureDef> for Reader {
+    fn read(&mut self, offset: usize, num_bytes: usize) -> Result<TextureDef> {
+        let patch_count: i16 = self.read(offset + 20, 2)?;
+        let mut patches: Vec<TexturePatch> = Vec::new();
+
+        for i in 0..patch_count.max(0) as usize {
+            patches.push(self.read(offset + 22 + i * 10, 10)?);
+        }
+
+        Ok(TextureDef {
+            name: self.read(offset, 8)?,
+            width: self.read(offset + 12, 2)?,
+            height: self.read(offset + 14, 2)?,
+            patches
+        })
+    }
+}
+
+impl ReadFromBytes<TexturePatch> for Reader {
+    fn read(&mut self, offset: usize, num_bytes: usize) -> Result<TexturePatch> {
+        Ok(TexturePatch {
+            origin_x: self.read(offset, 2)?,
+            origin_y: self.read(offset + 2, 2)?,
+            patch_id: self.read(offset + 4, 2)?
+        })
+    }
+}
+
 pub trait ReadLumpData<T> {
     fn read_lump(&mut self, lump_index: usize, num_bytes: usize, header_length: Option<usize>) -> Result<T>;
 }
@@ -235,6 +296,40 @@ impl ReadLumpData<Vec<Linedef>> for Reader {
     }
 }
 
+impl ReadLumpData<Vec<Sidedef>> for Reader {
+    fn read_lump(&mut self, lump_index: usize, num_bytes: usize, header_length: Option<usize>) -> Result<Vec<Sidedef>> {
+        let lump_info = self.directory.get(lump_index).unwrap().clone();
+        let mut sidedefs: Vec<Sidedef> = Vec::new();
+
+        let total_count = lump_info.size / num_bytes;
+
+        for i in 0..total_count {
+            let offset = lump_info.offset + i * num_bytes + header_length.unwrap_or_default();
+            let sidedef: Sidedef = self.read(offset, 30)?;
+            sidedefs.push(sidedef);
+        }
+
+        Ok(sidedefs)
+    }
+}
+
+impl ReadLumpData<Vec<Sector>> for Reader {
+    fn read_lump(&mut self, lump_index: usize, num_bytes: usize, header_length: Option<usize>) -> Result<Vec<Sector>> {
+        let lump_info = self.directory.get(lump_index).unwrap().clone();
+        let mut sectors: Vec<Sector> = Vec::new();
+
+        let total_count = lump_info.size / num_bytes;
+
+        for i in 0..total_count {
+            let offset = lump_info.offset + i * num_bytes + header_length.unwrap_or_default();
+            let sector: Sector = self.read(offset, 26)?;
+            sectors.push(sector);
+        }
+
+        Ok(sectors)
+    }
+}
+
 impl ReadLumpData<Vec<Node>> for Reader {
     fn read_lump(&mut self, lump_index: usize, num_bytes: usize, header_length: Option<usize>) -> Result<Vec<Node>> {
         let lump_info = self.directory.get(lump_index).unwrap().clone();
@@ -0,0 +1,159 @@
+use crate::backend::{DrawTarget, FrameBuffer, BASE_HEIGHT};
+use crate::math::Random;
+
+// f_wipe.c's melt: the old screen slides down column by column, uncovering the new one.
+// Vanilla moves 2 pixel wide columns of the 320x200 screen, larger screens move wider
+// columns by the same number of rows so the wipe looks and lasts the same.
+pub struct Melt {
+    start: FrameBuffer,
+    end: FrameBuffer,
+    scale: i32,
+    height: i32,
+    offsets: Vec<i32>
+}
+
+impl Melt {
+    // wipe_initMelt: each column starts up to 15 rows late, no more than a row apart from
+    // its neighbour.
+    pub fn new(start: FrameBuffer, end: FrameBuffer, random: &mut Random) -> Self {
+        let scale = (start.height() / BASE_HEIGHT as i32).max(1);
+        let height = (start.height() + scale - 1) / scale;
+        let columns = (start.width() + 2 * scale - 1) / (2 * scale);
+
+        let mut offsets = vec![0; columns.max(1) as usize];
+        offsets[0] = -(random.random() % 16);
+
+        for i in 1..offsets.len() {
+            let step = random.random() % 3 - 1;
+            offsets[i] = (offsets[i - 1] + step).clamp(-15, 0);
+        }
+
+        Self {
+            start,
+            end,
+            scale,
+            height,
+            offsets
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.offsets.iter().all(|&offset| offset >= self.height)
+    }
+
+    // wipe_doMelt, one tic: columns speed up over their first 16 rows, then fall 8 a tic.
+    pub fn tick(&mut self) {
+        for offset in &mut self.offsets {
+            if *offset < 0 {
+                *offset += 1;
+            } else if *offset < self.height {
+                let step = if *offset < 16 { *offset + 1 } else { 8 };
+                *offset = (*offset + step).min(self.height);
+            }
+        }
+    }
+
+    pub fn draw(&self, target: &mut dyn DrawTarget) {
+        let column_width = 2 * self.scale;
+
+        for x in 0..self.start.width() {
+            let shift = self.offsets[(x / column_width) as usize].max(0) * self.scale;
+
+            for y in 0..self.start.height() {
+                let pixel = if y < shift { self.end.pixel(x, y) } else { self.start.pixel(x, y - shift) };
+                target.set_pixel(x, y, pixel.unwrap_or(0));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tics for a column that starts on time to cover 200 rows: 5 speeding up to 31, then
+    // 22 of 8.
+    const MELT_TICS: i32 = 27;
+
+    // A Random `seed` numbers into the table.
+    fn random(seed: usize) -> Random {
+        let mut random = Random::new();
+
+        for _ in 0..seed {
+            random.random();
+        }
+
+        random
+    }
+
+    // Each row of the start frame is its row number, the end frame is all 255.
+    fn melt(width: u32, height: u32, seed: usize) -> Melt {
+        let mut start = FrameBuffer::new(width, height);
+        let mut end = FrameBuffer::new(width, height);
+
+        for y in 0..height as i32 {
+            start.write_row(0, y, &vec![y as u8; width as usize]);
+        }
+
+        end.clear(255);
+        Melt::new(start, end, &mut random(seed))
+    }
+
+    #[test]
+    fn columns_start_close_to_their_neighbours() {
+        for seed in 0..256 {
+            let melt = melt(320, 200, seed);
+
+            assert_eq!(melt.offsets.len(), 160);
+            assert!(melt.offsets.iter().all(|offset| (-15..=0).contains(offset)));
+            assert!(melt.offsets.windows(2).all(|pair| (pair[0] - pair[1]).abs() <= 1));
+        }
+    }
+
+    #[test]
+    fn melts_finish_in_vanilla_time() {
+        for seed in [0, 17, 100, 255] {
+            let mut melt = melt(320, 200, seed);
+            let latest = -melt.offsets.iter().min().unwrap();
+
+            for _ in 0..MELT_TICS + latest - 1 {
+                melt.tick();
+                assert!(!melt.is_done());
+            }
+
+            melt.tick();
+            assert!(melt.is_done());
+        }
+    }
+
+    #[test]
+    fn the_end_frame_shows_above_the_shift() {
+        let mut melt = melt(320, 200, 0);
+        melt.offsets.fill(0);
+        (melt.offsets[0], melt.offsets[1]) = (10, -5);
+
+        let mut frame = FrameBuffer::new(320, 200);
+        melt.draw(&mut frame);
+
+        let column = |x: i32, rows: &[i32]| rows.iter().map(|&y| frame.pixel(x, y).unwrap()).collect::<Vec<_>>();
+
+        assert_eq!(column(0, &[0, 9, 10, 50, 199]), [255, 255, 0, 40, 189]);
+        assert_eq!(column(1, &[9, 10]), [255, 0]);
+        // Columns still waiting haven't moved.
+        assert_eq!(column(2, &[0, 50]), [0, 50]);
+    }
+
+    #[test]
+    fn larger_screens_melt_wider_columns_further() {
+        let mut melt = melt(640, 400, 0);
+        assert_eq!(melt.offsets.len(), 160);
+
+        melt.offsets.fill(0);
+        melt.offsets[0] = 10;
+
+        let mut frame = FrameBuffer::new(640, 400);
+        melt.draw(&mut frame);
+
+        assert_eq!([frame.pixel(3, 19), frame.pixel(3, 20), frame.pixel(4, 19)], [Some(255), Some(0), Some(19)]);
+    }
+}